@@ -0,0 +1,288 @@
+//! `#[derive(Prompt)]` for promptt. Use it through promptt's `derive` feature.
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{Data, DeriveInput, Expr, Fields, LitStr, UnOp, parse_macro_input};
+
+/// Derives a questionnaire for a struct, or `PromptField` for an enum of unit variants.
+///
+/// Struct fields take `#[prompt(message = "..", initial = .., min = .., max = .., password,
+/// prefill, hint = "..")]`; enum variants take `#[prompt(title = "..")]`.
+#[proc_macro_derive(Prompt, attributes(prompt))]
+pub fn derive_prompt(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+fn expand(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => expand_struct(input, fields.named.iter()),
+            _ => Err(syn::Error::new_spanned(
+                &input.ident,
+                "#[derive(Prompt)] needs a struct with named fields",
+            )),
+        },
+        Data::Enum(data) => expand_enum(input, data.variants.iter()),
+        Data::Union(_) => Err(syn::Error::new_spanned(
+            &input.ident,
+            "#[derive(Prompt)] does not support unions",
+        )),
+    }
+}
+
+/// Options from a field's `#[prompt(..)]` attributes.
+#[derive(Default)]
+struct FieldOptions {
+    message: Option<LitStr>,
+    initial: Option<Expr>,
+    min: Option<Expr>,
+    max: Option<Expr>,
+    password: bool,
+    prefill: bool,
+    hint: Option<LitStr>,
+}
+
+impl FieldOptions {
+    fn parse(attrs: &[syn::Attribute]) -> syn::Result<Self> {
+        let mut opts = FieldOptions::default();
+        for attr in attrs.iter().filter(|a| a.path().is_ident("prompt")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("message") {
+                    opts.message = Some(meta.value()?.parse()?);
+                } else if meta.path.is_ident("initial") {
+                    opts.initial = Some(meta.value()?.parse()?);
+                } else if meta.path.is_ident("min") {
+                    opts.min = Some(meta.value()?.parse()?);
+                } else if meta.path.is_ident("max") {
+                    opts.max = Some(meta.value()?.parse()?);
+                } else if meta.path.is_ident("password") {
+                    opts.password = true;
+                } else if meta.path.is_ident("prefill") {
+                    opts.prefill = true;
+                } else if meta.path.is_ident("hint") {
+                    opts.hint = Some(meta.value()?.parse()?);
+                } else {
+                    return Err(meta.error("unknown prompt attribute"));
+                }
+                Ok(())
+            })?;
+        }
+        Ok(opts)
+    }
+}
+
+fn expand_struct<'a>(
+    input: &DeriveInput,
+    fields: impl Iterator<Item = &'a syn::Field>,
+) -> syn::Result<TokenStream2> {
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let mut questions = Vec::new();
+    let mut values = Vec::new();
+    for field in fields {
+        let field_ident = field.ident.as_ref().expect("named field");
+        let ty = &field.ty;
+        let name = field_ident.to_string().trim_start_matches("r#").to_string();
+        let opts = FieldOptions::parse(&field.attrs)?;
+        let message = match &opts.message {
+            Some(m) => m.value(),
+            None => humanize(&name),
+        };
+        let mut setters = Vec::new();
+        if opts.password {
+            setters.push(quote! {
+                q.type_name = "password".into();
+                q.style = ::promptt::InputStyle::Password;
+            });
+        }
+        if opts.prefill {
+            setters.push(quote!(q.prefill = true;));
+        }
+        if let Some(initial) = &opts.initial {
+            let value = if is_number(initial) {
+                quote!(::promptt::PromptValue::Float((#initial) as f64))
+            } else {
+                quote!(::promptt::PromptValue::from(#initial))
+            };
+            setters.push(quote!(q.set_initial(Some(#value));));
+        }
+        if let Some(min) = &opts.min {
+            setters.push(quote!(q.min = Some((#min) as f64);));
+        }
+        if let Some(max) = &opts.max {
+            setters.push(quote!(q.max = Some((#max) as f64);));
+        }
+        if let Some(hint) = &opts.hint {
+            setters.push(quote!(q.hint = Some(#hint.into());));
+        }
+        questions.push(quote! {{
+            #[allow(unused_mut)]
+            let mut q = <#ty as ::promptt::PromptField>::question(#name, #message);
+            #(#setters)*
+            q
+        }});
+        values.push(quote! {
+            #field_ident: match answers.get(#name) {
+                Some(value) => <#ty as ::promptt::PromptField>::from_answer(#name, value)?,
+                None => {
+                    return Err(::promptt::Error::Conversion {
+                        name: #name.into(),
+                        message: "no answer".into(),
+                    });
+                }
+            }
+        });
+    }
+    Ok(quote! {
+        impl #impl_generics #ident #ty_generics #where_clause {
+            /// One question per field, in declaration order.
+            pub fn questions() -> ::std::vec::Vec<::promptt::Question> {
+                ::std::vec![#(#questions),*]
+            }
+
+            /// Builds the struct from answers to `questions()`.
+            pub fn from_answers(answers: &::promptt::Answers) -> ::promptt::Result<Self> {
+                Ok(Self { #(#values),* })
+            }
+
+            /// Asks `questions()` on `term` with `promptt::prompt` and builds the struct.
+            pub fn prompt<T: ::promptt::Terminal + ?Sized>(term: &mut T) -> ::promptt::Result<Self> {
+                let answers = ::promptt::prompt(&Self::questions(), term)?;
+                Self::from_answers(&answers)
+            }
+        }
+    })
+}
+
+fn expand_enum<'a>(
+    input: &DeriveInput,
+    variants: impl Iterator<Item = &'a syn::Variant>,
+) -> syn::Result<TokenStream2> {
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let mut choices = Vec::new();
+    let mut arms = Vec::new();
+    for variant in variants {
+        if !matches!(variant.fields, Fields::Unit) {
+            return Err(syn::Error::new_spanned(
+                variant,
+                "#[derive(Prompt)] enums need unit variants",
+            ));
+        }
+        let variant_ident = &variant.ident;
+        let value = variant_ident.to_string();
+        let mut title = value.clone();
+        for attr in variant.attrs.iter().filter(|a| a.path().is_ident("prompt")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("title") {
+                    title = meta.value()?.parse::<LitStr>()?.value();
+                    Ok(())
+                } else {
+                    Err(meta.error("unknown prompt attribute"))
+                }
+            })?;
+        }
+        choices.push(quote!(::promptt::Choice::new(#title, #value)));
+        arms.push(quote!(#value => Ok(Self::#variant_ident),));
+    }
+    Ok(quote! {
+        impl #impl_generics ::promptt::PromptField for #ident #ty_generics #where_clause {
+            fn question(name: &str, message: &str) -> ::promptt::Question {
+                ::promptt::Question::select(name, message)
+                    .choices([#(#choices),*])
+                    .into()
+            }
+
+            fn from_answer(
+                name: &str,
+                value: &::promptt::PromptValue,
+            ) -> ::promptt::Result<Self> {
+                match value {
+                    ::promptt::PromptValue::String(s) => match s.as_str() {
+                        #(#arms)*
+                        _ => Err(::promptt::Error::Conversion {
+                            name: name.into(),
+                            message: format!("'{}' is not one of the choices", s),
+                        }),
+                    },
+                    other => Err(::promptt::Error::Conversion {
+                        name: name.into(),
+                        message: format!("expected a choice, got {:?}", other),
+                    }),
+                }
+            }
+        }
+    })
+}
+
+/// Default message for a field: `db_host` becomes `Db host`.
+fn humanize(name: &str) -> String {
+    let words = name.replace('_', " ");
+    let words = words.trim();
+    let mut chars = words.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+/// Whether `expr` is a (possibly negated) numeric literal.
+fn is_number(expr: &Expr) -> bool {
+    match expr {
+        Expr::Lit(lit) => matches!(lit.lit, syn::Lit::Int(_) | syn::Lit::Float(_)),
+        Expr::Unary(unary) if matches!(unary.op, UnOp::Neg(_)) => is_number(&unary.expr),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn humanize_field_names() {
+        assert_eq!(humanize("db_host"), "Db host");
+        assert_eq!(humanize("port"), "Port");
+        assert_eq!(humanize("_"), "");
+    }
+
+    #[test]
+    fn numeric_literals_are_detected() {
+        assert!(is_number(&syn::parse_quote!(8080)));
+        assert!(is_number(&syn::parse_quote!(-1.5)));
+        assert!(!is_number(&syn::parse_quote!("8080")));
+        assert!(!is_number(&syn::parse_quote!(true)));
+    }
+
+    #[test]
+    fn rejects_tuple_structs_and_data_enums() {
+        let e = expand(&syn::parse_quote!(
+            struct S(String);
+        ))
+        .unwrap_err();
+        assert!(e.to_string().contains("named fields"));
+        let e = expand(&syn::parse_quote!(
+            enum E {
+                A(u8),
+            }
+        ))
+        .unwrap_err();
+        assert!(e.to_string().contains("unit variants"));
+    }
+
+    #[test]
+    fn rejects_unknown_attributes() {
+        let e = expand(&syn::parse_quote!(
+            struct S {
+                #[prompt(colour = "red")]
+                name: String,
+            }
+        ))
+        .unwrap_err();
+        assert!(e.to_string().contains("unknown prompt attribute"));
+    }
+}
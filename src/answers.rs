@@ -0,0 +1,222 @@
+//! Answers collected by `prompt()`, kept in question order.
+
+use crate::prompts::PromptValue;
+use std::ops::Index;
+
+/// Answers by question name, in the order the questions were answered.
+///
+/// Offers the lookups of a `HashMap`; iteration and serialization follow question order.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Answers {
+    entries: Vec<(String, PromptValue)>,
+}
+
+impl Answers {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            entries: Vec::with_capacity(capacity),
+        }
+    }
+
+    /// Returns the answer to the question named `name`.
+    pub fn get(&self, name: &str) -> Option<&PromptValue> {
+        self.entries.iter().find(|(n, _)| n == name).map(|(_, v)| v)
+    }
+
+    /// Returns a mutable reference to the answer to the question named `name`.
+    pub fn get_mut(&mut self, name: &str) -> Option<&mut PromptValue> {
+        self.entries
+            .iter_mut()
+            .find(|(n, _)| n == name)
+            .map(|(_, v)| v)
+    }
+
+    pub fn contains_key(&self, name: &str) -> bool {
+        self.get(name).is_some()
+    }
+
+    /// Stores an answer. Replacing an existing one keeps its position and returns the old value.
+    pub fn insert(&mut self, name: impl Into<String>, value: PromptValue) -> Option<PromptValue> {
+        let name = name.into();
+        match self.get_mut(&name) {
+            Some(old) => Some(std::mem::replace(old, value)),
+            None => {
+                self.entries.push((name, value));
+                None
+            }
+        }
+    }
+
+    /// Removes and returns the answer to the question named `name`.
+    pub fn remove(&mut self, name: &str) -> Option<PromptValue> {
+        let pos = self.entries.iter().position(|(n, _)| n == name)?;
+        Some(self.entries.remove(pos).1)
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Iterates over `(name, value)` pairs in question order.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &PromptValue)> {
+        self.entries.iter().map(|(n, v)| (n.as_str(), v))
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = &str> {
+        self.entries.iter().map(|(n, _)| n.as_str())
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = &PromptValue> {
+        self.entries.iter().map(|(_, v)| v)
+    }
+}
+
+impl Index<&str> for Answers {
+    type Output = PromptValue;
+
+    /// Returns the answer to `name`; panics when there is none.
+    fn index(&self, name: &str) -> &PromptValue {
+        self.get(name)
+            .unwrap_or_else(|| panic!("no answer for '{}'", name))
+    }
+}
+
+impl<K: Into<String>> FromIterator<(K, PromptValue)> for Answers {
+    fn from_iter<I: IntoIterator<Item = (K, PromptValue)>>(iter: I) -> Self {
+        let mut answers = Answers::new();
+        answers.extend(iter);
+        answers
+    }
+}
+
+impl<K: Into<String>> Extend<(K, PromptValue)> for Answers {
+    fn extend<I: IntoIterator<Item = (K, PromptValue)>>(&mut self, iter: I) {
+        for (name, value) in iter {
+            self.insert(name, value);
+        }
+    }
+}
+
+impl<K: Into<String>, const N: usize> From<[(K, PromptValue); N]> for Answers {
+    fn from(entries: [(K, PromptValue); N]) -> Self {
+        entries.into_iter().collect()
+    }
+}
+
+impl IntoIterator for Answers {
+    type Item = (String, PromptValue);
+    type IntoIter = std::vec::IntoIter<(String, PromptValue)>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.entries.into_iter()
+    }
+}
+
+#[cfg(feature = "serde")]
+mod serde_impl {
+    use super::Answers;
+    use crate::prompts::PromptValue;
+    use serde::de::{MapAccess, Visitor};
+    use serde::ser::SerializeMap;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+    use std::fmt;
+
+    impl Serialize for Answers {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            let mut map = serializer.serialize_map(Some(self.len()))?;
+            for (name, value) in self.iter() {
+                map.serialize_entry(name, value)?;
+            }
+            map.end()
+        }
+    }
+
+    impl<'de> Deserialize<'de> for Answers {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            struct AnswersVisitor;
+
+            impl<'de> Visitor<'de> for AnswersVisitor {
+                type Value = Answers;
+
+                fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                    f.write_str("a map of answers")
+                }
+
+                fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Answers, A::Error> {
+                    let mut answers = Answers::with_capacity(map.size_hint().unwrap_or(0));
+                    while let Some((name, value)) = map.next_entry::<String, PromptValue>()? {
+                        answers.insert(name, value);
+                    }
+                    Ok(answers)
+                }
+            }
+
+            deserializer.deserialize_map(AnswersVisitor)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_insertion_order() {
+        let mut a = Answers::new();
+        a.insert("b", PromptValue::Bool(true));
+        a.insert("a", PromptValue::Float(1.0));
+        a.insert("c", "x".into());
+        assert_eq!(a.keys().collect::<Vec<_>>(), ["b", "a", "c"]);
+    }
+
+    #[test]
+    fn insert_replaces_in_place() {
+        let mut a = Answers::from([("x", PromptValue::Bool(false)), ("y", "y".into())]);
+        assert_eq!(
+            a.insert("x", PromptValue::Bool(true)),
+            Some(PromptValue::Bool(false))
+        );
+        assert_eq!(a.len(), 2);
+        assert_eq!(a.keys().next(), Some("x"));
+        assert_eq!(a["x"], PromptValue::Bool(true));
+    }
+
+    #[test]
+    fn lookups_and_remove() {
+        let mut a: Answers = [("name", PromptValue::from("Bob"))].into_iter().collect();
+        assert!(a.contains_key("name"));
+        assert!(a.get("other").is_none());
+        assert_eq!(a.remove("name"), Some("Bob".into()));
+        assert!(a.is_empty());
+        assert_eq!(a.remove("name"), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "no answer for 'missing'")]
+    fn index_panics_on_missing_answer() {
+        let _ = &Answers::new()["missing"];
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serializes_in_question_order() {
+        let a = Answers::from([
+            ("zeta", PromptValue::from("z")),
+            ("alpha", PromptValue::Float(2.0)),
+            ("tags", PromptValue::List(vec!["a".into()])),
+            ("ok", PromptValue::Bool(true)),
+        ]);
+        let json = serde_json::to_string(&a).unwrap();
+        assert_eq!(json, r#"{"zeta":"z","alpha":2.0,"tags":["a"],"ok":true}"#);
+        let back: Answers = serde_json::from_str(&json).unwrap();
+        assert_eq!(back, a);
+    }
+}
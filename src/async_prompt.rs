@@ -0,0 +1,188 @@
+//! Async versions of `prompt`, `prompt_with` and `run_prompt`, for the `tokio` feature.
+//!
+//! Keys are awaited through `AsyncTerminal`, so prompting does not block the runtime. Dropping
+//! a pending future cancels the prompt: it is drawn as aborted and raw mode is restored.
+
+use crate::answers::Answers;
+use crate::elements::{Input, read_async};
+use crate::error::Result;
+use crate::history::History;
+use crate::prompts::{PromptConfig, PromptValue, Question};
+use crate::registry::PromptRegistry;
+use crate::terminal::AsyncTerminal;
+use crate::util::action::{Key, KeyName};
+
+/// `prompt` for async code: runs questions in sequence on `term`.
+pub async fn prompt_async<T: AsyncTerminal + ?Sized>(
+    questions: &[Question],
+    term: &mut T,
+) -> Result<Answers> {
+    let config = PromptConfig {
+        history: Some(History::default()),
+        ..Default::default()
+    };
+    prompt_with_async(questions, term, &config).await
+}
+
+/// `prompt_with` for async code.
+///
+/// Cancelling `config.cancel` aborts the question being asked as Ctrl-C would, so it follows
+/// `config.on_cancel`. Types registered with `register_runner` are still run synchronously.
+pub async fn prompt_with_async<T: AsyncTerminal + ?Sized>(
+    questions: &[Question],
+    term: &mut T,
+    config: &PromptConfig,
+) -> Result<Answers> {
+    let cancel = config.cancel.clone();
+    crate::ask_all(questions, term, config, &mut async |t: &mut T, wait| {
+        let Some(token) = &cancel else {
+            return read_async(t, wait).await;
+        };
+        match token.run_until_cancelled(read_async(t, wait)).await {
+            Some(input) => input,
+            None => Ok(Input::Key(Key {
+                name: KeyName::Char('c'),
+                ctrl: true,
+                meta: false,
+            })),
+        }
+    })
+    .await
+}
+
+/// `run_prompt` for async code. Returns `None` on cancel.
+pub async fn run_prompt_async<T: AsyncTerminal + ?Sized>(
+    q: &Question,
+    term: &mut T,
+) -> Result<Option<PromptValue>> {
+    PromptRegistry::new()
+        .run_async(q, term, &Answers::new())
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::Error;
+    use crate::terminal::{MemoryTerminal, Terminal};
+    use crate::util::style;
+    use crate::{TextPromptOptions, run_text_async};
+    use std::io;
+    use std::time::Duration;
+    use tokio_util::sync::CancellationToken;
+
+    /// Memory terminal whose input never ends: once its keys run out, reads wait forever.
+    struct Waiting(MemoryTerminal);
+
+    impl Terminal for Waiting {
+        fn is_tty(&self) -> bool {
+            self.0.is_tty()
+        }
+
+        fn enable_raw(&mut self) -> io::Result<()> {
+            self.0.enable_raw()
+        }
+
+        fn disable_raw(&mut self) -> io::Result<()> {
+            self.0.disable_raw()
+        }
+
+        fn size(&self) -> io::Result<(u16, u16)> {
+            self.0.size()
+        }
+
+        fn read_key(&mut self) -> io::Result<Option<Key>> {
+            self.0.read_key()
+        }
+
+        fn write(&mut self, text: &str) -> io::Result<()> {
+            self.0.write(text)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            self.0.flush()
+        }
+    }
+
+    impl AsyncTerminal for Waiting {
+        async fn read_key_async(&mut self) -> io::Result<Option<Key>> {
+            match self.0.read_key()? {
+                Some(key) => Ok(Some(key)),
+                None => std::future::pending().await,
+            }
+        }
+    }
+
+    fn questions() -> Vec<Question> {
+        vec![
+            Question::text("name", "Name?").into(),
+            Question::confirm("ok", "Ok?").into(),
+        ]
+    }
+
+    #[tokio::test]
+    async fn prompt_async_answers_in_order() {
+        let mut term = MemoryTerminal::new("Bob\ny\n").tty(true);
+        let answers = prompt_async(&questions(), &mut term).await.unwrap();
+        assert_eq!(answers["name"], PromptValue::from("Bob"));
+        assert_eq!(answers["ok"], PromptValue::Bool(true));
+    }
+
+    #[tokio::test]
+    async fn run_prompt_async_returns_value() {
+        let q: Question = Question::number("n", "N?").into();
+        let mut term = MemoryTerminal::new("42\n").tty(true);
+        let v = run_prompt_async(&q, &mut term).await.unwrap();
+        assert_eq!(v, Some(PromptValue::Float(42.0)));
+        assert!(!term.is_raw());
+    }
+
+    #[tokio::test]
+    async fn dropping_the_future_restores_the_terminal() {
+        let mut term = Waiting(MemoryTerminal::new("ab").tty(true));
+        let opts = TextPromptOptions {
+            message: "Name?".into(),
+            ..Default::default()
+        };
+        let pending = run_text_async(&opts, &mut term);
+        assert!(
+            tokio::time::timeout(Duration::from_millis(10), pending)
+                .await
+                .is_err()
+        );
+        assert!(!term.0.is_raw());
+        assert!(term.0.output().contains(&style::symbol(false, true, false)));
+        assert!(term.0.output().ends_with("\r\n"));
+    }
+
+    #[tokio::test]
+    async fn cancel_token_aborts_the_current_question() {
+        let token = CancellationToken::new();
+        let config = PromptConfig {
+            cancel: Some(token.clone()),
+            ..Default::default()
+        };
+        let mut term = Waiting(MemoryTerminal::new("Bob\n").tty(true));
+        let questions = questions();
+        let asking = prompt_with_async(&questions, &mut term, &config);
+        let (result, ()) = tokio::join!(asking, async { token.cancel() });
+        match result {
+            Err(Error::Interrupted { name }) => assert_eq!(name, "ok"),
+            other => panic!("unexpected result: {:?}", other),
+        }
+        assert!(!term.0.is_raw());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn timeout_takes_the_initial_value() {
+        let q: Question = Question::text("name", "Name?")
+            .initial("anon")
+            .timeout(Duration::from_secs(2))
+            .into();
+        let mut term = Waiting(MemoryTerminal::new("").tty(true));
+        let v = run_prompt_async(&q, &mut term).await.unwrap();
+        assert_eq!(v, Some(PromptValue::from("anon")));
+        assert!(term.0.output().contains("Answering with the default in 1s"));
+        assert!(!term.0.is_raw());
+    }
+}
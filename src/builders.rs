@@ -0,0 +1,861 @@
+//! Typed question builders.
+//!
+//! `Question::text(name, message)` and friends return a builder that only exposes the options
+//! of its prompt type. Builders convert into `Question` with `.into()`.
+
+use crate::answers::Answers;
+use crate::elements::{Choice, Matcher};
+use crate::prompts::{OnTimeout, PromptValue, Question};
+use crate::util::action::Key;
+use crate::util::style::InputStyle;
+use chrono::NaiveDateTime;
+use std::sync::Arc;
+use std::time::Duration;
+
+impl Question {
+    fn typed(type_name: &str, name: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            type_name: type_name.into(),
+            message: message.into(),
+            ..Default::default()
+        }
+    }
+
+    /// Single-line text input.
+    pub fn text(name: impl Into<String>, message: impl Into<String>) -> TextQuestion {
+        TextQuestion(Self::typed("text", name, message))
+    }
+
+    /// Text input shown masked.
+    pub fn password(name: impl Into<String>, message: impl Into<String>) -> TextQuestion {
+        TextQuestion(Self {
+            style: InputStyle::Password,
+            ..Self::typed("password", name, message)
+        })
+    }
+
+    /// Text input that is not echoed.
+    pub fn invisible(name: impl Into<String>, message: impl Into<String>) -> TextQuestion {
+        TextQuestion(Self {
+            style: InputStyle::Invisible,
+            ..Self::typed("invisible", name, message)
+        })
+    }
+
+    /// Numeric input.
+    pub fn number(name: impl Into<String>, message: impl Into<String>) -> NumberQuestion {
+        NumberQuestion(Self::typed("number", name, message))
+    }
+
+    /// Yes/no question.
+    pub fn confirm(name: impl Into<String>, message: impl Into<String>) -> ConfirmQuestion {
+        ConfirmQuestion(Self::typed("confirm", name, message))
+    }
+
+    /// On/off switch with custom labels.
+    pub fn toggle(name: impl Into<String>, message: impl Into<String>) -> ToggleQuestion {
+        ToggleQuestion(Self::typed("toggle", name, message))
+    }
+
+    /// Single choice from a list.
+    pub fn select(name: impl Into<String>, message: impl Into<String>) -> SelectQuestion {
+        SelectQuestion(Self {
+            choices: Some(Vec::new()),
+            ..Self::typed("select", name, message)
+        })
+    }
+
+    /// Single choice from a list filtered by typing.
+    pub fn autocomplete(
+        name: impl Into<String>,
+        message: impl Into<String>,
+    ) -> AutocompleteQuestion {
+        AutocompleteQuestion(Self {
+            choices: Some(Vec::new()),
+            ..Self::typed("autocomplete", name, message)
+        })
+    }
+
+    /// Several choices from a list, toggled with Space.
+    pub fn multiselect(name: impl Into<String>, message: impl Into<String>) -> MultiselectQuestion {
+        MultiselectQuestion(Self {
+            choices: Some(Vec::new()),
+            ..Self::typed("multiselect", name, message)
+        })
+    }
+
+    /// Date and time edited segment by segment, or typed like `tomorrow 09:00`.
+    pub fn date(name: impl Into<String>, message: impl Into<String>) -> DateQuestion {
+        DateQuestion(Self::typed("date", name, message))
+    }
+
+    /// Text input split into a list.
+    pub fn list(name: impl Into<String>, message: impl Into<String>) -> ListQuestion {
+        ListQuestion(Self::typed("list", name, message))
+    }
+
+    /// Text written in an external editor.
+    pub fn editor(name: impl Into<String>, message: impl Into<String>) -> EditorQuestion {
+        EditorQuestion(Self::typed("editor", name, message))
+    }
+}
+
+/// Builder for `text`, `password` and `invisible` questions.
+#[derive(Clone)]
+pub struct TextQuestion(Question);
+
+impl TextQuestion {
+    /// Answer used when the input is left empty.
+    pub fn initial(mut self, initial: impl Into<String>) -> Self {
+        self.0.initial_text = Some(initial.into());
+        self
+    }
+
+    /// Starts the input with the initial value, ready to edit; Ctrl-G restores it.
+    pub fn prefill(mut self) -> Self {
+        self.0.prefill = true;
+        self
+    }
+
+    /// Remembers answers in `PromptConfig::history`, recalled with Up and Down. Password and
+    /// invisible input is never remembered.
+    pub fn history(mut self) -> Self {
+        self.0.history = true;
+        self
+    }
+
+    /// Accepts several lines: Enter starts a new line and Ctrl-D submits.
+    pub fn multiline(mut self) -> Self {
+        self.0.multiline = true;
+        self
+    }
+
+    /// Also submits multiline input on `key`, e.g. Alt-Enter.
+    pub fn submit_key(mut self, key: Key) -> Self {
+        self.0.submit_key = Some(key);
+        self
+    }
+}
+
+/// Builder for `number` questions.
+#[derive(Clone)]
+pub struct NumberQuestion(Question);
+
+impl NumberQuestion {
+    /// Answer used when the input is left empty.
+    pub fn initial(mut self, initial: impl Into<f64>) -> Self {
+        self.0.initial_number = Some(initial.into());
+        self
+    }
+
+    /// Starts the input with the initial value, ready to edit; Ctrl-G restores it.
+    pub fn prefill(mut self) -> Self {
+        self.0.prefill = true;
+        self
+    }
+
+    /// Smallest accepted value; lower input is clamped.
+    pub fn min(mut self, min: impl Into<f64>) -> Self {
+        self.0.min = Some(min.into());
+        self
+    }
+
+    /// Largest accepted value; higher input is clamped.
+    pub fn max(mut self, max: impl Into<f64>) -> Self {
+        self.0.max = Some(max.into());
+        self
+    }
+
+    /// Computes the smallest accepted value from the answers so far.
+    pub fn min_with<F>(mut self, f: F) -> Self
+    where
+        F: Fn(&Answers) -> Option<f64> + Send + Sync + 'static,
+    {
+        self.0.dynamic.min = Some(Arc::new(f));
+        self
+    }
+
+    /// Computes the largest accepted value from the answers so far.
+    pub fn max_with<F>(mut self, f: F) -> Self
+    where
+        F: Fn(&Answers) -> Option<f64> + Send + Sync + 'static,
+    {
+        self.0.dynamic.max = Some(Arc::new(f));
+        self
+    }
+
+    /// Accepts decimals, rounded to `round` places.
+    pub fn float(mut self, round: u32) -> Self {
+        self.0.float = true;
+        self.0.round = round;
+        self
+    }
+}
+
+/// Builder for `confirm` questions.
+#[derive(Clone)]
+pub struct ConfirmQuestion(Question);
+
+impl ConfirmQuestion {
+    /// Answer used when the input is left empty.
+    pub fn initial(mut self, initial: bool) -> Self {
+        self.0.initial_bool = Some(initial);
+        self
+    }
+}
+
+/// Builder for `toggle` questions.
+#[derive(Clone)]
+pub struct ToggleQuestion(Question);
+
+impl ToggleQuestion {
+    /// Initially selected side.
+    pub fn initial(mut self, initial: bool) -> Self {
+        self.0.initial_bool = Some(initial);
+        self
+    }
+
+    /// Label of the `true` side (default `on`).
+    pub fn active(mut self, label: impl Into<String>) -> Self {
+        self.0.active = Some(label.into());
+        self
+    }
+
+    /// Label of the `false` side (default `off`).
+    pub fn inactive(mut self, label: impl Into<String>) -> Self {
+        self.0.inactive = Some(label.into());
+        self
+    }
+}
+
+/// Builder for `select` questions.
+#[derive(Clone)]
+pub struct SelectQuestion(Question);
+
+/// Builder for `autocomplete` questions.
+#[derive(Clone)]
+pub struct AutocompleteQuestion(Question);
+
+impl AutocompleteQuestion {
+    /// How typed input filters the choices (default `Matcher::Fuzzy`).
+    pub fn matcher(mut self, matcher: Matcher) -> Self {
+        self.0.matcher = matcher;
+        self
+    }
+
+    /// Accepts input that is not one of the choices.
+    pub fn allow_custom(mut self) -> Self {
+        self.0.allow_custom = true;
+        self
+    }
+}
+
+/// Builder for `multiselect` questions. Choices with `selected` set start checked.
+#[derive(Clone)]
+pub struct MultiselectQuestion(Question);
+
+impl MultiselectQuestion {
+    /// Fewest choices that may be submitted.
+    pub fn min(mut self, min: usize) -> Self {
+        self.0.min = Some(min as f64);
+        self
+    }
+
+    /// Most choices that may be submitted.
+    pub fn max(mut self, max: usize) -> Self {
+        self.0.max = Some(max as f64);
+        self
+    }
+}
+
+/// Builder for `date` questions.
+#[derive(Clone)]
+pub struct DateQuestion(Question);
+
+impl DateQuestion {
+    /// Starting value; defaults to the current local time.
+    pub fn initial(mut self, initial: NaiveDateTime) -> Self {
+        self.0.initial_date = Some(initial);
+        self
+    }
+
+    /// Earliest value that may be submitted.
+    pub fn min(mut self, min: NaiveDateTime) -> Self {
+        self.0.min_date = Some(min);
+        self
+    }
+
+    /// Latest value that may be submitted.
+    pub fn max(mut self, max: NaiveDateTime) -> Self {
+        self.0.max_date = Some(max);
+        self
+    }
+
+    /// Layout of the value, e.g. `DD.MM.YYYY HH:mm`; see `DatePromptOptions::mask`.
+    pub fn mask(mut self, mask: impl Into<String>) -> Self {
+        self.0.mask = Some(mask.into());
+        self
+    }
+}
+
+macro_rules! choice_setters {
+    ($($builder:ident),*) => {
+        $(
+            impl $builder {
+                /// Adds a choice with the given title and value.
+                pub fn choice(self, title: impl Into<String>, value: impl Into<String>) -> Self {
+                    self.with_choice(Choice::new(title, value))
+                }
+
+                /// Adds a fully configured choice.
+                pub fn with_choice(mut self, choice: Choice) -> Self {
+                    self.0.choices.get_or_insert_with(Vec::new).push(choice);
+                    self
+                }
+
+                /// Adds several choices.
+                pub fn choices(mut self, choices: impl IntoIterator<Item = Choice>) -> Self {
+                    self.0.choices.get_or_insert_with(Vec::new).extend(choices);
+                    self
+                }
+
+                /// Index of the initially highlighted choice.
+                pub fn initial(mut self, index: usize) -> Self {
+                    self.0.initial_choice = Some(index);
+                    self
+                }
+
+                /// Computes the choices from the answers so far.
+                pub fn choices_with<F>(mut self, f: F) -> Self
+                where
+                    F: Fn(&Answers) -> Vec<Choice> + Send + Sync + 'static,
+                {
+                    self.0.dynamic.choices = Some(Arc::new(f));
+                    self
+                }
+
+                /// Usage tip shown under the choices.
+                pub fn hint(mut self, hint: impl Into<String>) -> Self {
+                    self.0.hint = Some(hint.into());
+                    self
+                }
+
+                /// Computes the usage tip from the answers so far.
+                pub fn hint_with<F>(mut self, f: F) -> Self
+                where
+                    F: Fn(&Answers) -> Option<String> + Send + Sync + 'static,
+                {
+                    self.0.dynamic.hint = Some(Arc::new(f));
+                    self
+                }
+            }
+        )*
+    };
+}
+
+choice_setters!(SelectQuestion, AutocompleteQuestion, MultiselectQuestion);
+
+/// Builder for `list` questions.
+#[derive(Clone)]
+pub struct ListQuestion(Question);
+
+impl ListQuestion {
+    /// Answer used when the input is left empty, before splitting.
+    pub fn initial(mut self, initial: impl Into<String>) -> Self {
+        self.0.initial_text = Some(initial.into());
+        self
+    }
+
+    /// Starts the input with the initial value, ready to edit; Ctrl-G restores it.
+    pub fn prefill(mut self) -> Self {
+        self.0.prefill = true;
+        self
+    }
+
+    /// Remembers answers in `PromptConfig::history`, recalled with Up and Down; the items are
+    /// joined with the separator.
+    pub fn history(mut self) -> Self {
+        self.0.history = true;
+        self
+    }
+
+    /// Separator to split on (default `,`).
+    pub fn separator(mut self, separator: impl Into<String>) -> Self {
+        self.0.separator = Some(separator.into());
+        self
+    }
+}
+
+/// Builder for `editor` questions.
+#[derive(Clone)]
+pub struct EditorQuestion(Question);
+
+impl EditorQuestion {
+    /// Text the editor opens with.
+    pub fn initial(mut self, initial: impl Into<String>) -> Self {
+        self.0.initial_text = Some(initial.into());
+        self
+    }
+
+    /// Command to run instead of `$VISUAL` or `$EDITOR`, e.g. `code --wait`.
+    pub fn command(mut self, command: impl Into<String>) -> Self {
+        self.0.editor = Some(command.into());
+        self
+    }
+
+    /// Drops lines starting with `prefix`, e.g. `#`, from the answer.
+    pub fn comment(mut self, prefix: impl Into<String>) -> Self {
+        self.0.comment = Some(prefix.into());
+        self
+    }
+}
+
+macro_rules! common_setters {
+    ($($builder:ident),*) => {
+        $(
+            impl $builder {
+                /// Rejects answers for which `f` returns an error message; the question is then
+                /// asked again.
+                pub fn validate<F>(mut self, f: F) -> Self
+                where
+                    F: Fn(&PromptValue, &Answers) -> Result<(), String> + Send + Sync + 'static,
+                {
+                    self.0.validate = Some(Arc::new(f));
+                    self
+                }
+
+                /// Gives up with `Error::Validation` after `attempts` rejected answers.
+                pub fn max_attempts(mut self, attempts: u32) -> Self {
+                    self.0.max_attempts = Some(attempts);
+                    self
+                }
+
+                /// Asks the question only when `f` returns `true` for the answers so far.
+                pub fn when<F>(mut self, f: F) -> Self
+                where
+                    F: Fn(&Answers) -> bool + Send + Sync + 'static,
+                {
+                    let type_name = self.0.type_name.clone();
+                    self.0.dynamic.type_name =
+                        Some(Arc::new(move |answers| f(answers).then(|| type_name.clone())));
+                    self
+                }
+
+                /// Computes the message from the answers so far.
+                pub fn message_with<F>(mut self, f: F) -> Self
+                where
+                    F: Fn(&Answers) -> String + Send + Sync + 'static,
+                {
+                    self.0.dynamic.message = Some(Arc::new(f));
+                    self
+                }
+
+                /// Computes the initial answer from the answers so far.
+                pub fn initial_with<F, V>(mut self, f: F) -> Self
+                where
+                    F: Fn(&Answers) -> Option<V> + Send + Sync + 'static,
+                    V: Into<PromptValue>,
+                {
+                    self.0.dynamic.initial = Some(Arc::new(move |answers| f(answers).map(Into::into)));
+                    self
+                }
+
+                /// Converts the validated answer into the value stored in the answers.
+                pub fn format<F>(mut self, f: F) -> Self
+                where
+                    F: Fn(PromptValue, &Answers) -> PromptValue + Send + Sync + 'static,
+                {
+                    self.0.format = Some(Arc::new(f));
+                    self
+                }
+
+                /// Sets the text printed for the answer once the question is done.
+                pub fn display<F>(mut self, f: F) -> Self
+                where
+                    F: Fn(&PromptValue) -> String + Send + Sync + 'static,
+                {
+                    self.0.display = Some(Arc::new(f));
+                    self
+                }
+            }
+        )*
+    };
+}
+
+macro_rules! timeout_setters {
+    ($($builder:ident),*) => {
+        $(
+            impl $builder {
+                /// Answers with the initial value when no key is pressed within `timeout`.
+                pub fn timeout(mut self, timeout: Duration) -> Self {
+                    self.0.timeout = Some(timeout);
+                    self
+                }
+
+                /// Sets what happens when the timeout runs out.
+                pub fn on_timeout(mut self, on_timeout: OnTimeout) -> Self {
+                    self.0.on_timeout = on_timeout;
+                    self
+                }
+            }
+        )*
+    };
+}
+
+common_setters!(
+    TextQuestion,
+    NumberQuestion,
+    ConfirmQuestion,
+    ToggleQuestion,
+    SelectQuestion,
+    AutocompleteQuestion,
+    MultiselectQuestion,
+    DateQuestion,
+    ListQuestion,
+    EditorQuestion
+);
+
+timeout_setters!(
+    TextQuestion,
+    NumberQuestion,
+    ConfirmQuestion,
+    ToggleQuestion,
+    SelectQuestion,
+    AutocompleteQuestion,
+    MultiselectQuestion,
+    DateQuestion,
+    ListQuestion
+);
+
+macro_rules! into_question {
+    ($($builder:ident),*) => {
+        $(
+            impl From<$builder> for Question {
+                fn from(b: $builder) -> Self {
+                    b.0
+                }
+            }
+        )*
+    };
+}
+
+into_question!(
+    TextQuestion,
+    NumberQuestion,
+    ConfirmQuestion,
+    ToggleQuestion,
+    SelectQuestion,
+    AutocompleteQuestion,
+    MultiselectQuestion,
+    DateQuestion,
+    ListQuestion,
+    EditorQuestion
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::Error;
+    use crate::prompts::run_prompt;
+    use crate::terminal::MemoryTerminal;
+
+    #[test]
+    fn text_builder_sets_type_and_initial() {
+        let q: Question = Question::text("name", "Name?").initial("anon").into();
+        assert_eq!(q.name, "name");
+        assert_eq!(q.type_name, "text");
+        assert_eq!(q.message, "Name?");
+        assert_eq!(q.initial_text.as_deref(), Some("anon"));
+    }
+
+    #[test]
+    fn password_and_invisible_set_style() {
+        let q: Question = Question::password("p", "P?").into();
+        assert_eq!(q.type_name, "password");
+        assert_eq!(q.style, InputStyle::Password);
+        let q: Question = Question::invisible("i", "I?").into();
+        assert_eq!(q.type_name, "invisible");
+        assert_eq!(q.style, InputStyle::Invisible);
+    }
+
+    #[test]
+    fn number_builder_accepts_integer_bounds() {
+        let q: Question = Question::number("n", "N?").min(1).max(10).initial(5).into();
+        assert_eq!(q.type_name, "number");
+        assert_eq!(q.min, Some(1.0));
+        assert_eq!(q.max, Some(10.0));
+        assert_eq!(q.initial_number, Some(5.0));
+        assert!(!q.float);
+    }
+
+    #[test]
+    fn number_builder_float_sets_round() {
+        let q: Question = Question::number("n", "N?").float(3).into();
+        assert!(q.float);
+        assert_eq!(q.round, 3);
+    }
+
+    #[test]
+    fn confirm_and_toggle_builders() {
+        let q: Question = Question::confirm("c", "C?").initial(true).into();
+        assert_eq!(q.type_name, "confirm");
+        assert_eq!(q.initial_bool, Some(true));
+        let q: Question = Question::toggle("t", "T?")
+            .active("yes")
+            .inactive("no")
+            .into();
+        assert_eq!(q.type_name, "toggle");
+        assert_eq!(q.active.as_deref(), Some("yes"));
+        assert_eq!(q.inactive.as_deref(), Some("no"));
+    }
+
+    #[test]
+    fn select_builder_collects_choices() {
+        let mut disabled = Choice::new("C", "c");
+        disabled.disabled = true;
+        let q: Question = Question::select("s", "S?")
+            .choice("A", "a")
+            .choices([Choice::new("B", "b")])
+            .with_choice(disabled)
+            .initial(1)
+            .hint("pick one")
+            .into();
+        let choices = q.choices.unwrap();
+        assert_eq!(choices.len(), 3);
+        assert_eq!(choices[1].value, "b");
+        assert!(choices[2].disabled);
+        assert_eq!(q.initial_choice, Some(1));
+        assert_eq!(q.hint.as_deref(), Some("pick one"));
+    }
+
+    #[test]
+    fn list_builder_sets_separator() {
+        let q: Question = Question::list("l", "L?").separator(";").into();
+        assert_eq!(q.type_name, "list");
+        assert_eq!(q.separator.as_deref(), Some(";"));
+    }
+
+    #[test]
+    fn autocomplete_builder_sets_matcher_and_runs() {
+        let q: Question = Question::autocomplete("region", "Region?")
+            .choice("eu-west-1", "eu-west-1")
+            .choice("us-east-1", "us-east-1")
+            .matcher(Matcher::Prefix)
+            .allow_custom()
+            .into();
+        assert_eq!(q.type_name, "autocomplete");
+        assert!(matches!(q.matcher, Matcher::Prefix) && q.allow_custom);
+        let mut term = MemoryTerminal::new("us\n");
+        assert_eq!(
+            run_prompt(&q, &mut term).unwrap(),
+            Some(PromptValue::from("us-east-1"))
+        );
+        let mut term = MemoryTerminal::new("ap-south-1\n");
+        assert_eq!(
+            run_prompt(&q, &mut term).unwrap(),
+            Some(PromptValue::from("ap-south-1"))
+        );
+    }
+
+    #[test]
+    fn multiselect_builder_sets_count_limits_and_runs() {
+        let mut ham = Choice::new("Ham", "ham");
+        ham.selected = true;
+        let q: Question = Question::multiselect("toppings", "Toppings?")
+            .choice("Cheese", "cheese")
+            .with_choice(ham)
+            .min(1)
+            .max(2)
+            .into();
+        assert_eq!(q.type_name, "multiselect");
+        assert_eq!((q.min, q.max), (Some(1.0), Some(2.0)));
+        let mut term = MemoryTerminal::new(" \n");
+        assert_eq!(
+            run_prompt(&q, &mut term).unwrap(),
+            Some(PromptValue::List(vec!["cheese".into(), "ham".into()]))
+        );
+    }
+
+    #[test]
+    fn date_builder_sets_bounds_and_mask() {
+        let at = |s| NaiveDateTime::parse_from_str(s, "%d.%m.%Y %H:%M").unwrap();
+        let q: Question = Question::date("window", "Maintenance window?")
+            .initial(at("01.03.2024 09:00"))
+            .min(at("01.03.2024 00:00"))
+            .mask("DD.MM.YYYY HH:mm")
+            .into();
+        assert_eq!(q.type_name, "date");
+        assert_eq!(q.min_date, Some(at("01.03.2024 00:00")));
+        assert!(q.max_date.is_none());
+        let mut term = MemoryTerminal::new("02.03.2024 10:30\n");
+        assert_eq!(
+            run_prompt(&q, &mut term).unwrap(),
+            Some(PromptValue::Date(at("02.03.2024 10:30")))
+        );
+    }
+
+    #[test]
+    fn editor_builder_sets_command_and_comment() {
+        let q: Question = Question::editor("notes", "Notes?")
+            .initial("# Describe the change\n")
+            .command("code --wait")
+            .comment("#")
+            .into();
+        assert_eq!(q.type_name, "editor");
+        assert_eq!(q.initial_text.as_deref(), Some("# Describe the change\n"));
+        assert_eq!(q.editor.as_deref(), Some("code --wait"));
+        assert_eq!(q.comment.as_deref(), Some("#"));
+    }
+
+    #[test]
+    fn prefill_starts_input_with_initial() {
+        let q = Question::text("v", "Version?")
+            .initial("1.2.3")
+            .prefill()
+            .into();
+        let mut term = MemoryTerminal::new("\x7f4\r").tty(true);
+        assert_eq!(
+            run_prompt(&q, &mut term).unwrap(),
+            Some(PromptValue::from("1.2.4"))
+        );
+        let q = Question::list("l", "L?").initial("a, b").prefill().into();
+        let mut term = MemoryTerminal::new(", c\r").tty(true);
+        assert_eq!(
+            run_prompt(&q, &mut term).unwrap(),
+            Some(PromptValue::List(vec!["a".into(), "b".into(), "c".into()]))
+        );
+        let q: Question = Question::number("n", "N?").initial(8).prefill().into();
+        assert!(q.prefill);
+    }
+
+    #[test]
+    fn built_questions_run() {
+        let q = Question::number("n", "N?").min(1).max(10).into();
+        let mut term = MemoryTerminal::new("42\n");
+        assert_eq!(
+            run_prompt(&q, &mut term).unwrap(),
+            Some(PromptValue::Float(10.0))
+        );
+        let q = Question::select("s", "S?")
+            .choice("A", "a")
+            .choice("B", "b")
+            .initial(1)
+            .into();
+        let mut term = MemoryTerminal::new("\n");
+        assert_eq!(
+            run_prompt(&q, &mut term).unwrap(),
+            Some(PromptValue::String("b".into()))
+        );
+    }
+
+    #[test]
+    fn timeout_setters_apply_to_every_builder() {
+        let q: Question = Question::confirm("c", "C?")
+            .timeout(Duration::from_secs(5))
+            .on_timeout(OnTimeout::Cancel)
+            .into();
+        assert_eq!(q.timeout, Some(Duration::from_secs(5)));
+        assert_eq!(q.on_timeout, OnTimeout::Cancel);
+        let q = Question::text("t", "T?")
+            .initial("anon")
+            .timeout(Duration::from_secs(1))
+            .into();
+        let mut term = MemoryTerminal::new("").tty(true);
+        assert_eq!(
+            run_prompt(&q, &mut term).unwrap(),
+            Some(PromptValue::String("anon".into()))
+        );
+    }
+
+    #[test]
+    fn validate_reprompts_until_accepted() {
+        let q = Question::text("user", "User?")
+            .validate(|v, _| match v {
+                PromptValue::String(s) if s.len() < 3 => Err("too short".into()),
+                _ => Ok(()),
+            })
+            .into();
+        let mut term = MemoryTerminal::new(
+            "al
+alice
+",
+        );
+        assert_eq!(
+            run_prompt(&q, &mut term).unwrap(),
+            Some(PromptValue::String("alice".into()))
+        );
+        assert!(term.output().contains("too short"));
+    }
+
+    #[test]
+    fn max_attempts_gives_up() {
+        let q = Question::number("n", "N?")
+            .validate(|_, _| Err("never".into()))
+            .max_attempts(2)
+            .into();
+        let mut term = MemoryTerminal::new("1\n2\n3\n");
+        let e = run_prompt(&q, &mut term).unwrap_err();
+        assert!(
+            matches!(&e, Error::Validation { name, message } if name == "n" && message == "never")
+        );
+        assert_eq!(term.remaining_input(), b"3\n");
+    }
+
+    #[test]
+    fn format_and_display_apply_to_answer() {
+        let q = Question::text("slug", "Slug?")
+            .format(|v, _| match v {
+                PromptValue::String(s) => PromptValue::String(s.to_lowercase()),
+                v => v,
+            })
+            .display(|v| format!("slug={:?}", v))
+            .into();
+        let mut term = MemoryTerminal::new("My-App\n");
+        assert_eq!(
+            run_prompt(&q, &mut term).unwrap(),
+            Some(PromptValue::String("my-app".into()))
+        );
+        assert!(term.output().contains("slug=String(\"my-app\")"));
+    }
+
+    #[test]
+    fn dynamic_setters_resolve_against_answers() {
+        let answers = Answers::from([("max".to_string(), PromptValue::Float(8.0))]);
+        let q: Question = Question::number("n", "N?")
+            .min(1)
+            .max_with(|a| match a.get("max") {
+                Some(PromptValue::Float(n)) => Some(*n),
+                _ => None,
+            })
+            .initial_with(|_| Some(4.0))
+            .into();
+        assert!(q.resolve(&Answers::new()).max.is_none());
+        let q = q.resolve(&answers);
+        assert_eq!(q.min, Some(1.0));
+        assert_eq!(q.max, Some(8.0));
+        assert_eq!(q.initial_number, Some(4.0));
+    }
+
+    #[test]
+    fn select_initial_with_picks_choice_by_value() {
+        let q: Question = Question::select("s", "S?")
+            .choice("A", "a")
+            .choice("B", "b")
+            .initial_with(|_| Some("b"))
+            .hint_with(|_| Some("computed".into()))
+            .into();
+        let q = q.resolve(&Answers::new());
+        assert_eq!(q.initial_choice, Some(1));
+        assert_eq!(q.hint.as_deref(), Some("computed"));
+    }
+
+    #[test]
+    fn when_false_skips_question() {
+        let q: Question = Question::confirm("c", "C?").when(|_| false).into();
+        assert!(q.resolve(&Answers::new()).type_name.is_empty());
+        let mut term = MemoryTerminal::new("y\n");
+        assert_eq!(run_prompt(&q, &mut term).unwrap(), None);
+        assert_eq!(term.output(), "");
+    }
+}
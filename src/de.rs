@@ -0,0 +1,481 @@
+//! Deserializing answers into application types.
+//!
+//! Answers are read as a map from question name to value. On top of the usual serde rules,
+//! a whole `Float` fills integer fields, `List` fills sequences, and strings (including list
+//! items) are parsed when a number or bool is expected. A `Date` is read as an ISO 8601 string.
+
+use crate::answers::Answers;
+use crate::error::{Error, Result};
+use crate::prompts::PromptValue;
+use serde::de::value::{SeqDeserializer, StrDeserializer};
+use serde::de::{self, DeserializeOwned, DeserializeSeed, IntoDeserializer, MapAccess, Visitor};
+use serde::forward_to_deserialize_any;
+use std::fmt;
+
+/// Builds `T` from the answers, e.g. a struct with one field per question.
+///
+/// A value that does not fit its field is an `Error::Conversion` naming the question.
+pub fn from_answers<T: DeserializeOwned>(answers: &Answers) -> Result<T> {
+    T::deserialize(AnswersDeserializer { answers }).map_err(Error::from)
+}
+
+impl Answers {
+    /// Returns the answer to `name` converted to `T`, e.g. `get_as::<u16>("port")`.
+    ///
+    /// A missing answer or one that does not fit `T` is an `Error::Conversion`.
+    pub fn get_as<T: DeserializeOwned>(&self, name: &str) -> Result<T> {
+        let value = self.get(name).ok_or_else(|| Error::Conversion {
+            name: name.to_string(),
+            message: "no answer".into(),
+        })?;
+        T::deserialize(ValueDeserializer { value }).map_err(|e| Error::from(e.in_question(name)))
+    }
+}
+
+/// Deserialization error, tagged with the question it happened in once known.
+#[derive(Debug)]
+struct DeError {
+    name: Option<String>,
+    message: String,
+}
+
+impl DeError {
+    fn in_question(mut self, name: &str) -> Self {
+        self.name.get_or_insert_with(|| name.to_string());
+        self
+    }
+}
+
+impl fmt::Display for DeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for DeError {}
+
+impl de::Error for DeError {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        DeError {
+            name: None,
+            message: msg.to_string(),
+        }
+    }
+
+    fn missing_field(field: &'static str) -> Self {
+        DeError {
+            name: Some(field.to_string()),
+            message: "no answer".into(),
+        }
+    }
+}
+
+impl From<DeError> for Error {
+    fn from(e: DeError) -> Self {
+        Error::Conversion {
+            name: e.name.unwrap_or_default(),
+            message: e.message,
+        }
+    }
+}
+
+struct AnswersDeserializer<'a> {
+    answers: &'a Answers,
+}
+
+impl<'de> de::Deserializer<'de> for AnswersDeserializer<'_> {
+    type Error = DeError;
+
+    fn deserialize_any<V: Visitor<'de>>(
+        self,
+        visitor: V,
+    ) -> std::result::Result<V::Value, DeError> {
+        visitor.visit_map(AnswersAccess {
+            entries: self.answers.iter(),
+            current: None,
+        })
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string bytes byte_buf
+        option unit unit_struct newtype_struct seq tuple tuple_struct map struct enum
+        identifier ignored_any
+    }
+}
+
+struct AnswersAccess<'a, I> {
+    entries: I,
+    current: Option<(&'a str, &'a PromptValue)>,
+}
+
+impl<'de, 'a, I> MapAccess<'de> for AnswersAccess<'a, I>
+where
+    I: Iterator<Item = (&'a str, &'a PromptValue)>,
+{
+    type Error = DeError;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> std::result::Result<Option<K::Value>, DeError> {
+        self.current = self.entries.next();
+        match self.current {
+            Some((name, _)) => {
+                let key: StrDeserializer<DeError> = name.into_deserializer();
+                seed.deserialize(key).map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(
+        &mut self,
+        seed: V,
+    ) -> std::result::Result<V::Value, DeError> {
+        let (name, value) = self
+            .current
+            .take()
+            .ok_or_else(|| <DeError as de::Error>::custom("value requested before key"))?;
+        seed.deserialize(ValueDeserializer { value })
+            .map_err(|e| e.in_question(name))
+    }
+}
+
+/// Visits a whole number as an integer; fractional numbers are rejected by the visitor.
+fn visit_number<'de, V: Visitor<'de>>(
+    n: f64,
+    visitor: V,
+) -> std::result::Result<V::Value, DeError> {
+    if n.fract() == 0.0 && n >= i64::MIN as f64 && n < i64::MAX as f64 {
+        visitor.visit_i64(n as i64)
+    } else if n.fract() == 0.0 && n >= 0.0 && n < u64::MAX as f64 {
+        visitor.visit_u64(n as u64)
+    } else {
+        visitor.visit_f64(n)
+    }
+}
+
+/// Visits a string that should hold a number, parsing it first.
+fn visit_numeric_str<'de, V: Visitor<'de>>(
+    s: &str,
+    visitor: V,
+) -> std::result::Result<V::Value, DeError> {
+    match s.trim().parse::<f64>() {
+        Ok(n) => visit_number(n, visitor),
+        Err(_) => visitor.visit_str(s),
+    }
+}
+
+macro_rules! deserialize_numbers {
+    ($($method:ident)*) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, visitor: V) -> std::result::Result<V::Value, DeError> {
+                self.numeric(visitor)
+            }
+        )*
+    };
+}
+
+struct ValueDeserializer<'a> {
+    value: &'a PromptValue,
+}
+
+impl ValueDeserializer<'_> {
+    fn numeric<'de, V: Visitor<'de>>(self, visitor: V) -> std::result::Result<V::Value, DeError> {
+        match self.value {
+            PromptValue::Float(n) => visit_number(*n, visitor),
+            PromptValue::String(s) => visit_numeric_str(s, visitor),
+            _ => de::Deserializer::deserialize_any(self, visitor),
+        }
+    }
+}
+
+impl<'de> de::Deserializer<'de> for ValueDeserializer<'_> {
+    type Error = DeError;
+
+    fn deserialize_any<V: Visitor<'de>>(
+        self,
+        visitor: V,
+    ) -> std::result::Result<V::Value, DeError> {
+        match self.value {
+            PromptValue::String(s) => visitor.visit_str(s),
+            PromptValue::Bool(b) => visitor.visit_bool(*b),
+            PromptValue::Float(n) => visitor.visit_f64(*n),
+            PromptValue::Date(d) => visitor.visit_string(d.format("%Y-%m-%dT%H:%M:%S").to_string()),
+            PromptValue::List(items) => {
+                let mut seq = SeqDeserializer::new(items.iter().map(|s| ItemDeserializer { s }));
+                let value = visitor.visit_seq(&mut seq)?;
+                seq.end()?;
+                Ok(value)
+            }
+        }
+    }
+
+    deserialize_numbers! {
+        deserialize_i8 deserialize_i16 deserialize_i32 deserialize_i64 deserialize_i128
+        deserialize_u8 deserialize_u16 deserialize_u32 deserialize_u64 deserialize_u128
+    }
+
+    fn deserialize_f32<V: Visitor<'de>>(
+        self,
+        visitor: V,
+    ) -> std::result::Result<V::Value, DeError> {
+        self.deserialize_f64(visitor)
+    }
+
+    fn deserialize_f64<V: Visitor<'de>>(
+        self,
+        visitor: V,
+    ) -> std::result::Result<V::Value, DeError> {
+        match self.value {
+            PromptValue::Float(n) => visitor.visit_f64(*n),
+            PromptValue::String(s) => match s.trim().parse::<f64>() {
+                Ok(n) => visitor.visit_f64(n),
+                Err(_) => visitor.visit_str(s),
+            },
+            _ => self.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_bool<V: Visitor<'de>>(
+        self,
+        visitor: V,
+    ) -> std::result::Result<V::Value, DeError> {
+        match self.value {
+            PromptValue::String(s) => match s.trim().parse::<bool>() {
+                Ok(b) => visitor.visit_bool(b),
+                Err(_) => visitor.visit_str(s),
+            },
+            _ => self.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(
+        self,
+        visitor: V,
+    ) -> std::result::Result<V::Value, DeError> {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> std::result::Result<V::Value, DeError> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> std::result::Result<V::Value, DeError> {
+        match self.value {
+            PromptValue::String(s) => {
+                let variant: StrDeserializer<DeError> = s.as_str().into_deserializer();
+                visitor.visit_enum(variant)
+            }
+            _ => self.deserialize_any(visitor),
+        }
+    }
+
+    forward_to_deserialize_any! {
+        char str string bytes byte_buf unit unit_struct seq tuple tuple_struct map struct
+        identifier ignored_any
+    }
+}
+
+/// Deserializer for one `List` item.
+struct ItemDeserializer<'a> {
+    s: &'a str,
+}
+
+impl ItemDeserializer<'_> {
+    fn numeric<'de, V: Visitor<'de>>(self, visitor: V) -> std::result::Result<V::Value, DeError> {
+        visit_numeric_str(self.s, visitor)
+    }
+}
+
+impl<'de> IntoDeserializer<'de, DeError> for ItemDeserializer<'_> {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self {
+        self
+    }
+}
+
+impl<'de> de::Deserializer<'de> for ItemDeserializer<'_> {
+    type Error = DeError;
+
+    fn deserialize_any<V: Visitor<'de>>(
+        self,
+        visitor: V,
+    ) -> std::result::Result<V::Value, DeError> {
+        visitor.visit_str(self.s)
+    }
+
+    deserialize_numbers! {
+        deserialize_i8 deserialize_i16 deserialize_i32 deserialize_i64 deserialize_i128
+        deserialize_u8 deserialize_u16 deserialize_u32 deserialize_u64 deserialize_u128
+        deserialize_f32 deserialize_f64
+    }
+
+    fn deserialize_bool<V: Visitor<'de>>(
+        self,
+        visitor: V,
+    ) -> std::result::Result<V::Value, DeError> {
+        match self.s.trim().parse::<bool>() {
+            Ok(b) => visitor.visit_bool(b),
+            Err(_) => visitor.visit_str(self.s),
+        }
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(
+        self,
+        visitor: V,
+    ) -> std::result::Result<V::Value, DeError> {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> std::result::Result<V::Value, DeError> {
+        let variant: StrDeserializer<DeError> = self.s.into_deserializer();
+        visitor.visit_enum(variant)
+    }
+
+    forward_to_deserialize_any! {
+        char str string bytes byte_buf unit unit_struct newtype_struct seq tuple tuple_struct
+        map struct identifier ignored_any
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    #[serde(rename_all = "lowercase")]
+    enum Env {
+        Staging,
+        Prod,
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Setup {
+        name: String,
+        port: u16,
+        ratio: f32,
+        tls: bool,
+        env: Env,
+        tags: Vec<String>,
+        ports: Vec<u32>,
+        nickname: Option<String>,
+    }
+
+    fn answers() -> Answers {
+        Answers::from([
+            ("name", PromptValue::from("api")),
+            ("port", PromptValue::Float(8080.0)),
+            ("ratio", PromptValue::Float(0.5)),
+            ("tls", PromptValue::Bool(true)),
+            ("env", PromptValue::from("prod")),
+            ("tags", PromptValue::List(vec!["a".into(), "b".into()])),
+            ("ports", PromptValue::List(vec!["80".into(), " 443".into()])),
+            ("extra", PromptValue::Bool(false)),
+        ])
+    }
+
+    #[test]
+    fn deserializes_struct_with_coercions() {
+        let setup: Setup = from_answers(&answers()).unwrap();
+        assert_eq!(
+            setup,
+            Setup {
+                name: "api".into(),
+                port: 8080,
+                ratio: 0.5,
+                tls: true,
+                env: Env::Prod,
+                tags: vec!["a".into(), "b".into()],
+                ports: vec![80, 443],
+                nickname: None,
+            }
+        );
+    }
+
+    #[test]
+    fn errors_name_the_question() {
+        let mut a = answers();
+        a.insert("port", PromptValue::Float(8080.5));
+        let e = from_answers::<Setup>(&a).unwrap_err();
+        assert!(
+            matches!(&e, Error::Conversion { name, .. } if name == "port"),
+            "{}",
+            e
+        );
+
+        a.insert("port", PromptValue::Float(70000.0));
+        let e = from_answers::<Setup>(&a).unwrap_err();
+        assert!(
+            matches!(&e, Error::Conversion { name, .. } if name == "port"),
+            "{}",
+            e
+        );
+
+        a.insert("port", PromptValue::Float(1.0));
+        a.insert("ports", PromptValue::List(vec!["http".into()]));
+        let e = from_answers::<Setup>(&a).unwrap_err();
+        assert!(
+            matches!(&e, Error::Conversion { name, .. } if name == "ports"),
+            "{}",
+            e
+        );
+
+        a.insert("ports", PromptValue::List(vec![]));
+        a.remove("env");
+        let e = from_answers::<Setup>(&a).unwrap_err();
+        assert!(
+            matches!(&e, Error::Conversion { name, message } if name == "env" && message == "no answer"),
+            "{}",
+            e
+        );
+    }
+
+    #[test]
+    fn get_as_converts_single_answers() {
+        let a = answers();
+        assert_eq!(a.get_as::<u16>("port").unwrap(), 8080);
+        assert_eq!(a.get_as::<f64>("port").unwrap(), 8080.0);
+        assert_eq!(a.get_as::<Env>("env").unwrap(), Env::Prod);
+        assert_eq!(a.get_as::<Vec<u16>>("ports").unwrap(), vec![80, 443]);
+        assert!(a.get_as::<Option<bool>>("tls").unwrap().unwrap());
+        let e = a.get_as::<u8>("port").unwrap_err();
+        assert_eq!(e.name(), Some("port"));
+        let e = a.get_as::<u8>("missing").unwrap_err();
+        assert_eq!(e.to_string(), "answer 'missing': no answer");
+    }
+
+    #[test]
+    fn numeric_strings_fill_number_fields() {
+        let a = Answers::from([("port", PromptValue::from("8080"))]);
+        assert_eq!(a.get_as::<u16>("port").unwrap(), 8080);
+        assert_eq!(a.get_as::<String>("port").unwrap(), "8080");
+    }
+
+    #[test]
+    fn dates_fill_date_and_string_fields() {
+        let d = chrono::NaiveDate::from_ymd_opt(2024, 3, 1)
+            .unwrap()
+            .and_hms_opt(9, 30, 0)
+            .unwrap();
+        let a = Answers::from([("window", PromptValue::Date(d))]);
+        assert_eq!(a.get_as::<chrono::NaiveDateTime>("window").unwrap(), d);
+        assert_eq!(a.get_as::<String>("window").unwrap(), "2024-03-01T09:30:00");
+    }
+}
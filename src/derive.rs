@@ -0,0 +1,220 @@
+//! Field types for `#[derive(Prompt)]`.
+
+use crate::error::{Error, Result};
+use crate::prompts::{PromptValue, Question};
+
+/// A type `#[derive(Prompt)]` can ask for. Deriving `Prompt` on an enum of unit variants
+/// implements it as a select over the variants.
+pub trait PromptField: Sized {
+    /// The question asking for a value of this type.
+    fn question(name: &str, message: &str) -> Question;
+
+    /// Converts the answer to the question named `name`.
+    fn from_answer(name: &str, value: &PromptValue) -> Result<Self>;
+}
+
+fn mismatch(name: &str, expected: &str, value: &PromptValue) -> Error {
+    Error::Conversion {
+        name: name.to_string(),
+        message: format!("expected {}, got {:?}", expected, value),
+    }
+}
+
+impl PromptField for String {
+    fn question(name: &str, message: &str) -> Question {
+        Question::text(name, message).into()
+    }
+
+    fn from_answer(name: &str, value: &PromptValue) -> Result<Self> {
+        match value {
+            PromptValue::String(s) => Ok(s.clone()),
+            other => Err(mismatch(name, "text", other)),
+        }
+    }
+}
+
+impl PromptField for bool {
+    fn question(name: &str, message: &str) -> Question {
+        Question::confirm(name, message).into()
+    }
+
+    fn from_answer(name: &str, value: &PromptValue) -> Result<Self> {
+        match value {
+            PromptValue::Bool(b) => Ok(*b),
+            other => Err(mismatch(name, "yes or no", other)),
+        }
+    }
+}
+
+impl PromptField for Vec<String> {
+    fn question(name: &str, message: &str) -> Question {
+        Question::list(name, message).into()
+    }
+
+    fn from_answer(name: &str, value: &PromptValue) -> Result<Self> {
+        match value {
+            PromptValue::List(items) => Ok(items.clone()),
+            other => Err(mismatch(name, "a list", other)),
+        }
+    }
+}
+
+impl PromptField for chrono::NaiveDateTime {
+    fn question(name: &str, message: &str) -> Question {
+        Question::date(name, message).into()
+    }
+
+    fn from_answer(name: &str, value: &PromptValue) -> Result<Self> {
+        match value {
+            PromptValue::Date(d) => Ok(*d),
+            other => Err(mismatch(name, "a date", other)),
+        }
+    }
+}
+
+macro_rules! integer_fields {
+    ($($ty:ty)*) => {
+        $(
+            impl PromptField for $ty {
+                /// Whole numbers, limited to the range of the type.
+                fn question(name: &str, message: &str) -> Question {
+                    Question::number(name, message)
+                        .min(<$ty>::MIN as f64)
+                        .max(<$ty>::MAX as f64)
+                        .into()
+                }
+
+                fn from_answer(name: &str, value: &PromptValue) -> Result<Self> {
+                    match value {
+                        PromptValue::Float(n)
+                            if n.fract() == 0.0
+                                && *n >= <$ty>::MIN as f64
+                                && *n <= <$ty>::MAX as f64 =>
+                        {
+                            Ok(*n as $ty)
+                        }
+                        PromptValue::Float(n) => Err(Error::Conversion {
+                            name: name.to_string(),
+                            message: format!("{} does not fit {}", n, stringify!($ty)),
+                        }),
+                        other => Err(mismatch(name, "a number", other)),
+                    }
+                }
+            }
+        )*
+    };
+}
+
+integer_fields! { i8 i16 i32 i64 isize u8 u16 u32 u64 usize }
+
+macro_rules! float_fields {
+    ($($ty:ty)*) => {
+        $(
+            impl PromptField for $ty {
+                fn question(name: &str, message: &str) -> Question {
+                    let mut q: Question = Question::number(name, message).into();
+                    q.float = true;
+                    q
+                }
+
+                fn from_answer(name: &str, value: &PromptValue) -> Result<Self> {
+                    match value {
+                        PromptValue::Float(n) => Ok(*n as $ty),
+                        other => Err(mismatch(name, "a number", other)),
+                    }
+                }
+            }
+        )*
+    };
+}
+
+float_fields! { f32 f64 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::terminal::MemoryTerminal;
+    use crate::{Answers, Prompt};
+
+    #[derive(Debug, PartialEq, Prompt)]
+    enum Env {
+        #[prompt(title = "Staging (safe)")]
+        Staging,
+        Prod,
+    }
+
+    #[derive(Debug, PartialEq, Prompt)]
+    struct Setup {
+        #[prompt(message = "Project name?", initial = "demo", prefill)]
+        name: String,
+        #[prompt(password)]
+        token: String,
+        #[prompt(initial = 8080, min = 1024)]
+        port: u16,
+        ratio: f64,
+        #[prompt(initial = true)]
+        tls: bool,
+        #[prompt(initial = "Prod", hint = "Prod needs approval")]
+        env: Env,
+        db_tags: Vec<String>,
+    }
+
+    #[test]
+    fn field_types_pick_the_prompt_type() {
+        let questions = Setup::questions();
+        let types: Vec<_> = questions.iter().map(|q| q.type_name.as_str()).collect();
+        assert_eq!(
+            types,
+            [
+                "text", "password", "number", "number", "confirm", "select", "list"
+            ]
+        );
+        assert_eq!(questions[0].message, "Project name?");
+        assert_eq!(questions[0].initial_text.as_deref(), Some("demo"));
+        assert!(questions[0].prefill && !questions[1].prefill);
+        assert_eq!(questions[2].initial_number, Some(8080.0));
+        assert_eq!(
+            (questions[2].min, questions[2].max),
+            (Some(1024.0), Some(65535.0))
+        );
+        assert!(!questions[2].float && questions[3].float);
+        assert_eq!(questions[4].initial_bool, Some(true));
+        let choices = questions[5].choices.as_ref().unwrap();
+        assert_eq!(choices[0].title, "Staging (safe)");
+        assert_eq!(questions[5].initial_choice, Some(1));
+        assert_eq!(questions[5].hint.as_deref(), Some("Prod needs approval"));
+        assert_eq!(questions[6].message, "Db tags");
+    }
+
+    #[test]
+    fn prompt_builds_the_struct() {
+        let mut term = MemoryTerminal::new("\nsecret\n\n0.5\n\n1\na, b\n").tty(true);
+        let setup = Setup::prompt(&mut term).unwrap();
+        assert_eq!(
+            setup,
+            Setup {
+                name: "demo".into(),
+                token: "secret".into(),
+                port: 8080,
+                ratio: 0.5,
+                tls: true,
+                env: Env::Staging,
+                db_tags: vec!["a".into(), "b".into()],
+            }
+        );
+    }
+
+    #[test]
+    fn conversion_errors_name_the_field() {
+        let answers = Answers::from([("env", PromptValue::from("Dev"))]);
+        let e = Env::from_answer("env", &answers["env"]).unwrap_err();
+        assert_eq!(
+            e.to_string(),
+            "answer 'env': 'Dev' is not one of the choices"
+        );
+        let e = u8::from_answer("port", &PromptValue::Float(300.0)).unwrap_err();
+        assert_eq!(e.name(), Some("port"));
+        let e = Setup::from_answers(&answers).unwrap_err();
+        assert_eq!(e.to_string(), "answer 'name': no answer");
+    }
+}
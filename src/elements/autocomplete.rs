@@ -0,0 +1,637 @@
+//! Autocomplete prompt: typing filters the choices.
+
+use crate::elements::prompt::{self, Check, Frame, Prompt, State, Transition};
+use crate::elements::select::Choice;
+use crate::error::Result;
+use crate::prompts::{OnTimeout, Validator};
+#[cfg(feature = "tokio")]
+use crate::terminal::AsyncTerminal;
+use crate::terminal::Terminal;
+use crate::util::action::PromptAction;
+use crate::util::figures::Figures;
+use crate::util::line_editor::LineEditor;
+use crate::util::lines::display_width;
+use colour::write_cyan_bold;
+use std::fmt;
+use std::io::Write;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// How well a choice title matches the typed input.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Match {
+    /// Higher ranks first; equal scores keep the order of the choices.
+    pub score: i64,
+    /// Indices of the title's chars to highlight.
+    pub positions: Vec<usize>,
+}
+
+/// Function of `(input, title)` returning how the title matches, or `None`.
+pub type MatchFn = Arc<dyn Fn(&str, &str) -> Option<Match> + Send + Sync>;
+
+/// How typed input filters the choices of an autocomplete prompt. Case is ignored.
+#[derive(Clone, Default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "lowercase")
+)]
+pub enum Matcher {
+    /// The input's chars appear in order, not necessarily together; tight matches at word
+    /// starts rank first.
+    #[default]
+    Fuzzy,
+    /// The title starts with the input.
+    Prefix,
+    /// The title contains the input; earlier occurrences rank first.
+    Substring,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    Custom(MatchFn),
+}
+
+impl fmt::Debug for Matcher {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Matcher::Fuzzy => f.write_str("Fuzzy"),
+            Matcher::Prefix => f.write_str("Prefix"),
+            Matcher::Substring => f.write_str("Substring"),
+            Matcher::Custom(_) => f.write_str("Custom(..)"),
+        }
+    }
+}
+
+impl Matcher {
+    /// Matches `input` against `title`. Empty input matches every title.
+    pub fn matches(&self, input: &str, title: &str) -> Option<Match> {
+        if input.is_empty() {
+            return Some(Match {
+                score: 0,
+                positions: Vec::new(),
+            });
+        }
+        let folded = |s: &str| s.chars().map(fold).collect::<Vec<_>>();
+        match self {
+            Matcher::Fuzzy => fuzzy(&folded(input), title),
+            Matcher::Prefix => {
+                let input = folded(input);
+                folded(title).starts_with(&input).then(|| Match {
+                    score: 0,
+                    positions: (0..input.len()).collect(),
+                })
+            }
+            Matcher::Substring => {
+                let (input, title) = (folded(input), folded(title));
+                (0..=title.len().checked_sub(input.len())?)
+                    .find(|&start| title[start..].starts_with(&input))
+                    .map(|start| Match {
+                        score: -(start as i64),
+                        positions: (start..start + input.len()).collect(),
+                    })
+            }
+            Matcher::Custom(f) => f(input, title),
+        }
+    }
+}
+
+/// Lower-cases a char for comparison, keeping one char per char.
+fn fold(c: char) -> char {
+    c.to_lowercase().next().unwrap_or(c)
+}
+
+/// Best fuzzy match of the folded `input` in `title`, trying every start of its first char.
+fn fuzzy(input: &[char], title: &str) -> Option<Match> {
+    let original: Vec<char> = title.chars().collect();
+    let title: Vec<char> = original.iter().map(|&c| fold(c)).collect();
+    let mut best: Option<Match> = None;
+    for start in (0..title.len()).filter(|&i| title[i] == input[0]) {
+        let mut positions = vec![start];
+        let mut next = start + 1;
+        for &c in &input[1..] {
+            let Some(found) = title[next..].iter().position(|&t| t == c) else {
+                return best;
+            };
+            positions.push(next + found);
+            next += found + 1;
+        }
+        let score = fuzzy_score(&positions, &original);
+        if best.as_ref().is_none_or(|b| score > b.score) {
+            best = Some(Match { score, positions });
+        }
+    }
+    best
+}
+
+/// Rewards matched chars at word starts and next to each other; penalises gaps.
+fn fuzzy_score(positions: &[usize], title: &[char]) -> i64 {
+    let mut score = 0;
+    let mut prev: Option<usize> = None;
+    for &p in positions {
+        let boundary = p == 0
+            || !title[p - 1].is_alphanumeric()
+            || (title[p - 1].is_lowercase() && title[p].is_uppercase());
+        score += 1 + if boundary { 6 } else { 0 };
+        score += match prev {
+            Some(q) if p == q + 1 => 5,
+            Some(q) => -((p - q - 1) as i64),
+            None => -(p as i64),
+        };
+        prev = Some(p);
+    }
+    score
+}
+
+/// Autocomplete prompt options.
+#[derive(Clone)]
+pub struct AutocompletePromptOptions {
+    pub message: String,
+    pub choices: Vec<Choice>,
+    /// Index of the choice highlighted before anything is typed.
+    pub initial: Option<usize>,
+    pub matcher: Matcher,
+    /// Accepts input that is not one of the choices; it is offered under the matches.
+    pub allow_custom: bool,
+    /// Matches shown at once; the list scrolls past them.
+    pub limit: usize,
+    pub hint: Option<String>,
+    /// Rejects an answer with a message; the prompt is then asked again.
+    pub validate: Option<Validator>,
+    /// Rejected answers allowed before giving up; `None` retries forever.
+    pub max_attempts: Option<u32>,
+    /// Time left to answer; a countdown is shown and any key stops it.
+    pub timeout: Option<Duration>,
+    /// What happens when `timeout` runs out.
+    pub on_timeout: OnTimeout,
+}
+
+impl Default for AutocompletePromptOptions {
+    fn default() -> Self {
+        Self {
+            message: String::new(),
+            choices: Vec::new(),
+            initial: None,
+            matcher: Matcher::default(),
+            allow_custom: false,
+            limit: 10,
+            hint: None,
+            validate: None,
+            max_attempts: None,
+            timeout: None,
+            on_timeout: OnTimeout::default(),
+        }
+    }
+}
+
+/// Autocomplete prompt state: the typed input and the choices it matches.
+pub struct AutocompletePrompt {
+    opts: AutocompletePromptOptions,
+    fig: Figures,
+    input: LineEditor,
+    /// Matching choices, best first, as `(choice index, highlighted chars)`.
+    hits: Vec<(usize, Vec<usize>)>,
+    /// Highlighted row: a hit, or `hits.len()` for the typed value when it is offered.
+    selected: Option<usize>,
+}
+
+impl AutocompletePrompt {
+    pub fn new(opts: AutocompletePromptOptions) -> Self {
+        let mut p = Self {
+            opts,
+            fig: Figures::default(),
+            input: LineEditor::new(),
+            hits: Vec::new(),
+            selected: None,
+        };
+        p.filter();
+        p
+    }
+
+    /// Input the choices are matched against.
+    fn typed(&self) -> String {
+        self.input.text().trim().to_string()
+    }
+
+    /// Whether the typed input is offered as a value of its own.
+    fn offers_typed(&self) -> bool {
+        let typed = self.typed();
+        self.opts.allow_custom
+            && !typed.is_empty()
+            && !self
+                .hits
+                .iter()
+                .any(|(i, _)| self.opts.choices[*i].title.eq_ignore_ascii_case(&typed))
+    }
+
+    fn rows(&self) -> usize {
+        self.hits.len() + self.offers_typed() as usize
+    }
+
+    fn enabled(&self, row: usize) -> bool {
+        self.hits
+            .get(row)
+            .is_none_or(|(i, _)| !self.opts.choices[*i].disabled)
+    }
+
+    /// Matches the choices against the input and highlights the best enabled one.
+    fn filter(&mut self) {
+        let typed = self.typed();
+        let mut hits: Vec<(i64, usize, Vec<usize>)> = self
+            .opts
+            .choices
+            .iter()
+            .enumerate()
+            .filter_map(|(i, c)| {
+                let m = self.opts.matcher.matches(&typed, &c.title)?;
+                Some((m.score, i, m.positions))
+            })
+            .collect();
+        hits.sort_by_key(|(score, _, _)| -score);
+        self.hits = hits.into_iter().map(|(_, i, p)| (i, p)).collect();
+        let initial = self
+            .opts
+            .initial
+            .filter(|_| typed.is_empty())
+            .and_then(|initial| self.hits.iter().position(|(i, _)| *i == initial))
+            .filter(|&row| self.enabled(row));
+        self.selected = initial.or_else(|| (0..self.rows()).find(|&row| self.enabled(row)));
+    }
+
+    /// Moves the highlight to the nearest enabled row above or below.
+    fn step(&mut self, up: bool) -> bool {
+        let Some(current) = self.selected else {
+            return false;
+        };
+        let next = match up {
+            true => (0..current).rev().find(|&row| self.enabled(row)),
+            false => (current + 1..self.rows()).find(|&row| self.enabled(row)),
+        };
+        self.selected = next.or(self.selected);
+        next.is_some()
+    }
+
+    fn choice(&self, row: usize) -> Option<&Choice> {
+        self.hits.get(row).map(|(i, _)| &self.opts.choices[*i])
+    }
+
+    /// Title of `choice` with the chars at `positions` highlighted.
+    fn highlight(choice: &Choice, positions: &[usize]) -> String {
+        if choice.disabled {
+            return prompt::gray(&choice.title);
+        }
+        let mut buf = Vec::with_capacity(choice.title.len() + 16);
+        for (i, c) in choice.title.chars().enumerate() {
+            if positions.contains(&i) {
+                write_cyan_bold!(&mut buf, "{}", c).ok();
+            } else {
+                write!(&mut buf, "{}", c).ok();
+            }
+        }
+        String::from_utf8_lossy(&buf).into_owned()
+    }
+}
+
+impl Prompt for AutocompletePrompt {
+    type Output = String;
+
+    fn render(&self, state: &State) -> Frame {
+        let head = prompt::title(&self.opts.message, state);
+        if *state != State::Active {
+            let answer = match (state, self.selected.and_then(|row| self.choice(row))) {
+                (State::Done, Some(c)) => c.title.clone(),
+                _ => self.typed(),
+            };
+            return Frame::line(format!("{} {}", head, answer));
+        }
+        let cursor = (
+            0,
+            display_width(&head) + 1 + display_width(&self.input.before_cursor()),
+        );
+        let mut lines = vec![format!("{} {}", head, self.input.text())];
+        let rows = self.rows();
+        let limit = self.opts.limit.max(1);
+        let start = self
+            .selected
+            .map_or(0, |row| (row + 1).saturating_sub(limit))
+            .min(rows.saturating_sub(limit));
+        let end = rows.min(start + limit);
+        for row in start..end {
+            let pointer = match self.selected == Some(row) {
+                true => self.fig.pointer_small,
+                false => " ",
+            };
+            let title = match self.hits.get(row) {
+                Some((i, positions)) => Self::highlight(&self.opts.choices[*i], positions),
+                None => format!("{} {}", self.typed(), prompt::gray("(custom value)")),
+            };
+            lines.push(format!("  {} {}", pointer, title));
+        }
+        if rows == 0 {
+            lines.push(format!("    {}", prompt::gray("No matches")));
+        }
+        let mut hint = self
+            .opts
+            .hint
+            .clone()
+            .unwrap_or_else(|| "Type to filter, arrow keys to move. Return to submit.".into());
+        if rows > limit {
+            hint = format!("{} ({}-{} of {})", hint, start + 1, end, rows);
+        }
+        lines.push(format!("  {}", prompt::gray(&hint)));
+        Frame {
+            lines,
+            cursor: Some(cursor),
+        }
+    }
+
+    fn handle(&mut self, action: PromptAction) -> Transition<String> {
+        match action {
+            PromptAction::Submit => match self.selected {
+                Some(row) => match self.choice(row) {
+                    Some(c) => Transition::Submit(c.value.clone()),
+                    None => Transition::Submit(self.typed()),
+                },
+                None if self.hits.is_empty() => Transition::Invalid("no matching choice".into()),
+                None => Transition::Invalid("selected option is disabled".into()),
+            },
+            PromptAction::Up | PromptAction::Down => match self.step(action == PromptAction::Up) {
+                true => Transition::Continue,
+                false => Transition::Bell,
+            },
+            PromptAction::PrevPage | PromptAction::NextPage => {
+                let up = action == PromptAction::PrevPage;
+                let moved = (0..self.opts.limit.max(1))
+                    .take_while(|_| self.step(up))
+                    .count();
+                match moved {
+                    0 => Transition::Bell,
+                    _ => Transition::Continue,
+                }
+            }
+            PromptAction::Next => {
+                let Some(title) = self.selected.and_then(|row| self.choice(row)) else {
+                    return Transition::Bell;
+                };
+                let title = title.title.clone();
+                self.input.set(&title);
+                self.filter();
+                Transition::Continue
+            }
+            PromptAction::Reset => {
+                self.input.set("");
+                self.filter();
+                Transition::Continue
+            }
+            action => {
+                let before = self.input.text();
+                match self.input.apply(&action) {
+                    Some(true) => {
+                        if self.input.text() != before {
+                            self.filter();
+                        }
+                        Transition::Continue
+                    }
+                    _ => Transition::Bell,
+                }
+            }
+        }
+    }
+
+    fn input(&mut self, c: char) -> Transition<String> {
+        self.input.insert(c);
+        self.filter();
+        Transition::Continue
+    }
+}
+
+/// Runs autocomplete prompt. Returns the value of the chosen choice, or the typed input when
+/// `allow_custom` is set; `None` when cancelled.
+pub fn run_autocomplete<T: Terminal + ?Sized>(
+    opts: &AutocompletePromptOptions,
+    term: &mut T,
+) -> Result<Option<String>> {
+    let check = Check {
+        validate: opts.validate.as_ref(),
+        max_attempts: opts.max_attempts,
+        timeout: opts.timeout,
+        on_timeout: opts.on_timeout,
+        ..Default::default()
+    };
+    prompt::drive(&mut AutocompletePrompt::new(opts.clone()), term, &check).map(|o| o.ok())
+}
+
+/// `run_autocomplete` for async code: waits for keys without blocking the runtime.
+#[cfg(feature = "tokio")]
+pub async fn run_autocomplete_async<T: AsyncTerminal + ?Sized>(
+    opts: &AutocompletePromptOptions,
+    term: &mut T,
+) -> Result<Option<String>> {
+    let check = Check {
+        validate: opts.validate.as_ref(),
+        max_attempts: opts.max_attempts,
+        timeout: opts.timeout,
+        on_timeout: opts.on_timeout,
+        ..Default::default()
+    };
+    prompt::drive_async(&mut AutocompletePrompt::new(opts.clone()), term, &check)
+        .await
+        .map(|o| o.ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::terminal::MemoryTerminal;
+    use crate::util::strip::strip_ansi;
+
+    fn services() -> AutocompletePromptOptions {
+        AutocompletePromptOptions {
+            message: "Service?".into(),
+            choices: ["dashboard", "database", "db-proxy", "my-db", "web"]
+                .into_iter()
+                .map(|name| Choice::new(name, name))
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    fn typed(p: &mut AutocompletePrompt, s: &str) {
+        for c in s.chars() {
+            p.input(c);
+        }
+    }
+
+    fn titles(p: &AutocompletePrompt) -> Vec<&str> {
+        p.hits
+            .iter()
+            .map(|(i, _)| p.opts.choices[*i].title.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn fuzzy_prefers_tight_matches_at_word_starts() {
+        let m = Matcher::Fuzzy;
+        let score = |title| m.matches("db", title).map(|m| m.score);
+        assert!(score("db-proxy") > score("my-db"));
+        assert!(score("my-db") > score("database"));
+        assert_eq!(score("web"), None);
+        assert_eq!(m.matches("DB", "my-db").unwrap().positions, [3, 4]);
+        assert_eq!(m.matches("gw", "apiGateWay").unwrap().positions, [3, 7]);
+    }
+
+    #[test]
+    fn prefix_and_substring_ignore_case() {
+        assert_eq!(
+            Matcher::Prefix.matches("Da", "database").unwrap().positions,
+            [0, 1]
+        );
+        assert!(Matcher::Prefix.matches("base", "database").is_none());
+        let m = Matcher::Substring.matches("BASE", "database").unwrap();
+        assert_eq!(m.positions, [4, 5, 6, 7]);
+        assert!(
+            Matcher::Substring
+                .matches("databases", "database")
+                .is_none()
+        );
+        assert!(Matcher::Fuzzy.matches("", "anything").is_some());
+    }
+
+    #[test]
+    fn custom_matcher_gets_input_and_title() {
+        let m = Matcher::Custom(Arc::new(|input, title| {
+            title.ends_with(input).then(|| Match {
+                score: 0,
+                positions: Vec::new(),
+            })
+        }));
+        assert!(m.matches("db", "my-db").is_some());
+        assert!(m.matches("db", "db-proxy").is_none());
+    }
+
+    #[test]
+    fn typing_filters_and_selects_the_best_match() {
+        let mut p = AutocompletePrompt::new(services());
+        assert_eq!(titles(&p).len(), 5);
+        typed(&mut p, "db");
+        assert_eq!(titles(&p), ["db-proxy", "my-db", "dashboard", "database"]);
+        assert_eq!(p.selected, Some(0));
+        assert_eq!(p.handle(PromptAction::Down), Transition::Continue);
+        assert_eq!(
+            p.handle(PromptAction::Submit),
+            Transition::Submit("my-db".into())
+        );
+        p.handle(PromptAction::Delete);
+        assert_eq!(titles(&p).len(), 4);
+        assert_eq!(p.selected, Some(0));
+    }
+
+    #[test]
+    fn disabled_choices_are_shown_but_skipped() {
+        let mut opts = services();
+        opts.choices[2].disabled = true;
+        let mut p = AutocompletePrompt::new(opts);
+        typed(&mut p, "db");
+        assert_eq!(titles(&p)[0], "db-proxy");
+        assert_eq!(p.selected, Some(1));
+        assert_eq!(p.handle(PromptAction::Up), Transition::Bell);
+        p.handle(PromptAction::Reset);
+        typed(&mut p, "db-p");
+        assert_eq!(titles(&p), ["db-proxy"]);
+        assert_eq!(p.selected, None);
+        assert_eq!(
+            p.handle(PromptAction::Submit),
+            Transition::Invalid("selected option is disabled".into())
+        );
+        let text = strip_ansi(&p.render(&State::Active).lines.join("\n"));
+        assert!(text.contains("db-proxy"));
+    }
+
+    #[test]
+    fn no_match_is_invalid_unless_custom_values_are_allowed() {
+        let mut p = AutocompletePrompt::new(services());
+        typed(&mut p, "zzz");
+        assert_eq!(
+            p.handle(PromptAction::Submit),
+            Transition::Invalid("no matching choice".into())
+        );
+        let mut p = AutocompletePrompt::new(AutocompletePromptOptions {
+            allow_custom: true,
+            ..services()
+        });
+        typed(&mut p, "we");
+        assert_eq!(p.rows(), 2);
+        assert_eq!(p.selected, Some(0));
+        p.handle(PromptAction::Down);
+        assert!(strip_ansi(&p.render(&State::Active).lines[2]).contains("we (custom value)"));
+        assert_eq!(
+            p.handle(PromptAction::Submit),
+            Transition::Submit("we".into())
+        );
+        p.input('b');
+        assert_eq!(p.rows(), 1, "an exact title is not offered twice");
+    }
+
+    #[test]
+    fn tab_completes_the_highlighted_title() {
+        let mut p = AutocompletePrompt::new(services());
+        typed(&mut p, "prox");
+        p.handle(PromptAction::Next);
+        assert_eq!(p.input.text(), "db-proxy");
+        p.handle(PromptAction::Reset);
+        assert_eq!(p.input.text(), "");
+        assert_eq!(titles(&p).len(), 5);
+    }
+
+    #[test]
+    fn initial_is_highlighted_until_typing() {
+        let mut p = AutocompletePrompt::new(AutocompletePromptOptions {
+            initial: Some(4),
+            ..services()
+        });
+        assert_eq!(p.selected, Some(4));
+        typed(&mut p, "d");
+        assert_eq!(p.selected, Some(0));
+    }
+
+    #[test]
+    fn render_highlights_matches_and_scrolls() {
+        let mut p = AutocompletePrompt::new(AutocompletePromptOptions {
+            limit: 2,
+            ..services()
+        });
+        let frame = p.render(&State::Active);
+        assert_eq!(frame.lines.len(), 4);
+        assert!(strip_ansi(&frame.lines[3]).contains("(1-2 of 5)"));
+        p.handle(PromptAction::NextPage);
+        p.handle(PromptAction::Down);
+        let lines: Vec<_> = p
+            .render(&State::Active)
+            .lines
+            .iter()
+            .map(|l| strip_ansi(l))
+            .collect();
+        assert!(lines[1].ends_with("db-proxy") && lines[2].ends_with("my-db"));
+        assert!(lines[3].contains("(3-4 of 5)"));
+        typed(&mut p, "web");
+        let frame = p.render(&State::Active);
+        assert_eq!(frame.cursor, Some((0, display_width(&frame.lines[0]))));
+        assert_eq!(AutocompletePrompt::highlight(&p.opts.choices[4], &[0]), {
+            let mut buf = Vec::new();
+            write_cyan_bold!(&mut buf, "w").ok();
+            format!("{}eb", String::from_utf8_lossy(&buf))
+        });
+    }
+
+    #[test]
+    fn run_autocomplete_types_and_submits() {
+        let mut term = MemoryTerminal::new("dtb\n");
+        assert_eq!(
+            run_autocomplete(&services(), &mut term).unwrap().as_deref(),
+            Some("database")
+        );
+        let mut term = MemoryTerminal::new("db\x1b[B\r").tty(true);
+        assert_eq!(
+            run_autocomplete(&services(), &mut term).unwrap().as_deref(),
+            Some("my-db")
+        );
+        assert!(strip_ansi(term.output()).contains("Service? … my-db"));
+    }
+}
@@ -1,10 +1,18 @@
 //! Yes/no confirm prompt.
 
-use crate::util::style;
-use colour::{write_bold, write_gray};
-use std::io::{self, BufRead, Write};
+use crate::elements::prompt::{self, Check, Frame, Prompt, State, Transition};
+use crate::error::Result;
+use crate::prompts::{OnTimeout, Validator};
+#[cfg(feature = "tokio")]
+use crate::terminal::AsyncTerminal;
+use crate::terminal::Terminal;
+use crate::util::action::PromptAction;
+use colour::write_gray;
+use std::io::Write;
+use std::time::Duration;
 
 /// Confirm prompt options.
+#[derive(Clone)]
 pub struct ConfirmPromptOptions {
     pub message: String,
     pub initial: bool,
@@ -12,6 +20,14 @@ pub struct ConfirmPromptOptions {
     pub no_msg: String,
     pub yes_option: String,
     pub no_option: String,
+    /// Rejects an answer with a message; the prompt is then asked again.
+    pub validate: Option<Validator>,
+    /// Rejected answers allowed before giving up; `None` retries forever.
+    pub max_attempts: Option<u32>,
+    /// Time left to answer; a countdown is shown and any key stops it.
+    pub timeout: Option<Duration>,
+    /// What happens when `timeout` runs out.
+    pub on_timeout: OnTimeout,
 }
 
 impl Default for ConfirmPromptOptions {
@@ -23,55 +39,164 @@ impl Default for ConfirmPromptOptions {
             no_msg: "no".into(),
             yes_option: "(Y/n)".into(),
             no_option: "(y/N)".into(),
+            validate: None,
+            max_attempts: None,
+            timeout: None,
+            on_timeout: OnTimeout::default(),
         }
     }
 }
 
-/// Runs confirm prompt. Returns true for yes, false for no.
-pub fn run_confirm<R: BufRead, W: Write>(
+/// Confirm prompt state: the current default answer and any typed reply.
+pub struct ConfirmPrompt {
+    opts: ConfirmPromptOptions,
+    value: bool,
+    typed: String,
+}
+
+impl ConfirmPrompt {
+    pub fn new(opts: ConfirmPromptOptions) -> Self {
+        let value = opts.initial;
+        Self {
+            opts,
+            value,
+            typed: String::new(),
+        }
+    }
+
+    /// Returns the answer: `y`/`yes` when typed, the current default when empty.
+    pub fn answer(&self) -> bool {
+        let raw = self.typed.trim().to_lowercase();
+        if raw.is_empty() {
+            self.value
+        } else {
+            raw == "y" || raw == "yes"
+        }
+    }
+}
+
+impl Prompt for ConfirmPrompt {
+    type Output = bool;
+
+    fn render(&self, state: &State) -> Frame {
+        let head = prompt::title(&self.opts.message, state);
+        if *state != State::Active {
+            let result: &str = if self.answer() {
+                &self.opts.yes_msg
+            } else {
+                &self.opts.no_msg
+            };
+            return Frame::line(format!("{} {}", head, result));
+        }
+        let hint = if self.value {
+            &self.opts.yes_option
+        } else {
+            &self.opts.no_option
+        };
+        let mut gray_buf = Vec::with_capacity(hint.len() + 16);
+        write_gray!(&mut gray_buf, "{}", hint).ok();
+        let hint_styled = String::from_utf8_lossy(&gray_buf).into_owned();
+        if self.typed.is_empty() {
+            Frame::line(format!("{} {}", head, hint_styled))
+        } else {
+            Frame::line(format!("{} {} {}", head, hint_styled, self.typed))
+        }
+    }
+
+    fn handle(&mut self, action: PromptAction) -> Transition<bool> {
+        match action {
+            PromptAction::Submit => Transition::Submit(self.answer()),
+            PromptAction::Delete if !self.typed.is_empty() => {
+                self.typed.pop();
+                Transition::Continue
+            }
+            PromptAction::Next => {
+                self.value = !self.value;
+                self.typed.clear();
+                Transition::Continue
+            }
+            PromptAction::Reset => {
+                self.value = self.opts.initial;
+                self.typed.clear();
+                Transition::Continue
+            }
+            _ => Transition::Bell,
+        }
+    }
+
+    fn input(&mut self, c: char) -> Transition<bool> {
+        self.typed.push(c);
+        Transition::Continue
+    }
+}
+
+/// Runs confirm prompt. Returns true for yes, false for no; `None` when cancelled.
+pub fn run_confirm<T: Terminal + ?Sized>(
     opts: &ConfirmPromptOptions,
-    stdin: &mut R,
-    stdout: &mut W,
-) -> io::Result<bool> {
-    let mut buf = Vec::with_capacity(opts.message.len() + 32);
-    write_bold!(&mut buf, "{}", opts.message).ok();
-    let msg = String::from_utf8_lossy(&buf).into_owned();
-    let hint = if opts.initial {
-        &opts.yes_option
-    } else {
-        &opts.no_option
+    term: &mut T,
+) -> Result<Option<bool>> {
+    let check = Check {
+        validate: opts.validate.as_ref(),
+        max_attempts: opts.max_attempts,
+        timeout: opts.timeout,
+        on_timeout: opts.on_timeout,
+        ..Default::default()
     };
-    let mut gray_buf = Vec::with_capacity(hint.len() + 16);
-    write_gray!(&mut gray_buf, "{}", hint).ok();
-    let hint_styled = String::from_utf8_lossy(&gray_buf).into_owned();
-    let symbol = style::symbol(false, false, false);
-    let delim = style::delimiter(false);
-    write!(stdout, "{} {} {} {}", symbol, msg, delim, hint_styled)?;
-    stdout.flush()?;
-    let mut line = String::new();
-    stdin.read_line(&mut line)?;
-    let raw = line.trim().to_lowercase();
-    let value = if raw.is_empty() {
-        opts.initial
-    } else {
-        raw == "y" || raw == "yes"
+    prompt::drive(&mut ConfirmPrompt::new(opts.clone()), term, &check).map(|o| o.ok())
+}
+
+/// `run_confirm` for async code: waits for keys without blocking the runtime.
+#[cfg(feature = "tokio")]
+pub async fn run_confirm_async<T: AsyncTerminal + ?Sized>(
+    opts: &ConfirmPromptOptions,
+    term: &mut T,
+) -> Result<Option<bool>> {
+    let check = Check {
+        validate: opts.validate.as_ref(),
+        max_attempts: opts.max_attempts,
+        timeout: opts.timeout,
+        on_timeout: opts.on_timeout,
+        ..Default::default()
     };
-    let result_str: &str = if value { &opts.yes_msg } else { &opts.no_msg };
-    let done_symbol = style::symbol(true, false, false);
-    let done_delim = style::delimiter(true);
-    writeln!(
-        stdout,
-        "\r{} {} {} {}",
-        done_symbol, msg, done_delim, result_str
-    )?;
-    stdout.flush()?;
-    Ok(value)
+    prompt::drive_async(&mut ConfirmPrompt::new(opts.clone()), term, &check)
+        .await
+        .map(|o| o.ok())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::io::Cursor;
+    use crate::terminal::MemoryTerminal;
+
+    #[test]
+    fn confirm_prompt_tab_flips_default() {
+        let mut p = ConfirmPrompt::new(ConfirmPromptOptions::default());
+        assert_eq!(p.handle(PromptAction::Next), Transition::Continue);
+        assert_eq!(p.handle(PromptAction::Submit), Transition::Submit(true));
+        p.handle(PromptAction::Reset);
+        assert_eq!(p.handle(PromptAction::Submit), Transition::Submit(false));
+    }
+
+    #[test]
+    fn confirm_prompt_typed_reply_wins() {
+        let mut p = ConfirmPrompt::new(ConfirmPromptOptions {
+            initial: true,
+            ..Default::default()
+        });
+        p.input('n');
+        assert_eq!(p.handle(PromptAction::Submit), Transition::Submit(false));
+        p.handle(PromptAction::Delete);
+        assert_eq!(p.handle(PromptAction::Delete), Transition::Bell);
+        assert_eq!(p.handle(PromptAction::Submit), Transition::Submit(true));
+    }
+
+    #[test]
+    fn confirm_prompt_render_shows_hint_for_default() {
+        let mut p = ConfirmPrompt::new(ConfirmPromptOptions::default());
+        assert!(p.render(&State::Active).lines[0].contains("(y/N)"));
+        p.handle(PromptAction::Next);
+        assert!(p.render(&State::Active).lines[0].contains("(Y/n)"));
+    }
 
     #[test]
     fn confirm_prompt_options_default() {
@@ -91,11 +216,10 @@ mod tests {
             initial: false,
             ..Default::default()
         };
-        let mut stdin = Cursor::new(b"yes\n");
-        let mut stdout = Vec::new();
-        let r = run_confirm(&opts, &mut stdin, &mut stdout);
+        let mut term = MemoryTerminal::new("yes\n");
+        let r = run_confirm(&opts, &mut term);
         assert!(r.is_ok());
-        assert_eq!(r.unwrap(), true);
+        assert!(r.unwrap().unwrap());
     }
 
     #[test]
@@ -104,9 +228,8 @@ mod tests {
             message: "Ok?".into(),
             ..Default::default()
         };
-        let mut stdin = Cursor::new(b"y\n");
-        let mut stdout = Vec::new();
-        assert_eq!(run_confirm(&opts, &mut stdin, &mut stdout).unwrap(), true);
+        let mut term = MemoryTerminal::new("y\n");
+        assert!(run_confirm(&opts, &mut term).unwrap().unwrap());
     }
 
     #[test]
@@ -116,11 +239,10 @@ mod tests {
             initial: true,
             ..Default::default()
         };
-        let mut stdin = Cursor::new(b"n\n");
-        let mut stdout = Vec::new();
-        let r = run_confirm(&opts, &mut stdin, &mut stdout);
+        let mut term = MemoryTerminal::new("n\n");
+        let r = run_confirm(&opts, &mut term);
         assert!(r.is_ok());
-        assert_eq!(r.unwrap(), false);
+        assert!(!r.unwrap().unwrap());
     }
 
     #[test]
@@ -130,9 +252,8 @@ mod tests {
             initial: true,
             ..Default::default()
         };
-        let mut stdin = Cursor::new(b"\n");
-        let mut stdout = Vec::new();
-        assert_eq!(run_confirm(&opts, &mut stdin, &mut stdout).unwrap(), true);
+        let mut term = MemoryTerminal::new("\n");
+        assert!(run_confirm(&opts, &mut term).unwrap().unwrap());
     }
 
     #[test]
@@ -144,10 +265,9 @@ mod tests {
             no_msg: "cancelled".into(),
             ..Default::default()
         };
-        let mut stdin = Cursor::new(b"y\n");
-        let mut stdout = Vec::new();
-        assert!(run_confirm(&opts, &mut stdin, &mut stdout).unwrap());
-        let out = String::from_utf8(stdout).unwrap();
+        let mut term = MemoryTerminal::new("y\n");
+        assert!(run_confirm(&opts, &mut term).unwrap().unwrap());
+        let out = term.output();
         assert!(out.contains("confirmed"));
     }
 
@@ -158,9 +278,8 @@ mod tests {
             initial: true,
             ..Default::default()
         };
-        let mut stdin = Cursor::new(b"no\n");
-        let mut stdout = Vec::new();
-        assert_eq!(run_confirm(&opts, &mut stdin, &mut stdout).unwrap(), false);
+        let mut term = MemoryTerminal::new("no\n");
+        assert!(!run_confirm(&opts, &mut term).unwrap().unwrap());
     }
 
     #[test]
@@ -170,9 +289,8 @@ mod tests {
             initial: true,
             ..Default::default()
         };
-        let mut stdin = Cursor::new(b"n\n");
-        let mut stdout = Vec::new();
-        assert_eq!(run_confirm(&opts, &mut stdin, &mut stdout).unwrap(), false);
+        let mut term = MemoryTerminal::new("n\n");
+        assert!(!run_confirm(&opts, &mut term).unwrap().unwrap());
     }
 
     #[test]
@@ -182,9 +300,8 @@ mod tests {
             initial: false,
             ..Default::default()
         };
-        let mut stdin = Cursor::new(b"\n");
-        let mut stdout = Vec::new();
-        assert_eq!(run_confirm(&opts, &mut stdin, &mut stdout).unwrap(), false);
+        let mut term = MemoryTerminal::new("\n");
+        assert!(!run_confirm(&opts, &mut term).unwrap().unwrap());
     }
 
     #[test]
@@ -193,8 +310,7 @@ mod tests {
             message: "?".into(),
             ..Default::default()
         };
-        let mut stdin = Cursor::new(b"YES\n");
-        let mut stdout = Vec::new();
-        assert_eq!(run_confirm(&opts, &mut stdin, &mut stdout).unwrap(), true);
+        let mut term = MemoryTerminal::new("YES\n");
+        assert!(run_confirm(&opts, &mut term).unwrap().unwrap());
     }
 }
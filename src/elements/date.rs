@@ -0,0 +1,831 @@
+//! Date and time prompt.
+
+use crate::elements::prompt::{self, Check, Frame, Prompt, State, Transition};
+use crate::error::Result;
+use crate::prompts::{OnTimeout, Validator};
+#[cfg(feature = "tokio")]
+use crate::terminal::AsyncTerminal;
+use crate::terminal::Terminal;
+use crate::util::action::PromptAction;
+use crate::util::line_editor::LineEditor;
+use crate::util::lines::display_width;
+use chrono::{
+    Datelike, Days, Local, Months, NaiveDate, NaiveDateTime, NaiveTime, TimeDelta, Timelike,
+};
+use colour::write_cyan;
+use std::io::Write;
+use std::time::Duration;
+
+/// Date prompt options.
+#[derive(Clone)]
+pub struct DatePromptOptions {
+    pub message: String,
+    /// Starting value; defaults to the current local time.
+    pub initial: Option<NaiveDateTime>,
+    /// Layout of the value: `YYYY`, `MM`, `DD`, `HH`, `mm` and `ss` are edited, anything else
+    /// is shown as is. Defaults to `YYYY-MM-DD HH:mm`.
+    pub mask: String,
+    /// Earliest value that may be submitted.
+    pub min: Option<NaiveDateTime>,
+    /// Latest value that may be submitted.
+    pub max: Option<NaiveDateTime>,
+    pub hint: Option<String>,
+    /// Rejects an answer with a message; the prompt is then asked again.
+    pub validate: Option<Validator>,
+    /// Rejected answers allowed before giving up; `None` retries forever.
+    pub max_attempts: Option<u32>,
+    /// Time left to answer; a countdown is shown and any key stops it.
+    pub timeout: Option<Duration>,
+    /// What happens when `timeout` runs out.
+    pub on_timeout: OnTimeout,
+}
+
+impl Default for DatePromptOptions {
+    fn default() -> Self {
+        Self {
+            message: String::new(),
+            initial: None,
+            mask: "YYYY-MM-DD HH:mm".into(),
+            min: None,
+            max: None,
+            hint: None,
+            validate: None,
+            max_attempts: None,
+            timeout: None,
+            on_timeout: OnTimeout::default(),
+        }
+    }
+}
+
+/// Part of a date edited as one segment.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Unit {
+    Year,
+    Month,
+    Day,
+    Hour,
+    Minute,
+    Second,
+}
+
+impl Unit {
+    /// Digits shown for the unit.
+    fn width(self) -> usize {
+        match self {
+            Unit::Year => 4,
+            _ => 2,
+        }
+    }
+
+    fn get(self, v: &NaiveDateTime) -> i64 {
+        match self {
+            Unit::Year => v.year() as i64,
+            Unit::Month => v.month() as i64,
+            Unit::Day => v.day() as i64,
+            Unit::Hour => v.hour() as i64,
+            Unit::Minute => v.minute() as i64,
+            Unit::Second => v.second() as i64,
+        }
+    }
+
+    /// `v` with the unit set to `n`. Changing the year or month keeps the day within the
+    /// month, so Jan 31 becomes Feb 28 or 29; an impossible value gives `None`.
+    fn set(self, v: &NaiveDateTime, n: u32) -> Option<NaiveDateTime> {
+        let (mut year, mut month, mut day) = (v.year(), v.month(), v.day());
+        let (mut hour, mut minute, mut second) = (v.hour(), v.minute(), v.second());
+        match self {
+            Unit::Year => year = i32::try_from(n).ok()?,
+            Unit::Month => month = n,
+            Unit::Day => day = n,
+            Unit::Hour => hour = n,
+            Unit::Minute => minute = n,
+            Unit::Second => second = n,
+        }
+        if self != Unit::Day {
+            day = day.min(days_in_month(year, month)?);
+        }
+        NaiveDate::from_ymd_opt(year, month, day)?.and_hms_opt(hour, minute, second)
+    }
+
+    /// `v` moved by `delta` units, carrying into the larger units.
+    fn step(self, v: &NaiveDateTime, delta: i32) -> Option<NaiveDateTime> {
+        let n = delta.unsigned_abs();
+        match self {
+            Unit::Year | Unit::Month => {
+                let months = Months::new(match self {
+                    Unit::Year => n.checked_mul(12)?,
+                    _ => n,
+                });
+                match delta < 0 {
+                    true => v.checked_sub_months(months),
+                    false => v.checked_add_months(months),
+                }
+            }
+            Unit::Day => match delta < 0 {
+                true => v.checked_sub_days(Days::new(n as u64)),
+                false => v.checked_add_days(Days::new(n as u64)),
+            },
+            Unit::Hour => v.checked_add_signed(TimeDelta::hours(delta as i64)),
+            Unit::Minute => v.checked_add_signed(TimeDelta::minutes(delta as i64)),
+            Unit::Second => v.checked_add_signed(TimeDelta::seconds(delta as i64)),
+        }
+    }
+}
+
+fn days_in_month(year: i32, month: u32) -> Option<u32> {
+    (28..=31)
+        .rev()
+        .find(|&day| NaiveDate::from_ymd_opt(year, month, day).is_some())
+}
+
+/// Piece of a mask: a segment or literal text between segments.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    Field(Unit),
+    Text(String),
+}
+
+fn tokens(mask: &str) -> Vec<Token> {
+    const FIELDS: [(&str, Unit); 6] = [
+        ("YYYY", Unit::Year),
+        ("MM", Unit::Month),
+        ("DD", Unit::Day),
+        ("HH", Unit::Hour),
+        ("mm", Unit::Minute),
+        ("ss", Unit::Second),
+    ];
+    let mut out = Vec::new();
+    let mut rest = mask;
+    while let Some(c) = rest.chars().next() {
+        match FIELDS.iter().find(|(pattern, _)| rest.starts_with(pattern)) {
+            Some((pattern, unit)) => {
+                out.push(Token::Field(*unit));
+                rest = &rest[pattern.len()..];
+            }
+            None => {
+                match out.last_mut() {
+                    Some(Token::Text(text)) => text.push(c),
+                    _ => out.push(Token::Text(c.to_string())),
+                }
+                rest = &rest[c.len_utf8()..];
+            }
+        }
+    }
+    out
+}
+
+/// `v` with the time units the mask does not show set to zero.
+fn truncate(v: NaiveDateTime, tokens: &[Token]) -> NaiveDateTime {
+    let shown = |unit| tokens.contains(&Token::Field(unit));
+    let time = NaiveTime::from_hms_opt(
+        if shown(Unit::Hour) { v.hour() } else { 0 },
+        if shown(Unit::Minute) { v.minute() } else { 0 },
+        if shown(Unit::Second) { v.second() } else { 0 },
+    );
+    v.date().and_time(time.unwrap_or_default())
+}
+
+/// Reads a date laid out like `mask`; units it leaves out are taken from `now`'s date at
+/// midnight.
+fn parse_masked(input: &str, tokens: &[Token], now: NaiveDateTime) -> Option<NaiveDateTime> {
+    let mut values = Vec::new();
+    let mut rest = input;
+    for token in tokens {
+        match token {
+            Token::Text(text) => rest = rest.strip_prefix(text.as_str())?,
+            Token::Field(unit) => {
+                let digits = rest
+                    .bytes()
+                    .take(unit.width())
+                    .take_while(u8::is_ascii_digit)
+                    .count();
+                values.push((*unit, rest[..digits].parse::<u32>().ok()?));
+                rest = &rest[digits..];
+            }
+        }
+    }
+    if !rest.is_empty() {
+        return None;
+    }
+    values.sort();
+    let start = now.date().and_time(NaiveTime::MIN);
+    values
+        .into_iter()
+        .try_fold(start, |v, (unit, n)| unit.set(&v, n))
+}
+
+/// Reads one word of relative input: `now`, `today`, `tomorrow`, `yesterday`, a time such as
+/// `09:00`, or an offset such as `+3d`.
+fn apply_word(v: NaiveDateTime, word: &str, now: NaiveDateTime) -> Option<NaiveDateTime> {
+    match word {
+        "now" => return Some(now),
+        "today" => return Some(now.date().and_time(v.time())),
+        "tomorrow" => return now.date().succ_opt().map(|d| d.and_time(v.time())),
+        "yesterday" => return now.date().pred_opt().map(|d| d.and_time(v.time())),
+        _ => {}
+    }
+    if let Some(time) = ["%H:%M:%S", "%H:%M"]
+        .iter()
+        .find_map(|f| NaiveTime::parse_from_str(word, f).ok())
+    {
+        return Some(v.date().and_time(time));
+    }
+    let sign = match word.chars().next()? {
+        '+' => 1,
+        '-' => -1,
+        _ => return None,
+    };
+    let body = &word[1..];
+    let split = body.find(|c: char| !c.is_ascii_digit())?;
+    let n: i32 = body[..split].parse().ok()?;
+    let (unit, n) = match &body[split..] {
+        "y" => (Unit::Year, n),
+        "mo" => (Unit::Month, n),
+        "w" => (Unit::Day, n.checked_mul(7)?),
+        "d" => (Unit::Day, n),
+        "h" => (Unit::Hour, n),
+        "m" => (Unit::Minute, n),
+        "s" => (Unit::Second, n),
+        _ => return None,
+    };
+    unit.step(&v, sign * n)
+}
+
+/// Reads typed input: a date laid out like the mask, or one relative to `now` made of words
+/// such as `now`, `today`, `tomorrow 09:00`, `+3d` or `-2w 14:30`.
+///
+/// Offsets take `y`, `mo`, `w`, `d`, `h`, `m` (minutes) or `s`; relative input keeps the
+/// current time unless a time is given.
+pub(crate) fn parse_date(input: &str, mask: &str, now: NaiveDateTime) -> Option<NaiveDateTime> {
+    let tokens = tokens(mask);
+    let input = input.trim();
+    if let Some(v) = parse_masked(input, &tokens, now) {
+        return Some(v);
+    }
+    let lower = input.to_lowercase();
+    let mut words = lower.split_whitespace().peekable();
+    words.peek()?;
+    let v = words.try_fold(now, |v, word| apply_word(v, word, now))?;
+    Some(truncate(v, &tokens))
+}
+
+fn in_range(value: NaiveDateTime, min: Option<NaiveDateTime>, max: Option<NaiveDateTime>) -> bool {
+    min.is_none_or(|min| value >= min) && max.is_none_or(|max| value <= max)
+}
+
+/// Error shown when `value` is outside `min`/`max`, formatted with `mask`.
+pub(crate) fn out_of_range(
+    value: NaiveDateTime,
+    min: Option<NaiveDateTime>,
+    max: Option<NaiveDateTime>,
+    mask: &str,
+) -> Option<String> {
+    let tokens = tokens(mask);
+    match (min, max) {
+        (Some(min), _) if value < min => Some(format!("Enter {} or later", show(&tokens, &min))),
+        (_, Some(max)) if value > max => Some(format!("Enter {} or earlier", show(&tokens, &max))),
+        _ => None,
+    }
+}
+
+/// `v` laid out by the mask `tokens`.
+fn show(tokens: &[Token], v: &NaiveDateTime) -> String {
+    tokens
+        .iter()
+        .map(|token| match token {
+            Token::Field(unit) => format!("{:0w$}", unit.get(v), w = unit.width()),
+            Token::Text(text) => text.clone(),
+        })
+        .collect()
+}
+
+/// Date prompt state: the value, the segment being edited and any typed text.
+pub struct DatePrompt {
+    opts: DatePromptOptions,
+    tokens: Vec<Token>,
+    fields: Vec<Unit>,
+    /// Time relative input is counted from.
+    now: NaiveDateTime,
+    value: NaiveDateTime,
+    segment: usize,
+    /// Digits typed into the current segment.
+    digits: String,
+    /// Whether segments were typed into since the last reset; `+` and `-` are then mask
+    /// separators rather than the start of relative input.
+    typed: bool,
+    /// Free text such as `tomorrow 09:00`, started by typing a letter, `+` or `-`.
+    text: Option<LineEditor>,
+    /// Set by [`Prompt::line_mode`]: input stays typed text even when emptied or reset.
+    line_mode: bool,
+}
+
+impl DatePrompt {
+    pub fn new(opts: DatePromptOptions) -> Self {
+        let tokens = tokens(&opts.mask);
+        let fields = tokens
+            .iter()
+            .filter_map(|token| match token {
+                Token::Field(unit) => Some(*unit),
+                Token::Text(_) => None,
+            })
+            .collect();
+        let now = truncate(Local::now().naive_local(), &tokens);
+        let value = opts.initial.unwrap_or(now);
+        Self {
+            opts,
+            tokens,
+            fields,
+            now,
+            value,
+            segment: 0,
+            digits: String::new(),
+            typed: false,
+            text: None,
+            line_mode: false,
+        }
+    }
+
+    /// The current value.
+    pub fn value(&self) -> NaiveDateTime {
+        self.value
+    }
+
+    fn move_to(&mut self, segment: usize) -> Transition<NaiveDateTime> {
+        self.digits.clear();
+        if segment == self.segment || segment >= self.fields.len() {
+            return Transition::Bell;
+        }
+        self.segment = segment;
+        Transition::Continue
+    }
+
+    /// Moves the current segment by `delta`; a step past `min` or `max` rings the bell.
+    fn step(&mut self, delta: i32) -> Transition<NaiveDateTime> {
+        self.digits.clear();
+        let stepped = self
+            .fields
+            .get(self.segment)
+            .and_then(|unit| unit.step(&self.value, delta))
+            .filter(|v| in_range(*v, self.opts.min, self.opts.max));
+        match stepped {
+            Some(v) => {
+                self.value = v;
+                Transition::Continue
+            }
+            None => Transition::Bell,
+        }
+    }
+
+    /// Types a digit into the current segment, moving on once it is full.
+    fn type_digit(&mut self, c: char) -> Transition<NaiveDateTime> {
+        let Some(&unit) = self.fields.get(self.segment) else {
+            return Transition::Bell;
+        };
+        self.typed = true;
+        self.digits.push(c);
+        let full = self.digits.len() >= unit.width();
+        match self
+            .digits
+            .parse()
+            .ok()
+            .and_then(|n| unit.set(&self.value, n))
+        {
+            Some(v) => self.value = v,
+            // A leading zero of a month or day is not a value yet.
+            None if !full => return Transition::Continue,
+            None => {
+                self.digits.clear();
+                return Transition::Bell;
+            }
+        }
+        if full {
+            self.digits.clear();
+            if self.segment + 1 < self.fields.len() {
+                self.segment += 1;
+            }
+        }
+        Transition::Continue
+    }
+
+    fn submit(&mut self) -> Transition<NaiveDateTime> {
+        if let Some(text) = self.text.as_ref().filter(|text| !text.is_empty()) {
+            match parse_date(&text.text(), &self.opts.mask, self.now) {
+                Some(v) => {
+                    self.value = v;
+                    self.text = None;
+                    self.digits.clear();
+                }
+                None => return Transition::Invalid(format!("'{}' is not a date", text.text())),
+            }
+        }
+        match out_of_range(self.value, self.opts.min, self.opts.max, &self.opts.mask) {
+            Some(message) => Transition::Invalid(message),
+            None => Transition::Submit(self.value),
+        }
+    }
+
+    fn reset(&mut self) {
+        self.value = self.opts.initial.unwrap_or(self.now);
+        self.segment = 0;
+        self.digits.clear();
+        self.typed = false;
+        self.text = self.line_mode.then(LineEditor::new);
+    }
+
+    /// The value laid out by the mask, with the current segment in cyan.
+    fn styled(&self) -> String {
+        let mut out = String::new();
+        let mut field = 0;
+        for token in &self.tokens {
+            match token {
+                Token::Text(text) => out.push_str(text),
+                Token::Field(unit) => {
+                    let text = format!("{:0w$}", unit.get(&self.value), w = unit.width());
+                    match field == self.segment {
+                        true => {
+                            let mut buf = Vec::new();
+                            write_cyan!(&mut buf, "{}", text).ok();
+                            out.push_str(&String::from_utf8_lossy(&buf));
+                        }
+                        false => out.push_str(&text),
+                    }
+                    field += 1;
+                }
+            }
+        }
+        out
+    }
+}
+
+impl Prompt for DatePrompt {
+    type Output = NaiveDateTime;
+
+    fn render(&self, state: &State) -> Frame {
+        let head = prompt::title(&self.opts.message, state);
+        if *state != State::Active {
+            return Frame::line(format!("{} {}", head, show(&self.tokens, &self.value)));
+        }
+        let hint = self.opts.hint.as_deref().unwrap_or(
+            "Up/Down to change, Left/Right to move, or type e.g. tomorrow 09:00 or +3d.",
+        );
+        let hint = format!("  {}", prompt::gray(hint));
+        match &self.text {
+            Some(text) => {
+                let col = display_width(&head) + 1 + display_width(&text.before_cursor());
+                Frame {
+                    lines: vec![format!("{} {}", head, text.text()), hint],
+                    cursor: Some((0, col)),
+                }
+            }
+            None => Frame {
+                lines: vec![format!("{} {}", head, self.styled()), hint],
+                cursor: None,
+            },
+        }
+    }
+
+    fn handle(&mut self, action: PromptAction) -> Transition<NaiveDateTime> {
+        if action == PromptAction::Submit {
+            return self.submit();
+        }
+        if action == PromptAction::Reset {
+            self.reset();
+            return Transition::Continue;
+        }
+        if let Some(text) = &mut self.text {
+            let changed = text.apply(&action);
+            if text.is_empty() && !self.line_mode {
+                self.text = None;
+            }
+            return match changed {
+                Some(true) => Transition::Continue,
+                _ => Transition::Bell,
+            };
+        }
+        let last = self.fields.len().saturating_sub(1);
+        match action {
+            PromptAction::Up => self.step(1),
+            PromptAction::Down => self.step(-1),
+            PromptAction::Left => self.move_to(self.segment.wrapping_sub(1)),
+            PromptAction::Right => self.move_to(self.segment + 1),
+            PromptAction::Next => self.move_to(if self.segment < last {
+                self.segment + 1
+            } else {
+                0
+            }),
+            PromptAction::Home | PromptAction::First => self.move_to(0),
+            PromptAction::End | PromptAction::Last => self.move_to(last),
+            _ => Transition::Bell,
+        }
+    }
+
+    fn input(&mut self, c: char) -> Transition<NaiveDateTime> {
+        if let Some(text) = &mut self.text {
+            text.insert(c);
+            return Transition::Continue;
+        }
+        if c.is_ascii_digit() {
+            return self.type_digit(c);
+        }
+        // A separator from the mask finishes the segment being typed.
+        let separator = self
+            .tokens
+            .iter()
+            .any(|token| matches!(token, Token::Text(text) if text.contains(c)));
+        if separator && !self.digits.is_empty() {
+            return self.move_to(self.segment + 1);
+        }
+        let sign = (c == '+' || c == '-') && !self.typed;
+        if c.is_alphabetic() || sign {
+            let mut text = LineEditor::new();
+            text.insert(c);
+            self.text = Some(text);
+            return Transition::Continue;
+        }
+        match separator {
+            true => Transition::Continue,
+            false => Transition::Bell,
+        }
+    }
+
+    /// Whole lines are read as typed text, so `2026-03-01 09:00` and `tomorrow` both work.
+    fn line_mode(&mut self) {
+        self.line_mode = true;
+        self.text = Some(LineEditor::new());
+    }
+}
+
+/// Runs date prompt. Returns the entered date and time; `None` when cancelled.
+pub fn run_date<T: Terminal + ?Sized>(
+    opts: &DatePromptOptions,
+    term: &mut T,
+) -> Result<Option<NaiveDateTime>> {
+    let check = Check {
+        validate: opts.validate.as_ref(),
+        max_attempts: opts.max_attempts,
+        timeout: opts.timeout,
+        on_timeout: opts.on_timeout,
+        ..Default::default()
+    };
+    prompt::drive(&mut DatePrompt::new(opts.clone()), term, &check).map(|o| o.ok())
+}
+
+/// `run_date` for async code: waits for keys without blocking the runtime.
+#[cfg(feature = "tokio")]
+pub async fn run_date_async<T: AsyncTerminal + ?Sized>(
+    opts: &DatePromptOptions,
+    term: &mut T,
+) -> Result<Option<NaiveDateTime>> {
+    let check = Check {
+        validate: opts.validate.as_ref(),
+        max_attempts: opts.max_attempts,
+        timeout: opts.timeout,
+        on_timeout: opts.on_timeout,
+        ..Default::default()
+    };
+    prompt::drive_async(&mut DatePrompt::new(opts.clone()), term, &check)
+        .await
+        .map(|o| o.ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::terminal::MemoryTerminal;
+    use crate::util::strip::strip_ansi;
+
+    fn at(s: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M").unwrap()
+    }
+
+    fn prompt(initial: &str) -> DatePrompt {
+        DatePrompt::new(DatePromptOptions {
+            message: "When?".into(),
+            initial: Some(at(initial)),
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn tokens_split_mask_into_segments() {
+        assert_eq!(
+            tokens("DD.MM.YYYY"),
+            [
+                Token::Field(Unit::Day),
+                Token::Text(".".into()),
+                Token::Field(Unit::Month),
+                Token::Text(".".into()),
+                Token::Field(Unit::Year),
+            ]
+        );
+    }
+
+    #[test]
+    fn up_and_down_carry_into_larger_units() {
+        let mut p = prompt("2024-12-31 23:59");
+        p.handle(PromptAction::End);
+        p.handle(PromptAction::Up);
+        assert_eq!(p.value(), at("2025-01-01 00:00"));
+        p.handle(PromptAction::Home);
+        p.handle(PromptAction::Down);
+        assert_eq!(p.value(), at("2024-01-01 00:00"));
+    }
+
+    #[test]
+    fn month_and_year_steps_keep_day_within_month() {
+        let mut p = prompt("2024-01-31 09:00");
+        p.handle(PromptAction::Right);
+        p.handle(PromptAction::Up);
+        assert_eq!(p.value(), at("2024-02-29 09:00"));
+        p.handle(PromptAction::Left);
+        p.handle(PromptAction::Up);
+        assert_eq!(p.value(), at("2025-02-28 09:00"));
+        p.handle(PromptAction::Right);
+        p.handle(PromptAction::Right);
+        p.handle(PromptAction::Up);
+        assert_eq!(p.value(), at("2025-03-01 09:00"));
+    }
+
+    #[test]
+    fn digits_fill_segments_and_move_on() {
+        let mut p = prompt("2024-01-31 09:00");
+        for c in "2023022".chars() {
+            assert_eq!(p.input(c), Transition::Continue);
+        }
+        assert_eq!(p.value(), at("2023-02-02 09:00"));
+        assert_eq!(p.input('9'), Transition::Bell);
+        p.input('8');
+        p.input(' ');
+        assert_eq!(p.segment, 3);
+        assert_eq!(p.input('7'), Transition::Continue);
+        assert_eq!(
+            p.handle(PromptAction::Submit),
+            Transition::Submit(at("2023-02-08 07:00"))
+        );
+    }
+
+    #[test]
+    fn typed_date_with_separators_fills_segments() {
+        let mut p = prompt("2024-01-31 09:00");
+        for c in "2026-05-04 10:30".chars() {
+            assert_eq!(p.input(c), Transition::Continue);
+        }
+        assert_eq!(
+            p.handle(PromptAction::Submit),
+            Transition::Submit(at("2026-05-04 10:30"))
+        );
+        let mut p = prompt("2024-01-31 09:00");
+        for c in "2026-5-4 9:05".chars() {
+            p.input(c);
+        }
+        assert_eq!(p.value(), at("2026-05-04 09:05"));
+        let mut p = prompt("2024-01-31 09:00");
+        p.input('-');
+        assert!(p.text.is_some());
+    }
+
+    #[test]
+    fn parse_date_reads_mask_and_relative_input() {
+        let now = at("2024-02-28 13:37");
+        let mask = "YYYY-MM-DD HH:mm";
+        assert_eq!(
+            parse_date("2024-03-01 09:05", mask, now),
+            Some(at("2024-03-01 09:05"))
+        );
+        assert_eq!(
+            parse_date("Tomorrow 09:00", mask, now),
+            Some(at("2024-02-29 09:00"))
+        );
+        assert_eq!(parse_date("+3d", mask, now), Some(at("2024-03-02 13:37")));
+        assert_eq!(
+            parse_date("-1w 8:15", mask, now),
+            Some(at("2024-02-21 08:15"))
+        );
+        assert_eq!(
+            parse_date("now +90m", mask, now),
+            Some(at("2024-02-28 15:07"))
+        );
+        assert_eq!(
+            parse_date("+1y", "YYYY-MM-DD", now),
+            Some(at("2025-02-28 00:00"))
+        );
+        assert_eq!(
+            parse_date("01.03.2024", "DD.MM.YYYY", now),
+            Some(at("2024-03-01 00:00"))
+        );
+        for bad in [
+            "",
+            "2024-02-30 09:00",
+            "soon",
+            "+3x",
+            "25:00",
+            "+999999999y",
+        ] {
+            assert_eq!(parse_date(bad, mask, now), None, "{}", bad);
+        }
+    }
+
+    #[test]
+    fn typed_text_is_parsed_on_submit() {
+        let mut p = prompt("2024-02-28 13:37");
+        p.now = at("2024-02-28 13:37");
+        for c in "tomorow".chars() {
+            p.input(c);
+        }
+        assert!(matches!(
+            p.handle(PromptAction::Submit),
+            Transition::Invalid(_)
+        ));
+        for _ in 0..3 {
+            p.handle(PromptAction::Left);
+        }
+        p.input('r');
+        assert_eq!(
+            p.handle(PromptAction::Submit),
+            Transition::Submit(at("2024-02-29 13:37"))
+        );
+    }
+
+    #[test]
+    fn min_and_max_are_enforced() {
+        let mut p = DatePrompt::new(DatePromptOptions {
+            initial: Some(at("2024-03-01 09:00")),
+            min: Some(at("2024-03-01 00:00")),
+            max: Some(at("2024-03-31 23:59")),
+            ..Default::default()
+        });
+        p.handle(PromptAction::Right);
+        p.handle(PromptAction::Right);
+        assert_eq!(p.handle(PromptAction::Down), Transition::Bell);
+        p.input('3');
+        p.input('1');
+        p.input('2');
+        p.input('3');
+        p.input('5');
+        p.input('9');
+        assert_eq!(
+            p.handle(PromptAction::Submit),
+            Transition::Submit(at("2024-03-31 23:59"))
+        );
+        p.handle(PromptAction::Left);
+        assert_eq!(p.handle(PromptAction::Up), Transition::Bell);
+        p.handle(PromptAction::Home);
+        p.input('2');
+        p.input('0');
+        p.input('2');
+        p.input('5');
+        assert_eq!(
+            p.handle(PromptAction::Submit),
+            Transition::Invalid("Enter 2024-03-31 23:59 or earlier".into())
+        );
+    }
+
+    #[test]
+    fn render_highlights_segment_and_shows_done_value() {
+        let mut p = prompt("2024-03-01 09:00");
+        let frame = p.render(&State::Active);
+        assert!(strip_ansi(&frame.lines[0]).ends_with(" 2024-03-01 09:00"));
+        assert!(frame.lines[0].contains("\x1b"));
+        p.handle(PromptAction::Submit);
+        assert!(strip_ansi(&p.render(&State::Done).lines[0]).ends_with(" 2024-03-01 09:00"));
+    }
+
+    #[test]
+    fn run_date_reads_lines_without_tty() {
+        let opts = DatePromptOptions {
+            message: "When?".into(),
+            initial: Some(at("2024-03-01 09:00")),
+            ..Default::default()
+        };
+        let mut term = MemoryTerminal::new("2024-04-02 10:30\n");
+        assert_eq!(
+            run_date(&opts, &mut term).unwrap(),
+            Some(at("2024-04-02 10:30"))
+        );
+        let mut term = MemoryTerminal::new("\n");
+        assert_eq!(
+            run_date(&opts, &mut term).unwrap(),
+            Some(at("2024-03-01 09:00"))
+        );
+        let mut term = MemoryTerminal::new("\x1b[C\x1b[A\r").tty(true);
+        assert_eq!(
+            run_date(&opts, &mut term).unwrap(),
+            Some(at("2024-04-01 09:00"))
+        );
+    }
+
+    #[test]
+    fn run_date_retries_a_rejected_line() {
+        let opts = DatePromptOptions {
+            message: "When?".into(),
+            initial: Some(at("2024-03-01 09:00")),
+            ..Default::default()
+        };
+        let mut term = MemoryTerminal::new("soon\n2024-04-02 10:30\n");
+        assert_eq!(
+            run_date(&opts, &mut term).unwrap(),
+            Some(at("2024-04-02 10:30"))
+        );
+        assert!(term.output().contains("'soon' is not a date"));
+    }
+}
@@ -0,0 +1,358 @@
+//! Editor prompt: the answer is written in an external editor.
+
+use crate::answers::Answers;
+use crate::elements::prompt::{Check, Outcome, State, error_line, gray, title};
+use crate::elements::text::more_lines;
+use crate::error::{Error, Result};
+use crate::prompts::{PromptValue, Validator};
+use crate::terminal::Terminal;
+use ansi_escapes::{CursorUp, EraseLine};
+use std::env;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::process::Command;
+
+/// Editor run when neither `command`, `$VISUAL` nor `$EDITOR` names one.
+#[cfg(windows)]
+const DEFAULT_EDITOR: &str = "notepad";
+#[cfg(not(windows))]
+const DEFAULT_EDITOR: &str = "vi";
+
+/// Editor prompt options.
+#[derive(Clone)]
+pub struct EditorPromptOptions {
+    pub message: String,
+    /// Text the file starts with.
+    pub initial: Option<String>,
+    /// Command to run, e.g. `code --wait`, split into words as a shell would, so paths with
+    /// spaces can be quoted; the file path is appended.
+    /// Defaults to `$VISUAL`, then `$EDITOR`, then `vi` (`notepad` on Windows).
+    pub command: Option<String>,
+    /// Lines starting with this prefix, e.g. `#`, are dropped from the answer.
+    pub comment: Option<String>,
+    /// Extension of the temporary file, which editors use to pick a syntax.
+    pub extension: String,
+    /// Rejects an answer with a message; the editor is then opened again on it.
+    pub validate: Option<Validator>,
+    /// Rejected answers allowed before giving up; `None` retries forever.
+    pub max_attempts: Option<u32>,
+}
+
+impl Default for EditorPromptOptions {
+    fn default() -> Self {
+        Self {
+            message: String::new(),
+            initial: None,
+            command: None,
+            comment: None,
+            extension: "txt".into(),
+            validate: None,
+            max_attempts: None,
+        }
+    }
+}
+
+/// Opens the editor on a temporary file holding `initial` and returns the saved text, without
+/// comment lines and surrounding blank lines. Returns `None` when the editor exits with an error.
+///
+/// The editor runs in the foreground on the process's own terminal; `term` only shows the
+/// prompt. There is no timeout.
+pub fn run_editor<T: Terminal + ?Sized>(
+    opts: &EditorPromptOptions,
+    term: &mut T,
+) -> Result<Option<String>> {
+    let check = Check {
+        validate: opts.validate.as_ref(),
+        max_attempts: opts.max_attempts,
+        ..Default::default()
+    };
+    edit(opts, term, &check).map(Outcome::ok)
+}
+
+/// `run_editor` with the driver's answer checks; `check.timeout` is ignored.
+pub(crate) fn edit<T: Terminal + ?Sized>(
+    opts: &EditorPromptOptions,
+    term: &mut T,
+    check: &Check,
+) -> Result<Outcome<String>> {
+    let empty = Answers::new();
+    let answers = check.answers.unwrap_or(&empty);
+    let command = command(opts.command.as_deref(), |name| env::var(name).ok());
+    let file = tempfile::Builder::new()
+        .prefix("promptt-")
+        .suffix(&format!(".{}", opts.extension))
+        .tempfile()?
+        .into_temp_path();
+    let mut text = opts.initial.clone().unwrap_or_default();
+    let mut failures = 0;
+    loop {
+        let waiting = gray("(waiting for your editor)");
+        term.write(&format!(
+            "{} {}\n",
+            title(&opts.message, &State::Active),
+            waiting
+        ))?;
+        term.flush()?;
+        fs::write(&file, &text)?;
+        let saved = open(&command, &file)?;
+        if term.is_tty() {
+            term.write(&format!("{}\r{}", CursorUp(1), EraseLine))?;
+        }
+        let Some(saved) = saved.then(|| fs::read_to_string(&file)).transpose()? else {
+            term.write(&format!("{}\n", title(&opts.message, &State::Exited)))?;
+            term.flush()?;
+            return Ok(Outcome::Cancelled(State::Exited));
+        };
+        let value = strip(&saved, opts.comment.as_deref());
+        let rejected = check
+            .validate
+            .and_then(|validate| validate(&PromptValue::String(value.clone()), answers).err());
+        let Some(message) = rejected else {
+            let shown = match check.display {
+                Some(display) => display(&PromptValue::String(value.clone())),
+                None => format!(
+                    "{}{}",
+                    value.lines().next().unwrap_or_default(),
+                    more_lines(&value)
+                ),
+            };
+            term.write(&format!(
+                "{} {}\n",
+                title(&opts.message, &State::Done),
+                shown
+            ))?;
+            term.flush()?;
+            return Ok(Outcome::Submitted(value));
+        };
+        term.write(&format!("{}\n", error_line(&message)))?;
+        term.flush()?;
+        failures += 1;
+        if check.max_attempts.is_some_and(|n| failures >= n) {
+            return Err(Error::Validation {
+                name: String::new(),
+                message,
+            });
+        }
+        text = saved;
+    }
+}
+
+/// The editor command: `configured`, else `$VISUAL`, else `$EDITOR`, skipping blank ones.
+fn command(configured: Option<&str>, var: impl Fn(&str) -> Option<String>) -> String {
+    configured
+        .map(str::to_string)
+        .into_iter()
+        .chain(var("VISUAL"))
+        .chain(var("EDITOR"))
+        .find(|c| !c.trim().is_empty())
+        .unwrap_or_else(|| DEFAULT_EDITOR.into())
+}
+
+/// Splits `command` into words with shell quoting: `"/Applications/Sublime Text.app/sub" -w`
+/// is a program and one argument. Backslashes are kept on Windows, where they separate paths.
+fn words(command: &str) -> io::Result<Vec<String>> {
+    #[cfg(windows)]
+    let command = &command.replace('\\', "\\\\");
+    shell_words::split(command).map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))
+}
+
+/// Runs `command` on `path` and waits for it; `false` when it exits with an error.
+fn open(command: &str, path: &Path) -> io::Result<bool> {
+    let fail =
+        |e: io::Error| io::Error::new(e.kind(), format!("cannot run editor '{}': {}", command, e));
+    let words = words(command).map_err(fail)?;
+    let (program, args) = words
+        .split_first()
+        .map_or((DEFAULT_EDITOR, &[][..]), |(p, args)| (p.as_str(), args));
+    Command::new(program)
+        .args(args)
+        .arg(path)
+        .status()
+        .map(|status| status.success())
+        .map_err(fail)
+}
+
+/// Drops lines starting with `comment`, then the blank lines around the text.
+fn strip(text: &str, comment: Option<&str>) -> String {
+    let comment = comment.filter(|c| !c.is_empty());
+    let kept: Vec<&str> = text
+        .lines()
+        .filter(|line| comment.is_none_or(|c| !line.trim_start().starts_with(c)))
+        .collect();
+    kept.join("\n")
+        .trim_end()
+        .trim_start_matches(['\r', '\n'])
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::terminal::MemoryTerminal;
+    use std::sync::Arc;
+
+    #[test]
+    fn command_prefers_configured_then_visual_then_editor() {
+        let vars = |visual: &'static str, editor: &'static str| {
+            move |name: &str| match name {
+                "VISUAL" => Some(visual.to_string()),
+                "EDITOR" => Some(editor.to_string()),
+                _ => None,
+            }
+        };
+        assert_eq!(
+            command(Some("code --wait"), vars("vim", "nano")),
+            "code --wait"
+        );
+        assert_eq!(command(None, vars("vim", "nano")), "vim");
+        assert_eq!(command(None, vars(" ", "nano")), "nano");
+        assert_eq!(command(None, |_| None), DEFAULT_EDITOR);
+    }
+
+    #[test]
+    fn words_keep_quoted_paths_and_arguments_together() {
+        assert_eq!(words("code --wait").unwrap(), ["code", "--wait"]);
+        assert_eq!(
+            words("\"/Applications/Sublime Text.app/bin/subl\" -w").unwrap(),
+            ["/Applications/Sublime Text.app/bin/subl", "-w"]
+        );
+        assert_eq!(
+            words("code --wait \"-n\"").unwrap(),
+            ["code", "--wait", "-n"]
+        );
+        assert!(words("vim \"unclosed").is_err());
+    }
+
+    #[test]
+    fn strip_drops_comments_and_blank_edges() {
+        let text = "\n\nSubject\n  # indented note\n\nBody\n# note\n\n";
+        assert_eq!(strip(text, Some("#")), "Subject\n\nBody");
+        assert_eq!(
+            strip(text, None),
+            "Subject\n  # indented note\n\nBody\n# note"
+        );
+        assert_eq!(strip("a\n#b", Some("")), "a\n#b");
+    }
+
+    /// Options running a fake editor: `sh` on a script given the file path as `$1`. The script
+    /// sits in a directory with a space in its name, quoted in the command.
+    #[cfg(unix)]
+    fn fake_editor(dir: &tempfile::TempDir, script: &str) -> EditorPromptOptions {
+        let path = dir.path().join("fake editor").join("editor.sh");
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, script).unwrap();
+        EditorPromptOptions {
+            message: "Notes?".into(),
+            command: Some(format!("sh '{}'", path.display())),
+            ..Default::default()
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn returns_the_edited_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let opts = EditorPromptOptions {
+            initial: Some("first\n".into()),
+            ..fake_editor(&dir, "echo added >> \"$1\"")
+        };
+        let mut t = MemoryTerminal::new("");
+        assert_eq!(
+            run_editor(&opts, &mut t).unwrap().as_deref(),
+            Some("first\nadded")
+        );
+        assert!(t.output().contains("(waiting for your editor)"));
+        assert!(t.output().contains("first"));
+        assert!(t.output().contains("(+1 lines)"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn strips_comment_lines() {
+        let dir = tempfile::tempdir().unwrap();
+        let opts = EditorPromptOptions {
+            initial: Some("# Describe the change\n".into()),
+            comment: Some("#".into()),
+            extension: "md".into(),
+            ..fake_editor(
+                &dir,
+                "case \"$1\" in *.md) ;; *) exit 1 ;; esac\necho Fix typo >> \"$1\"",
+            )
+        };
+        let mut t = MemoryTerminal::new("");
+        assert_eq!(
+            run_editor(&opts, &mut t).unwrap().as_deref(),
+            Some("Fix typo")
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn failing_editor_cancels() {
+        let dir = tempfile::tempdir().unwrap();
+        let opts = fake_editor(&dir, "exit 1");
+        let mut t = MemoryTerminal::new("");
+        let check = Check::default();
+        assert_eq!(
+            edit(&opts, &mut t, &check).unwrap(),
+            Outcome::Cancelled(State::Exited)
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn rejected_answer_reopens_the_editor_on_it() {
+        let dir = tempfile::tempdir().unwrap();
+        let opts = EditorPromptOptions {
+            validate: Some(Arc::new(|v, _| match v {
+                PromptValue::String(s) if s.len() < 2 => Err("Too short".into()),
+                _ => Ok(()),
+            })),
+            ..fake_editor(&dir, "printf x >> \"$1\"")
+        };
+        let mut t = MemoryTerminal::new("");
+        assert_eq!(run_editor(&opts, &mut t).unwrap().as_deref(), Some("xx"));
+        assert!(t.output().contains("Too short"));
+        assert_eq!(t.output().matches("waiting for your editor").count(), 2);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn max_attempts_gives_up() {
+        let dir = tempfile::tempdir().unwrap();
+        let opts = EditorPromptOptions {
+            validate: Some(Arc::new(|_, _| Err("Never".into()))),
+            max_attempts: Some(2),
+            ..fake_editor(&dir, "true")
+        };
+        let mut t = MemoryTerminal::new("");
+        let err = run_editor(&opts, &mut t).unwrap_err();
+        assert!(matches!(err, Error::Validation { message, .. } if message == "Never"));
+    }
+
+    #[test]
+    fn missing_editor_is_an_error() {
+        let opts = EditorPromptOptions {
+            command: Some("promptt-no-such-editor --wait".into()),
+            ..Default::default()
+        };
+        let mut t = MemoryTerminal::new("");
+        let err = run_editor(&opts, &mut t).unwrap_err();
+        assert!(err.to_string().contains("cannot run editor"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn tty_replaces_the_waiting_line() {
+        let dir = tempfile::tempdir().unwrap();
+        let opts = fake_editor(&dir, "echo done > \"$1\"");
+        let mut t = MemoryTerminal::new("").tty(true);
+        run_editor(&opts, &mut t).unwrap();
+        assert!(
+            t.output()
+                .contains(&format!("{}\r{}", CursorUp(1), EraseLine))
+        );
+        assert!(!t.is_raw());
+    }
+}
@@ -1,15 +1,47 @@
 //! Prompt element implementations.
 
+mod autocomplete;
 mod confirm;
+mod date;
+mod editor;
+mod multiselect;
 mod number;
 mod prompt;
 mod select;
 mod text;
 mod toggle;
 
-pub use confirm::{ConfirmPromptOptions, run_confirm};
-pub use number::{NumberPromptOptions, run_number};
-pub use prompt::Prompt;
-pub use select::{Choice, SelectPromptOptions, run_select};
-pub use text::{TextPromptOptions, run_text};
-pub use toggle::{TogglePromptOptions, run_toggle};
+#[cfg(feature = "tokio")]
+pub use autocomplete::run_autocomplete_async;
+pub use autocomplete::{
+    AutocompletePrompt, AutocompletePromptOptions, Match, MatchFn, Matcher, run_autocomplete,
+};
+#[cfg(feature = "tokio")]
+pub use confirm::run_confirm_async;
+pub use confirm::{ConfirmPrompt, ConfirmPromptOptions, run_confirm};
+#[cfg(feature = "tokio")]
+pub use date::run_date_async;
+pub use date::{DatePrompt, DatePromptOptions, run_date};
+pub(crate) use date::{out_of_range, parse_date};
+pub(crate) use editor::edit;
+pub use editor::{EditorPromptOptions, run_editor};
+#[cfg(feature = "tokio")]
+pub use multiselect::run_multiselect_async;
+pub use multiselect::{MultiselectPrompt, MultiselectPromptOptions, run_multiselect};
+pub(crate) use number::parse_number;
+#[cfg(feature = "tokio")]
+pub use number::run_number_async;
+pub use number::{NumberPrompt, NumberPromptOptions, run_number};
+#[cfg(feature = "tokio")]
+pub(crate) use prompt::read_async;
+pub(crate) use prompt::{Check, Input, Map, Outcome, drive_with, error_line, now, read_blocking};
+pub use prompt::{Frame, Prompt, State, Transition};
+#[cfg(feature = "tokio")]
+pub use select::run_select_async;
+pub use select::{Choice, SelectPrompt, SelectPromptOptions, run_select};
+#[cfg(feature = "tokio")]
+pub use text::run_text_async;
+pub use text::{TextPrompt, TextPromptOptions, run_text};
+#[cfg(feature = "tokio")]
+pub use toggle::run_toggle_async;
+pub use toggle::{TogglePrompt, TogglePromptOptions, run_toggle};
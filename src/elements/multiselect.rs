@@ -0,0 +1,404 @@
+//! Multiselect prompt.
+
+use crate::elements::prompt::{self, Check, Frame, Prompt, State, Transition};
+use crate::elements::select::{Choice, next_enabled, prev_enabled};
+use crate::error::Result;
+use crate::prompts::{OnTimeout, Validator};
+#[cfg(feature = "tokio")]
+use crate::terminal::AsyncTerminal;
+use crate::terminal::Terminal;
+use crate::util::action::{Key, PromptAction};
+use crate::util::figures::Figures;
+use colour::write_cyan;
+use std::io::Write;
+use std::time::Duration;
+
+/// Multiselect prompt options. Choices with `selected` set start checked.
+#[derive(Clone, Default)]
+pub struct MultiselectPromptOptions {
+    pub message: String,
+    pub choices: Vec<Choice>,
+    /// Index of the initially highlighted choice.
+    pub initial: Option<usize>,
+    /// Fewest choices that may be submitted.
+    pub min: Option<usize>,
+    /// Most choices that may be submitted.
+    pub max: Option<usize>,
+    pub hint: Option<String>,
+    /// Rejects an answer with a message; the prompt is then asked again.
+    pub validate: Option<Validator>,
+    /// Rejected answers allowed before giving up; `None` retries forever.
+    pub max_attempts: Option<u32>,
+    /// Time left to answer; a countdown is shown and any key stops it.
+    pub timeout: Option<Duration>,
+    /// What happens when `timeout` runs out.
+    pub on_timeout: OnTimeout,
+}
+
+/// Multiselect prompt state: the highlighted choice and which choices are checked.
+pub struct MultiselectPrompt {
+    opts: MultiselectPromptOptions,
+    fig: Figures,
+    cursor: usize,
+    checked: Vec<bool>,
+}
+
+impl MultiselectPrompt {
+    pub fn new(opts: MultiselectPromptOptions) -> Self {
+        let cursor = Self::initial_cursor(&opts);
+        let checked = Self::initial_checked(&opts);
+        Self {
+            opts,
+            fig: Figures::default(),
+            cursor,
+            checked,
+        }
+    }
+
+    fn initial_cursor(opts: &MultiselectPromptOptions) -> usize {
+        let initial = opts
+            .initial
+            .filter(|&i| i < opts.choices.len())
+            .unwrap_or(0);
+        match opts.choices.get(initial) {
+            Some(c) if c.disabled => next_enabled(&opts.choices, initial),
+            _ => initial,
+        }
+    }
+
+    fn initial_checked(opts: &MultiselectPromptOptions) -> Vec<bool> {
+        opts.choices.iter().map(|c| c.selected).collect()
+    }
+
+    /// Values of the checked choices, in choice order.
+    pub fn values(&self) -> Vec<String> {
+        self.opts
+            .choices
+            .iter()
+            .zip(&self.checked)
+            .filter(|(_, checked)| **checked)
+            .map(|(c, _)| c.value.clone())
+            .collect()
+    }
+
+    fn count(&self) -> usize {
+        self.checked.iter().filter(|c| **c).count()
+    }
+
+    fn move_to(&mut self, index: usize) -> Transition<Vec<String>> {
+        if index == self.cursor {
+            return Transition::Bell;
+        }
+        self.cursor = index;
+        Transition::Continue
+    }
+
+    /// Sets every enabled choice to `f` of its current state; disabled ones keep theirs.
+    fn set_all(&mut self, f: impl Fn(bool) -> bool) -> Transition<Vec<String>> {
+        let choices = &self.opts.choices;
+        for (checked, c) in self.checked.iter_mut().zip(choices) {
+            if !c.disabled {
+                *checked = f(*checked);
+            }
+        }
+        Transition::Continue
+    }
+
+    fn submit(&self) -> Transition<Vec<String>> {
+        let count = self.count();
+        match (self.opts.min, self.opts.max) {
+            (Some(min), _) if count < min => {
+                Transition::Invalid(format!("Select at least {}", plural(min)))
+            }
+            (_, Some(max)) if count > max => {
+                Transition::Invalid(format!("Select at most {}", plural(max)))
+            }
+            _ => Transition::Submit(self.values()),
+        }
+    }
+}
+
+/// `n` followed by "choice" or "choices".
+fn plural(n: usize) -> String {
+    match n {
+        1 => "1 choice".into(),
+        n => format!("{} choices", n),
+    }
+}
+
+impl Prompt for MultiselectPrompt {
+    type Output = Vec<String>;
+
+    fn render(&self, state: &State) -> Frame {
+        let head = prompt::title(&self.opts.message, state);
+        if *state != State::Active {
+            let titles: Vec<&str> = self
+                .opts
+                .choices
+                .iter()
+                .zip(&self.checked)
+                .filter(|(_, checked)| **checked)
+                .map(|(c, _)| c.title.as_str())
+                .collect();
+            return Frame::line(format!("{} {}", head, titles.join(", ")));
+        }
+        let mut lines = Vec::with_capacity(self.opts.choices.len() + 2);
+        lines.push(head);
+        for (i, (c, checked)) in self.opts.choices.iter().zip(&self.checked).enumerate() {
+            let pointer = match !c.disabled && i == self.cursor {
+                true => self.fig.pointer_small,
+                false => " ",
+            };
+            let radio = match checked {
+                true => {
+                    let mut buf = Vec::new();
+                    write_cyan!(&mut buf, "{}", self.fig.radio_on).ok();
+                    String::from_utf8_lossy(&buf).into_owned()
+                }
+                false => self.fig.radio_off.to_string(),
+            };
+            let title = match c.disabled {
+                true => prompt::gray(&c.title),
+                false => c.title.clone(),
+            };
+            lines.push(format!("  {} {} {}", pointer, radio, title));
+        }
+        let hint = self
+            .opts
+            .hint
+            .as_deref()
+            .unwrap_or("Space to toggle, a to toggle all, i to invert. Return to submit.");
+        let count = match (self.opts.min, self.opts.max) {
+            (None, None) => String::new(),
+            (min, max) => {
+                let limits: Vec<String> = [
+                    min.map(|n| format!("min {}", n)),
+                    max.map(|n| format!("max {}", n)),
+                ]
+                .into_iter()
+                .flatten()
+                .collect();
+                format!(" ({} selected, {})", self.count(), limits.join(", "))
+            }
+        };
+        lines.push(format!("  {}", prompt::gray(&format!("{}{}", hint, count))));
+        Frame {
+            lines,
+            cursor: None,
+        }
+    }
+
+    fn handle(&mut self, action: PromptAction) -> Transition<Vec<String>> {
+        let choices = &self.opts.choices;
+        match action {
+            PromptAction::Submit => self.submit(),
+            PromptAction::Up => self.move_to(prev_enabled(choices, self.cursor)),
+            PromptAction::Down | PromptAction::Next => {
+                self.move_to(next_enabled(choices, self.cursor))
+            }
+            PromptAction::Home | PromptAction::First => {
+                let first = choices.iter().position(|c| !c.disabled);
+                self.move_to(first.unwrap_or(self.cursor))
+            }
+            PromptAction::End | PromptAction::Last => {
+                let last = choices.iter().rposition(|c| !c.disabled);
+                self.move_to(last.unwrap_or(self.cursor))
+            }
+            PromptAction::Reset => {
+                self.cursor = Self::initial_cursor(&self.opts);
+                self.checked = Self::initial_checked(&self.opts);
+                Transition::Continue
+            }
+            _ => Transition::Bell,
+        }
+    }
+
+    fn input(&mut self, c: char) -> Transition<Vec<String>> {
+        match c {
+            ' ' => match self.opts.choices.get(self.cursor) {
+                Some(choice) if !choice.disabled => {
+                    self.checked[self.cursor] ^= true;
+                    Transition::Continue
+                }
+                _ => Transition::Bell,
+            },
+            'a' => {
+                let all = self
+                    .opts
+                    .choices
+                    .iter()
+                    .zip(&self.checked)
+                    .all(|(c, checked)| c.disabled || *checked);
+                self.set_all(|_| !all)
+            }
+            'i' => self.set_all(|checked| !checked),
+            _ => Transition::Bell,
+        }
+    }
+
+    fn keypress(&mut self, key: &Key) -> Transition<Vec<String>> {
+        prompt::dispatch(self, key, true)
+    }
+}
+
+/// Runs multiselect prompt. Returns the values of the checked choices; `None` when cancelled.
+pub fn run_multiselect<T: Terminal + ?Sized>(
+    opts: &MultiselectPromptOptions,
+    term: &mut T,
+) -> Result<Option<Vec<String>>> {
+    let check = Check {
+        validate: opts.validate.as_ref(),
+        max_attempts: opts.max_attempts,
+        timeout: opts.timeout,
+        on_timeout: opts.on_timeout,
+        ..Default::default()
+    };
+    prompt::drive(&mut MultiselectPrompt::new(opts.clone()), term, &check).map(|o| o.ok())
+}
+
+/// `run_multiselect` for async code: waits for keys without blocking the runtime.
+#[cfg(feature = "tokio")]
+pub async fn run_multiselect_async<T: AsyncTerminal + ?Sized>(
+    opts: &MultiselectPromptOptions,
+    term: &mut T,
+) -> Result<Option<Vec<String>>> {
+    let check = Check {
+        validate: opts.validate.as_ref(),
+        max_attempts: opts.max_attempts,
+        timeout: opts.timeout,
+        on_timeout: opts.on_timeout,
+        ..Default::default()
+    };
+    prompt::drive_async(&mut MultiselectPrompt::new(opts.clone()), term, &check)
+        .await
+        .map(|o| o.ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::terminal::MemoryTerminal;
+    use crate::util::strip::strip_ansi;
+
+    fn toppings() -> MultiselectPromptOptions {
+        let mut olives = Choice::new("Olives", "olives");
+        olives.disabled = true;
+        let mut cheese = Choice::new("Cheese", "cheese");
+        cheese.selected = true;
+        MultiselectPromptOptions {
+            message: "Toppings?".into(),
+            choices: vec![
+                cheese,
+                Choice::new("Ham", "ham"),
+                olives,
+                Choice::new("Basil", "basil"),
+            ],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn preselected_choices_start_checked() {
+        let p = MultiselectPrompt::new(toppings());
+        assert_eq!(p.values(), ["cheese"]);
+    }
+
+    #[test]
+    fn space_toggles_the_highlighted_choice() {
+        let mut p = MultiselectPrompt::new(toppings());
+        p.input(' ');
+        p.handle(PromptAction::Down);
+        p.input(' ');
+        assert_eq!(
+            p.handle(PromptAction::Submit),
+            Transition::Submit(vec!["ham".into()])
+        );
+    }
+
+    #[test]
+    fn navigation_skips_disabled_choices() {
+        let mut p = MultiselectPrompt::new(toppings());
+        p.handle(PromptAction::Down);
+        p.handle(PromptAction::Down);
+        assert_eq!(p.cursor, 3);
+        assert_eq!(p.handle(PromptAction::Down), Transition::Bell);
+        p.handle(PromptAction::Up);
+        assert_eq!(p.cursor, 1);
+    }
+
+    #[test]
+    fn toggle_all_and_invert_leave_disabled_choices_alone() {
+        let mut opts = toppings();
+        opts.choices[2].selected = true;
+        let mut p = MultiselectPrompt::new(opts);
+        p.input('a');
+        assert_eq!(p.values(), ["cheese", "ham", "olives", "basil"]);
+        p.input('a');
+        assert_eq!(p.values(), ["olives"]);
+        p.input(' ');
+        p.input('i');
+        assert_eq!(p.values(), ["ham", "olives", "basil"]);
+        p.handle(PromptAction::Reset);
+        assert_eq!(p.values(), ["cheese", "olives"]);
+    }
+
+    #[test]
+    fn min_and_max_are_enforced_on_submit() {
+        let mut p = MultiselectPrompt::new(MultiselectPromptOptions {
+            min: Some(2),
+            max: Some(2),
+            ..toppings()
+        });
+        assert_eq!(
+            p.handle(PromptAction::Submit),
+            Transition::Invalid("Select at least 2 choices".into())
+        );
+        p.input('a');
+        assert_eq!(
+            p.handle(PromptAction::Submit),
+            Transition::Invalid("Select at most 2 choices".into())
+        );
+        p.input(' ');
+        assert_eq!(
+            p.handle(PromptAction::Submit),
+            Transition::Submit(vec!["ham".into(), "basil".into()])
+        );
+        let hint = strip_ansi(p.render(&State::Active).lines.last().unwrap());
+        assert!(hint.ends_with("(2 selected, min 2, max 2)"));
+    }
+
+    #[test]
+    fn render_shows_radios_and_done_titles() {
+        let mut p = MultiselectPrompt::new(toppings());
+        let fig = Figures::default();
+        let lines: Vec<_> = p
+            .render(&State::Active)
+            .lines
+            .iter()
+            .map(|l| strip_ansi(l))
+            .collect();
+        assert_eq!(lines.len(), 6);
+        assert_eq!(
+            lines[1],
+            format!("  {} {} Cheese", fig.pointer_small, fig.radio_on)
+        );
+        assert_eq!(lines[2], format!("    {} Ham", fig.radio_off));
+        p.input('i');
+        let done = strip_ansi(&p.render(&State::Done).lines[0]);
+        assert!(done.ends_with("Ham, Basil"));
+    }
+
+    #[test]
+    fn run_multiselect_shows_errors_inline() {
+        let opts = MultiselectPromptOptions {
+            min: Some(1),
+            ..toppings()
+        };
+        let mut term = MemoryTerminal::new(" \rj \r").tty(true);
+        assert_eq!(
+            run_multiselect(&opts, &mut term).unwrap(),
+            Some(vec!["ham".into()])
+        );
+        assert!(term.output().contains("Select at least 1 choice"));
+    }
+}
@@ -1,18 +1,36 @@
 //! Number prompt.
 
-use crate::util::style;
-use colour::write_bold;
-use std::io::{self, BufRead, Write};
+use crate::elements::prompt::{self, Check, Frame, Prompt, State, Transition};
+use crate::error;
+use crate::prompts::{OnTimeout, Validator};
+#[cfg(feature = "tokio")]
+use crate::terminal::AsyncTerminal;
+use crate::terminal::Terminal;
+use crate::util::action::PromptAction;
+use crate::util::line_editor::LineEditor;
+use crate::util::lines::display_width;
+use std::time::Duration;
 
 /// Number prompt options.
+#[derive(Clone)]
 pub struct NumberPromptOptions {
     pub message: String,
     pub initial: Option<f64>,
+    /// Starts the input with `initial`, ready to edit; Ctrl-G restores it.
+    pub prefill: bool,
     pub min: Option<f64>,
     pub max: Option<f64>,
     pub float: bool,
     pub round: u32,
     pub error_msg: Option<String>,
+    /// Rejects an answer with a message; the prompt is then asked again.
+    pub validate: Option<Validator>,
+    /// Rejected answers allowed before giving up; `None` retries forever.
+    pub max_attempts: Option<u32>,
+    /// Time left to answer; a countdown is shown and any key stops it.
+    pub timeout: Option<Duration>,
+    /// What happens when `timeout` runs out.
+    pub on_timeout: OnTimeout,
 }
 
 impl Default for NumberPromptOptions {
@@ -20,11 +38,16 @@ impl Default for NumberPromptOptions {
         Self {
             message: String::new(),
             initial: None,
+            prefill: false,
             min: None,
             max: None,
             float: false,
             round: 2,
             error_msg: Some("Please Enter A Valid Value".into()),
+            validate: None,
+            max_attempts: None,
+            timeout: None,
+            on_timeout: OnTimeout::default(),
         }
     }
 }
@@ -34,66 +57,229 @@ fn round_n(x: f64, n: u32) -> f64 {
     (x * factor).round() / factor
 }
 
-/// Runs number prompt. Returns value or initial/0 when empty.
-pub fn run_number<R: BufRead, W: Write>(
-    opts: &NumberPromptOptions,
-    stdin: &mut R,
-    stdout: &mut W,
-) -> io::Result<f64> {
-    let mut buf = Vec::with_capacity(opts.message.len() + 32);
-    write_bold!(&mut buf, "{}", opts.message).ok();
-    let msg = String::from_utf8_lossy(&buf).into_owned();
-    // Do not pre-display initial value: it is not editable, so users could not change it.
-    // Use initial only when the user submits with empty input.
-    let symbol = style::symbol(false, false, false);
-    let delim = style::delimiter(false);
-    write!(stdout, "{} {} {} ", symbol, msg, delim)?;
-    stdout.flush()?;
-    let mut line = String::new();
-    stdin.read_line(&mut line)?;
-    let raw = line.trim();
-    let value = if raw.is_empty() {
-        opts.initial.unwrap_or(0.0)
+/// Parses non-empty input for `opts`: rounded and clamped to `min`/`max`.
+pub(crate) fn parse_number(opts: &NumberPromptOptions, raw: &str) -> Result<f64, String> {
+    let err = || {
+        opts.error_msg
+            .clone()
+            .unwrap_or_else(|| "invalid number".into())
+    };
+    let v = if opts.float {
+        raw.parse::<f64>().map_err(|_| err())?
     } else {
-        let v = if opts.float {
-            raw.parse::<f64>().map_err(|_| {
-                io::Error::new(
-                    io::ErrorKind::InvalidInput,
-                    opts.error_msg.as_deref().unwrap_or("invalid number"),
-                )
-            })?
-        } else {
-            raw.parse::<i64>().map_err(|_| {
-                io::Error::new(
-                    io::ErrorKind::InvalidInput,
-                    opts.error_msg.as_deref().unwrap_or("invalid number"),
-                )
-            })? as f64
+        raw.parse::<i64>().map_err(|_| err())? as f64
+    };
+    let v = round_n(v, opts.round);
+    let v = opts.min.map_or(v, |m| v.max(m));
+    Ok(opts.max.map_or(v, |m| v.min(m)))
+}
+
+/// Number prompt state: the line being edited and the submitted value.
+pub struct NumberPrompt {
+    opts: NumberPromptOptions,
+    input: LineEditor,
+    value: Option<f64>,
+}
+
+impl NumberPrompt {
+    pub fn new(opts: NumberPromptOptions) -> Self {
+        let mut prompt = Self {
+            opts,
+            input: LineEditor::new(),
+            value: None,
         };
-        let v = round_n(v, opts.round);
-        let v = opts.min.map_or(v, |m| v.max(m));
-        opts.max.map_or(v, |m| v.min(m))
+        prompt.reset();
+        prompt
+    }
+
+    /// Empties the input, or refills it with the initial value when `prefill` is set.
+    fn reset(&mut self) {
+        match self.opts.initial {
+            Some(initial) if self.opts.prefill => self.input.set(&self.display(initial)),
+            _ => self.input.set(""),
+        }
+    }
+
+    /// Parses the typed input: initial/0 when empty, rounded and clamped otherwise.
+    pub fn parse(&self) -> Result<f64, String> {
+        let typed = self.input.text();
+        let raw = typed.trim();
+        if raw.is_empty() {
+            return Ok(self.opts.initial.unwrap_or(0.0));
+        }
+        parse_number(&self.opts, raw)
+    }
+
+    fn display(&self, value: f64) -> String {
+        if self.opts.float {
+            format!("{:.prec$}", value, prec = self.opts.round as usize)
+        } else {
+            format!("{}", value as i64)
+        }
+    }
+}
+
+impl Prompt for NumberPrompt {
+    type Output = f64;
+
+    fn render(&self, state: &State) -> Frame {
+        let head = prompt::title(&self.opts.message, state);
+        if *state != State::Active {
+            let shown = self.value.map(|v| self.display(v)).unwrap_or_default();
+            return Frame::line(format!("{} {}", head, shown));
+        }
+        // Without `prefill` the initial value is not shown; it is used when the input is empty.
+        let typed = self.input.text();
+        let col = display_width(&head) + 1 + display_width(&self.input.before_cursor());
+        Frame {
+            lines: vec![format!("{} {}", head, typed)],
+            cursor: Some((0, col)),
+        }
+    }
+
+    fn handle(&mut self, action: PromptAction) -> Transition<f64> {
+        if let Some(changed) = self.input.apply(&action) {
+            return if changed {
+                Transition::Continue
+            } else {
+                Transition::Bell
+            };
+        }
+        match action {
+            PromptAction::Submit => match self.parse() {
+                Ok(v) => {
+                    self.value = Some(v);
+                    Transition::Submit(v)
+                }
+                Err(msg) => Transition::Invalid(msg),
+            },
+            PromptAction::Reset => {
+                self.reset();
+                Transition::Continue
+            }
+            PromptAction::Next => match self.opts.initial {
+                Some(initial) if self.input.is_empty() => {
+                    self.input.set(&self.display(initial));
+                    Transition::Continue
+                }
+                _ => Transition::Bell,
+            },
+            _ => Transition::Bell,
+        }
+    }
+
+    fn input(&mut self, c: char) -> Transition<f64> {
+        self.input.insert(c);
+        Transition::Continue
+    }
+
+    /// A pre-filled value cannot be edited on a line, so the input starts empty.
+    fn line_mode(&mut self) {
+        self.opts.prefill = false;
+        self.reset();
+    }
+}
+
+/// Runs number prompt. Returns value or initial/0 when empty; `None` when cancelled.
+pub fn run_number<T: Terminal + ?Sized>(
+    opts: &NumberPromptOptions,
+    term: &mut T,
+) -> error::Result<Option<f64>> {
+    let check = Check {
+        validate: opts.validate.as_ref(),
+        max_attempts: opts.max_attempts,
+        timeout: opts.timeout,
+        on_timeout: opts.on_timeout,
+        ..Default::default()
     };
-    let displayed = if opts.float {
-        format!("{:.prec$}", value, prec = opts.round as usize)
-    } else {
-        format!("{}", value as i64)
+    prompt::drive(&mut NumberPrompt::new(opts.clone()), term, &check).map(|o| o.ok())
+}
+
+/// `run_number` for async code: waits for keys without blocking the runtime.
+#[cfg(feature = "tokio")]
+pub async fn run_number_async<T: AsyncTerminal + ?Sized>(
+    opts: &NumberPromptOptions,
+    term: &mut T,
+) -> error::Result<Option<f64>> {
+    let check = Check {
+        validate: opts.validate.as_ref(),
+        max_attempts: opts.max_attempts,
+        timeout: opts.timeout,
+        on_timeout: opts.on_timeout,
+        ..Default::default()
     };
-    let done_symbol = style::symbol(true, false, false);
-    let done_delim = style::delimiter(true);
-    writeln!(
-        stdout,
-        "\r{} {} {} {}",
-        done_symbol, msg, done_delim, displayed
-    )?;
-    stdout.flush()?;
-    Ok(value)
+    prompt::drive_async(&mut NumberPrompt::new(opts.clone()), term, &check)
+        .await
+        .map(|o| o.ok())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::io::Cursor;
+    use crate::terminal::MemoryTerminal;
+
+    fn typed(p: &mut NumberPrompt, s: &str) {
+        for c in s.chars() {
+            p.input(c);
+        }
+    }
+
+    #[test]
+    fn number_prompt_parse_and_submit() {
+        let mut p = NumberPrompt::new(NumberPromptOptions {
+            max: Some(10.0),
+            ..Default::default()
+        });
+        typed(&mut p, "42");
+        assert_eq!(p.handle(PromptAction::Submit), Transition::Submit(10.0));
+    }
+
+    #[test]
+    fn number_prompt_invalid_input_is_rejected() {
+        let mut p = NumberPrompt::new(NumberPromptOptions::default());
+        typed(&mut p, "4x");
+        assert!(matches!(
+            p.handle(PromptAction::Submit),
+            Transition::Invalid(_)
+        ));
+        p.handle(PromptAction::Delete);
+        assert_eq!(p.handle(PromptAction::Submit), Transition::Submit(4.0));
+    }
+
+    #[test]
+    fn number_prompt_tab_fills_initial() {
+        let mut p = NumberPrompt::new(NumberPromptOptions {
+            initial: Some(1.5),
+            float: true,
+            round: 1,
+            ..Default::default()
+        });
+        assert_eq!(p.handle(PromptAction::Next), Transition::Continue);
+        assert_eq!(p.input.text(), "1.5");
+        p.handle(PromptAction::Reset);
+        assert!(p.input.is_empty());
+    }
+
+    #[test]
+    fn number_prompt_prefill_is_editable() {
+        let opts = NumberPromptOptions {
+            message: "Ratio?".into(),
+            initial: Some(1.5),
+            prefill: true,
+            float: true,
+            round: 1,
+            ..Default::default()
+        };
+        let mut p = NumberPrompt::new(opts.clone());
+        assert!(p.render(&State::Active).lines[0].ends_with(" 1.5"));
+        p.handle(PromptAction::Delete);
+        p.input('7');
+        assert_eq!(p.handle(PromptAction::Submit), Transition::Submit(1.7));
+        p.handle(PromptAction::Reset);
+        assert_eq!(p.input.text(), "1.5");
+        let mut term = MemoryTerminal::new("\x7f2\r").tty(true);
+        assert_eq!(run_number(&opts, &mut term).unwrap(), Some(1.2));
+    }
 
     #[test]
     fn number_prompt_options_default() {
@@ -114,11 +300,10 @@ mod tests {
             float: false,
             ..Default::default()
         };
-        let mut stdin = Cursor::new(b"100\n");
-        let mut stdout = Vec::new();
-        let r = run_number(&opts, &mut stdin, &mut stdout);
+        let mut term = MemoryTerminal::new("100\n");
+        let r = run_number(&opts, &mut term);
         assert!(r.is_ok());
-        assert_eq!(r.unwrap(), 100.0);
+        assert_eq!(r.unwrap().unwrap(), 100.0);
     }
 
     #[test]
@@ -129,11 +314,10 @@ mod tests {
             round: 2,
             ..Default::default()
         };
-        let mut stdin = Cursor::new(b"2.5\n");
-        let mut stdout = Vec::new();
-        let r = run_number(&opts, &mut stdin, &mut stdout);
+        let mut term = MemoryTerminal::new("2.5\n");
+        let r = run_number(&opts, &mut term);
         assert!(r.is_ok());
-        assert!((r.unwrap() - 2.5).abs() < 0.001);
+        assert!((r.unwrap().unwrap() - 2.5).abs() < 0.001);
     }
 
     #[test]
@@ -143,9 +327,8 @@ mod tests {
             initial: Some(7.0),
             ..Default::default()
         };
-        let mut stdin = Cursor::new(b"\n");
-        let mut stdout = Vec::new();
-        assert_eq!(run_number(&opts, &mut stdin, &mut stdout).unwrap(), 7.0);
+        let mut term = MemoryTerminal::new("\n");
+        assert_eq!(run_number(&opts, &mut term).unwrap().unwrap(), 7.0);
     }
 
     #[test]
@@ -154,9 +337,8 @@ mod tests {
             message: "N?".into(),
             ..Default::default()
         };
-        let mut stdin = Cursor::new(b"\n");
-        let mut stdout = Vec::new();
-        assert_eq!(run_number(&opts, &mut stdin, &mut stdout).unwrap(), 0.0);
+        let mut term = MemoryTerminal::new("\n");
+        assert_eq!(run_number(&opts, &mut term).unwrap().unwrap(), 0.0);
     }
 
     #[test]
@@ -166,9 +348,8 @@ mod tests {
             min: Some(10.0),
             ..Default::default()
         };
-        let mut stdin = Cursor::new(b"5\n");
-        let mut stdout = Vec::new();
-        assert_eq!(run_number(&opts, &mut stdin, &mut stdout).unwrap(), 10.0);
+        let mut term = MemoryTerminal::new("5\n");
+        assert_eq!(run_number(&opts, &mut term).unwrap().unwrap(), 10.0);
     }
 
     #[test]
@@ -178,9 +359,8 @@ mod tests {
             max: Some(10.0),
             ..Default::default()
         };
-        let mut stdin = Cursor::new(b"99\n");
-        let mut stdout = Vec::new();
-        assert_eq!(run_number(&opts, &mut stdin, &mut stdout).unwrap(), 10.0);
+        let mut term = MemoryTerminal::new("99\n");
+        assert_eq!(run_number(&opts, &mut term).unwrap().unwrap(), 10.0);
     }
 
     #[test]
@@ -189,9 +369,8 @@ mod tests {
             message: "N?".into(),
             ..Default::default()
         };
-        let mut stdin = Cursor::new(b"not a number\n");
-        let mut stdout = Vec::new();
-        assert!(run_number(&opts, &mut stdin, &mut stdout).is_err());
+        let mut term = MemoryTerminal::new("not a number\n");
+        assert!(run_number(&opts, &mut term).is_err());
     }
 
     #[test]
@@ -201,9 +380,8 @@ mod tests {
             float: true,
             ..Default::default()
         };
-        let mut stdin = Cursor::new(b"abc\n");
-        let mut stdout = Vec::new();
-        assert!(run_number(&opts, &mut stdin, &mut stdout).is_err());
+        let mut term = MemoryTerminal::new("abc\n");
+        assert!(run_number(&opts, &mut term).is_err());
     }
 
     #[test]
@@ -213,9 +391,8 @@ mod tests {
             error_msg: None,
             ..Default::default()
         };
-        let mut stdin = Cursor::new(b"x\n");
-        let mut stdout = Vec::new();
-        let r = run_number(&opts, &mut stdin, &mut stdout);
+        let mut term = MemoryTerminal::new("x\n");
+        let r = run_number(&opts, &mut term);
         assert!(r.is_err());
         assert_eq!(r.unwrap_err().to_string(), "invalid number");
     }
@@ -228,11 +405,10 @@ mod tests {
             round: 2,
             ..Default::default()
         };
-        let mut stdin = Cursor::new(b"2.345\n");
-        let mut stdout = Vec::new();
-        let r = run_number(&opts, &mut stdin, &mut stdout);
+        let mut term = MemoryTerminal::new("2.345\n");
+        let r = run_number(&opts, &mut term);
         assert!(r.is_ok());
-        let v = r.unwrap();
+        let v = r.unwrap().unwrap();
         assert!((v - 2.35).abs() < 0.001);
     }
 
@@ -243,11 +419,10 @@ mod tests {
             float: false,
             ..Default::default()
         };
-        let mut stdin = Cursor::new(b"-42\n");
-        let mut stdout = Vec::new();
-        let r = run_number(&opts, &mut stdin, &mut stdout);
+        let mut term = MemoryTerminal::new("-42\n");
+        let r = run_number(&opts, &mut term);
         assert!(r.is_ok());
-        assert_eq!(r.unwrap(), -42.0);
+        assert_eq!(r.unwrap().unwrap(), -42.0);
     }
 
     #[test]
@@ -258,11 +433,10 @@ mod tests {
             min: Some(0.0),
             ..Default::default()
         };
-        let mut stdin = Cursor::new(b"-5.0\n");
-        let mut stdout = Vec::new();
-        let r = run_number(&opts, &mut stdin, &mut stdout);
+        let mut term = MemoryTerminal::new("-5.0\n");
+        let r = run_number(&opts, &mut term);
         assert!(r.is_ok());
-        assert_eq!(r.unwrap(), 0.0);
+        assert_eq!(r.unwrap().unwrap(), 0.0);
     }
 
     #[test]
@@ -273,10 +447,9 @@ mod tests {
             round: 0,
             ..Default::default()
         };
-        let mut stdin = Cursor::new(b"3.7\n");
-        let mut stdout = Vec::new();
-        let r = run_number(&opts, &mut stdin, &mut stdout);
+        let mut term = MemoryTerminal::new("3.7\n");
+        let r = run_number(&opts, &mut term);
         assert!(r.is_ok());
-        assert!((r.unwrap() - 4.0).abs() < 0.001);
+        assert!((r.unwrap().unwrap() - 4.0).abs() < 0.001);
     }
 }
@@ -1,26 +1,1074 @@
-//! Base prompt. Line-based I/O via stdout/stdin.
+//! Base prompt: the state-machine trait every element implements, and the loop that drives it.
 
-use ansi_escapes::Beep;
+use crate::answers::Answers;
+use crate::error::{Error, Result};
+use crate::prompts::{OnTimeout, PromptValue, Validator};
+#[cfg(feature = "tokio")]
+use crate::terminal::AsyncTerminal;
+use crate::terminal::Terminal;
+use crate::util::action::{Key, KeyName, PromptAction, key_action};
+use crate::util::lines::lines_count;
+use crate::util::{clear, style};
+use ansi_escapes::{Beep, CursorTo, CursorUp};
+use colour::{write_bold, write_gray, write_red};
 use std::io::{self, Write};
+use std::task::{Context, Poll, Waker};
+use std::time::Duration;
 
-/// Base prompt behavior: output helpers and bell (beep).
-pub struct Prompt;
+/// Lifecycle state a prompt is rendered in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum State {
+    Active,
+    Done,
+    Aborted,
+    Exited,
+}
+
+/// Rendered prompt: lines top to bottom and the cursor position as `(line, column)`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Frame {
+    pub lines: Vec<String>,
+    pub cursor: Option<(usize, usize)>,
+}
+
+impl Frame {
+    /// Builds a single-line frame with the cursor left at the end.
+    pub fn line(text: impl Into<String>) -> Self {
+        Self {
+            lines: vec![text.into()],
+            cursor: None,
+        }
+    }
+}
+
+/// Outcome of handling one key or action.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Transition<T> {
+    /// State changed; redraw.
+    Continue,
+    /// Action does not apply; ring the bell.
+    Bell,
+    /// Submitted input was rejected with the given message.
+    Invalid(String),
+    /// Prompt finished with a value.
+    Submit(T),
+    /// Prompt was aborted (Ctrl-C, Ctrl-D).
+    Abort,
+    /// Prompt was exited (Escape).
+    Exit,
+}
+
+impl<T> Transition<T> {
+    /// Converts the submitted value, keeping every other transition.
+    pub fn map<U>(self, f: impl FnOnce(T) -> U) -> Transition<U> {
+        match self {
+            Transition::Continue => Transition::Continue,
+            Transition::Bell => Transition::Bell,
+            Transition::Invalid(msg) => Transition::Invalid(msg),
+            Transition::Submit(v) => Transition::Submit(f(v)),
+            Transition::Abort => Transition::Abort,
+            Transition::Exit => Transition::Exit,
+        }
+    }
+}
+
+/// A prompt element as a pure state machine: `render` draws it, `handle` advances it.
+pub trait Prompt {
+    type Output;
+
+    /// Renders the prompt for the given lifecycle state.
+    fn render(&self, state: &State) -> Frame;
+
+    /// Applies an action produced by `key_action`.
+    fn handle(&mut self, action: PromptAction) -> Transition<Self::Output>;
+
+    /// Applies a typed character. Prompts without free text input ring the bell.
+    fn input(&mut self, _c: char) -> Transition<Self::Output> {
+        Transition::Bell
+    }
+
+    /// Maps a key to an action or typed character and applies it.
+    fn keypress(&mut self, key: &Key) -> Transition<Self::Output> {
+        dispatch(self, key, false)
+    }
+
+    /// Called before the prompt is asked without a terminal, where answers are typed as whole
+    /// lines and cannot be edited in place.
+    fn line_mode(&mut self) {}
+
+    /// Called when input ends. Cancels by default.
+    fn input_end(&mut self) -> Transition<Self::Output> {
+        Transition::Exit
+    }
+}
+
+/// Prompt whose submitted value is converted by `f`.
+pub(crate) struct Map<P, F> {
+    pub(crate) prompt: P,
+    pub(crate) f: F,
+}
+
+impl<P: Prompt, U, F: Fn(P::Output) -> U> Prompt for Map<P, F> {
+    type Output = U;
+
+    fn render(&self, state: &State) -> Frame {
+        self.prompt.render(state)
+    }
+
+    fn handle(&mut self, action: PromptAction) -> Transition<U> {
+        self.prompt.handle(action).map(&self.f)
+    }
+
+    fn input(&mut self, c: char) -> Transition<U> {
+        self.prompt.input(c).map(&self.f)
+    }
+
+    fn keypress(&mut self, key: &Key) -> Transition<U> {
+        self.prompt.keypress(key).map(&self.f)
+    }
+
+    fn line_mode(&mut self) {
+        self.prompt.line_mode()
+    }
 
-impl Prompt {
-    pub fn bell(out: &mut dyn Write) -> io::Result<()> {
-        write!(out, "{}", Beep)
+    fn input_end(&mut self) -> Transition<U> {
+        self.prompt.input_end().map(&self.f)
     }
 }
 
+/// Default key routing: Abort and Exit cancel, other mapped actions go to `handle`, plain
+/// characters to `input`.
+pub(crate) fn dispatch<P: Prompt + ?Sized>(
+    prompt: &mut P,
+    key: &Key,
+    is_select: bool,
+) -> Transition<P::Output> {
+    match key_action(key, is_select) {
+        Some(PromptAction::Abort) => Transition::Abort,
+        Some(PromptAction::Exit) => Transition::Exit,
+        Some(action) => prompt.handle(action),
+        None => match key.name {
+            KeyName::Char(c) if !key.ctrl && !key.meta => prompt.input(c),
+            _ => Transition::Bell,
+        },
+    }
+}
+
+/// Rings the terminal bell.
+pub(crate) fn bell<T: Terminal + ?Sized>(term: &mut T) -> io::Result<()> {
+    term.write(&Beep.to_string())?;
+    term.flush()
+}
+
+/// Returns the leading `symbol message delimiter` part shared by all prompts.
+pub(crate) fn title(message: &str, state: &State) -> String {
+    let mut buf = Vec::with_capacity(message.len() + 32);
+    write_bold!(&mut buf, "{}", message).ok();
+    let msg = String::from_utf8_lossy(&buf).into_owned();
+    let symbol = style::symbol(
+        *state == State::Done,
+        *state == State::Aborted,
+        *state == State::Exited,
+    );
+    let delim = style::delimiter(*state != State::Active);
+    format!("{} {} {}", symbol, msg, delim)
+}
+
+/// Terminal rows taken by `line` at `width` columns.
+fn rows(line: &str, width: usize) -> usize {
+    lines_count(line, width).max(1)
+}
+
+/// Redraws frames in place on a raw-mode terminal.
+struct Screen {
+    width: usize,
+    /// Text of the last frame and how many rows the cursor sits above its end.
+    prev: Option<(String, usize)>,
+}
+
+impl Screen {
+    fn draw(&mut self, frame: &Frame) -> String {
+        let mut out = String::new();
+        if let Some((text, up)) = self.prev.take() {
+            if up > 0 {
+                out += &ansi_escapes::CursorDown(up as u16).to_string();
+            }
+            out += &clear::clear(&text, self.width);
+        }
+        // In raw mode, \n alone does not move to column 0; use \r\n so each line starts at column 0.
+        let text = frame.lines.join("\r\n");
+        out += &text;
+        let mut up = 0;
+        if let Some((line, col)) = frame.cursor {
+            let width = self.width.max(1);
+            let below: usize = frame.lines[line + 1..].iter().map(|l| rows(l, width)).sum();
+            up = below + (rows(&frame.lines[line], width) - 1).saturating_sub(col / width);
+            if up > 0 {
+                out += &CursorUp(up as u16).to_string();
+            }
+            out += &CursorTo::AbsoluteX((col % width) as u16).to_string();
+        }
+        self.prev = Some((text.replace("\r\n", "\n"), up));
+        out
+    }
+}
+
+/// How a driven prompt ended.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Outcome<T> {
+    Submitted(T),
+    /// Cancelled; holds the final state (`Aborted` or `Exited`).
+    Cancelled(State),
+    /// Cancelled because the timeout ran out.
+    TimedOut,
+}
+
+impl<T> Outcome<T> {
+    /// Returns the value, or `None` when cancelled.
+    pub(crate) fn ok(self) -> Option<T> {
+        match self {
+            Outcome::Submitted(v) => Some(v),
+            Outcome::Cancelled(_) | Outcome::TimedOut => None,
+        }
+    }
+}
+
+/// Answer checks the driver applies on submit.
+#[derive(Default)]
+pub(crate) struct Check<'a> {
+    pub(crate) validate: Option<&'a Validator>,
+    /// Answers given so far, passed to `validate`.
+    pub(crate) answers: Option<&'a Answers>,
+    /// Failed submissions allowed before giving up; `None` retries forever.
+    pub(crate) max_attempts: Option<u32>,
+    /// Replaces the answer shown on the done line, after `message`.
+    pub(crate) display: Option<&'a dyn Fn(&PromptValue) -> String>,
+    /// Message the done line starts with when `display` is set.
+    pub(crate) message: &'a str,
+    /// Time left to answer before `on_timeout` applies; any key stops the countdown.
+    pub(crate) timeout: Option<Duration>,
+    pub(crate) on_timeout: OnTimeout,
+}
+
+/// How often the countdown is redrawn.
+const TICK: Duration = Duration::from_secs(1);
+
+/// Result of waiting for a key.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Input {
+    Key(Key),
+    /// End of input.
+    End,
+    /// No key arrived within the wait.
+    Idle,
+}
+
+/// Reads a key with `Terminal::read_key`, first waiting up to `wait` for one.
+pub(crate) fn read_blocking<T: Terminal + ?Sized>(
+    term: &mut T,
+    wait: Option<Duration>,
+) -> io::Result<Input> {
+    if let Some(wait) = wait
+        && !term.poll_key(wait)?
+    {
+        return Ok(Input::Idle);
+    }
+    Ok(term.read_key()?.map_or(Input::End, Input::Key))
+}
+
+/// Awaits a key with `AsyncTerminal::read_key_async`, giving up after `wait`.
+#[cfg(feature = "tokio")]
+pub(crate) async fn read_async<T: AsyncTerminal + ?Sized>(
+    term: &mut T,
+    wait: Option<Duration>,
+) -> io::Result<Input> {
+    let key = match wait {
+        Some(wait) => match tokio::time::timeout(wait, term.read_key_async()).await {
+            Ok(key) => key?,
+            Err(_) => return Ok(Input::Idle),
+        },
+        None => term.read_key_async().await?,
+    };
+    Ok(key.map_or(Input::End, Input::Key))
+}
+
+/// Returns `text` in gray.
+pub(crate) fn gray(text: &str) -> String {
+    let mut buf = Vec::with_capacity(text.len() + 16);
+    write_gray!(&mut buf, "{}", text).ok();
+    String::from_utf8_lossy(&buf).into_owned()
+}
+
+/// Returns the countdown line shown under a prompt with a timeout.
+fn countdown_line(left: Duration, on_timeout: OnTimeout) -> String {
+    let secs = left.as_secs_f64().ceil() as u64;
+    gray(&match on_timeout {
+        OnTimeout::Initial => format!("Answering with the default in {}s", secs),
+        OnTimeout::Cancel => format!("Cancelling in {}s", secs),
+    })
+}
+
+/// Returns the red-cross line shown under a prompt whose answer was rejected.
+pub(crate) fn error_line(message: &str) -> String {
+    let mut buf = Vec::with_capacity(message.len() + 16);
+    write_red!(&mut buf, "{}", message).ok();
+    format!(
+        "{} {}",
+        style::symbol(false, true, false),
+        String::from_utf8_lossy(&buf)
+    )
+}
+
+/// Runs a prompt to completion on `term`.
+///
+/// On a TTY the terminal is put in raw mode and every change is redrawn in place; raw mode is
+/// restored before returning. Otherwise the prompt is written once, keys are consumed as typed,
+/// and only the result is printed. Abort, Exit and end of input cancel the prompt.
+///
+/// A rejected answer (`Transition::Invalid` or a failing `check.validate`) is shown under the
+/// input and the prompt is asked again. It becomes `Error::Validation` once `check.max_attempts`
+/// is used up, or when input ends while the error is still pending.
+pub(crate) fn drive<P, T>(prompt: &mut P, term: &mut T, check: &Check) -> Result<Outcome<P::Output>>
+where
+    P: Prompt + ?Sized,
+    P::Output: Clone + Into<PromptValue>,
+    T: Terminal + ?Sized,
+{
+    now(drive_with(
+        prompt,
+        term,
+        check,
+        &mut async |t: &mut T, wait| read_blocking(t, wait),
+    ))
+}
+
+/// `drive` reading keys without blocking the async runtime.
+#[cfg(feature = "tokio")]
+pub(crate) async fn drive_async<P, T>(
+    prompt: &mut P,
+    term: &mut T,
+    check: &Check<'_>,
+) -> Result<Outcome<P::Output>>
+where
+    P: Prompt + ?Sized,
+    P::Output: Clone + Into<PromptValue>,
+    T: AsyncTerminal + ?Sized,
+{
+    drive_with(prompt, term, check, &mut async |t: &mut T, wait| {
+        read_async(t, wait).await
+    })
+    .await
+}
+
+/// Polls a future that never waits, such as a driver reading keys with `Terminal::read_key`.
+pub(crate) fn now<F: Future>(future: F) -> F::Output {
+    let mut future = std::pin::pin!(future);
+    match future
+        .as_mut()
+        .poll(&mut Context::from_waker(Waker::noop()))
+    {
+        Poll::Ready(output) => output,
+        Poll::Pending => unreachable!("blocking key reads never return pending"),
+    }
+}
+
+/// `drive` with keys read by `read`, which waits at most the given time when there is one.
+///
+/// Dropping the future before it completes, e.g. to cancel an async prompt, draws the prompt as
+/// aborted and restores raw mode.
+pub(crate) async fn drive_with<P, T, R>(
+    prompt: &mut P,
+    term: &mut T,
+    check: &Check<'_>,
+    read: &mut R,
+) -> Result<Outcome<P::Output>>
+where
+    P: Prompt + ?Sized,
+    P::Output: Clone + Into<PromptValue>,
+    T: Terminal + ?Sized,
+    R: AsyncFnMut(&mut T, Option<Duration>) -> io::Result<Input>,
+{
+    let interactive = term.is_tty();
+    if interactive {
+        term.enable_raw()?;
+    }
+    let mut session = Session {
+        prompt,
+        term,
+        interactive,
+        raw: interactive,
+        finished: false,
+        screen: Screen {
+            width: 80,
+            prev: None,
+        },
+    };
+    let result = session.run(check, read).await;
+    session.finished = true;
+    session.raw = false;
+    let restored = if interactive {
+        session.term.disable_raw()
+    } else {
+        Ok(())
+    };
+    let value = result?;
+    restored?;
+    Ok(value)
+}
+
+/// A prompt being driven on a terminal.
+struct Session<'a, P: Prompt + ?Sized, T: Terminal + ?Sized> {
+    prompt: &'a mut P,
+    term: &'a mut T,
+    interactive: bool,
+    /// Whether raw mode still has to be restored.
+    raw: bool,
+    /// Whether the final frame was written.
+    finished: bool,
+    screen: Screen,
+}
+
+impl<P, T> Session<'_, P, T>
+where
+    P: Prompt + ?Sized,
+    P::Output: Clone + Into<PromptValue>,
+    T: Terminal + ?Sized,
+{
+    async fn run<R>(&mut self, check: &Check<'_>, read: &mut R) -> Result<Outcome<P::Output>>
+    where
+        R: AsyncFnMut(&mut T, Option<Duration>) -> io::Result<Input>,
+    {
+        let empty = Answers::new();
+        let answers = check.answers.unwrap_or(&empty);
+        let interactive = self.interactive;
+        let mut error: Option<String> = None;
+        let mut failures = 0;
+        let mut countdown = check.timeout;
+        if interactive {
+            self.screen.width = self.term.size().map_or(80, |(w, _)| w as usize);
+            self.redraw(countdown, check.on_timeout)?;
+        } else {
+            self.prompt.line_mode();
+            self.term
+                .write(&self.prompt.render(&State::Active).lines.join("\n"))?;
+            self.term.flush()?;
+        }
+        loop {
+            let wait = countdown.map(|left| left.min(TICK));
+            let transition = match read(&mut *self.term, wait).await? {
+                Input::Key(key) => {
+                    if countdown.take().is_some() && interactive {
+                        self.redraw(None, check.on_timeout)?;
+                    }
+                    self.prompt.keypress(&key)
+                }
+                Input::Idle => {
+                    let (Some(left), Some(waited)) = (countdown, wait) else {
+                        continue;
+                    };
+                    let left = left.saturating_sub(waited);
+                    if left.is_zero() {
+                        return self.time_out(check, answers);
+                    }
+                    countdown = Some(left);
+                    if interactive {
+                        self.redraw(countdown, check.on_timeout)?;
+                    }
+                    continue;
+                }
+                Input::End => match error.take() {
+                    Some(message) => {
+                        if interactive {
+                            self.term.write("\r\n")?;
+                        }
+                        return Err(Error::Validation {
+                            name: String::new(),
+                            message,
+                        });
+                    }
+                    None => self.prompt.input_end(),
+                },
+            };
+            let transition = match transition {
+                Transition::Submit(value) => match check.validate {
+                    Some(validate) => match validate(&value.clone().into(), answers) {
+                        Ok(()) => Transition::Submit(value),
+                        Err(msg) => Transition::Invalid(msg),
+                    },
+                    None => Transition::Submit(value),
+                },
+                t => t,
+            };
+            let state = match transition {
+                Transition::Continue => {
+                    error = None;
+                    if interactive {
+                        self.redraw(None, check.on_timeout)?;
+                    }
+                    continue;
+                }
+                Transition::Bell => {
+                    if interactive {
+                        bell(self.term)?;
+                    }
+                    continue;
+                }
+                Transition::Invalid(msg) => {
+                    failures += 1;
+                    let exhausted = check.max_attempts.is_some_and(|n| failures >= n);
+                    if interactive {
+                        let mut frame = self.prompt.render(&State::Active);
+                        frame.lines.push(error_line(&msg));
+                        if exhausted {
+                            let text = self.screen.draw(&Frame {
+                                cursor: None,
+                                ..frame
+                            });
+                            self.term.write(&format!("{}\r\n", text))?;
+                        } else {
+                            let text = self.screen.draw(&frame);
+                            self.term.write(&text)?;
+                        }
+                    } else {
+                        self.term.write(&format!("\r{}\n", error_line(&msg)))?;
+                        if !exhausted {
+                            self.prompt.handle(PromptAction::Reset);
+                            self.term
+                                .write(&self.prompt.render(&State::Active).lines.join("\n"))?;
+                        }
+                    }
+                    self.term.flush()?;
+                    if exhausted {
+                        self.finished = true;
+                        return Err(Error::Validation {
+                            name: String::new(),
+                            message: msg,
+                        });
+                    }
+                    error = Some(msg);
+                    continue;
+                }
+                Transition::Submit(value) => {
+                    let done = match check.display {
+                        Some(display) => Frame::line(format!(
+                            "{} {}",
+                            title(check.message, &State::Done),
+                            display(&value.clone().into())
+                        )),
+                        None => self.prompt.render(&State::Done),
+                    };
+                    self.finished = true;
+                    finish(self.term, &mut self.screen, done, interactive)?;
+                    return Ok(Outcome::Submitted(value));
+                }
+                Transition::Abort => State::Aborted,
+                Transition::Exit => State::Exited,
+            };
+            self.finished = true;
+            let frame = self.prompt.render(&state);
+            finish(self.term, &mut self.screen, frame, interactive)?;
+            return Ok(Outcome::Cancelled(state));
+        }
+    }
+
+    /// Redraws the active frame, with the countdown under it while one runs.
+    fn redraw(&mut self, countdown: Option<Duration>, on_timeout: OnTimeout) -> io::Result<()> {
+        let mut frame = self.prompt.render(&State::Active);
+        if let Some(left) = countdown {
+            frame.lines.push(countdown_line(left, on_timeout));
+        }
+        let text = self.screen.draw(&frame);
+        self.term.write(&text)?;
+        self.term.flush()
+    }
+
+    /// Ends the prompt after its timeout ran out: submits the initial value, or cancels.
+    ///
+    /// An initial value that is not accepted fails with `Error::Timeout`.
+    fn time_out(&mut self, check: &Check<'_>, answers: &Answers) -> Result<Outcome<P::Output>> {
+        self.finished = true;
+        let interactive = self.interactive;
+        let submitted = match check.on_timeout {
+            OnTimeout::Initial => match self.prompt.handle(PromptAction::Submit) {
+                Transition::Submit(value) => match check.validate {
+                    Some(validate) if validate(&value.clone().into(), answers).is_err() => None,
+                    _ => Some(value),
+                },
+                _ => None,
+            },
+            OnTimeout::Cancel => None,
+        };
+        let Some(value) = submitted else {
+            let mut frame = self.prompt.render(&State::Aborted);
+            note(&mut frame, "(timed out)");
+            finish(self.term, &mut self.screen, frame, interactive)?;
+            return match check.on_timeout {
+                OnTimeout::Cancel => Ok(Outcome::TimedOut),
+                OnTimeout::Initial => Err(Error::Timeout {
+                    name: String::new(),
+                }),
+            };
+        };
+        let mut done = match check.display {
+            Some(display) => Frame::line(format!(
+                "{} {}",
+                title(check.message, &State::Done),
+                display(&value.clone().into())
+            )),
+            None => self.prompt.render(&State::Done),
+        };
+        note(&mut done, "(timed out, took the default)");
+        finish(self.term, &mut self.screen, done, interactive)?;
+        Ok(Outcome::Submitted(value))
+    }
+}
+
+impl<P: Prompt + ?Sized, T: Terminal + ?Sized> Drop for Session<'_, P, T> {
+    /// Cleans up after a session cut short by dropping its future.
+    fn drop(&mut self) {
+        if !self.finished {
+            let frame = self.prompt.render(&State::Aborted);
+            finish(self.term, &mut self.screen, frame, self.interactive).ok();
+        }
+        if self.raw {
+            self.term.disable_raw().ok();
+        }
+    }
+}
+
+/// Appends a gray note to the last line of `frame`.
+fn note(frame: &mut Frame, text: &str) {
+    if let Some(line) = frame.lines.last_mut() {
+        line.push(' ');
+        line.push_str(&gray(text));
+    }
+}
+
+/// Writes the final frame and moves to the next line.
+fn finish<T: Terminal + ?Sized>(
+    term: &mut T,
+    screen: &mut Screen,
+    done: Frame,
+    interactive: bool,
+) -> io::Result<()> {
+    if interactive {
+        let text = screen.draw(&Frame {
+            cursor: None,
+            ..done
+        });
+        term.write(&format!("{}\r\n", text))?;
+    } else {
+        term.write(&format!("\r{}\n", done.lines.join("\n")))?;
+    }
+    term.flush()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::terminal::MemoryTerminal;
+    use std::sync::Arc;
+
+    /// Collects typed characters until submit; rejects "bad".
+    struct Echo {
+        typed: String,
+    }
+
+    impl Prompt for Echo {
+        type Output = String;
+
+        fn render(&self, state: &State) -> Frame {
+            Frame::line(format!("{} {}", title("Echo?", state), self.typed))
+        }
+
+        fn handle(&mut self, action: PromptAction) -> Transition<String> {
+            match action {
+                PromptAction::Submit if self.typed == "bad" => Transition::Invalid("no".into()),
+                PromptAction::Submit => Transition::Submit(self.typed.clone()),
+                PromptAction::Reset => {
+                    self.typed.clear();
+                    Transition::Continue
+                }
+                _ => Transition::Bell,
+            }
+        }
+
+        fn input(&mut self, c: char) -> Transition<String> {
+            self.typed.push(c);
+            Transition::Continue
+        }
+    }
+
+    #[test]
+    fn screen_draw_counts_wrapped_wide_characters() {
+        let mut screen = Screen {
+            width: 10,
+            prev: None,
+        };
+        let frame = Frame {
+            lines: vec!["日本語日本語".into(), "hint".into()],
+            cursor: Some((0, 4)),
+        };
+        let out = screen.draw(&frame);
+        assert!(out.ends_with(&format!("{}{}", CursorUp(2), CursorTo::AbsoluteX(4))));
+        let out = screen.draw(&frame);
+        let cleared = format!(
+            "{}{}",
+            ansi_escapes::CursorDown(2),
+            ansi_escapes::EraseLines(3)
+        );
+        assert!(out.starts_with(&cleared));
+    }
 
     #[test]
     fn prompt_bell_writes_to_output() {
-        let mut buf = Vec::new();
-        let r = Prompt::bell(&mut buf);
-        assert!(r.is_ok());
-        assert!(!buf.is_empty());
+        let mut term = MemoryTerminal::new("");
+        assert!(bell(&mut term).is_ok());
+        assert!(!term.output().is_empty());
+    }
+
+    #[test]
+    fn frame_line_has_no_cursor() {
+        let f = Frame::line("x");
+        assert_eq!(f.lines, ["x"]);
+        assert!(f.cursor.is_none());
+    }
+
+    #[test]
+    fn title_contains_message() {
+        assert!(title("Hello?", &State::Active).contains("Hello?"));
+        assert_ne!(title("Hi", &State::Active), title("Hi", &State::Done));
+    }
+
+    #[test]
+    fn keypress_routes_chars_to_input_and_keys_to_handle() {
+        let mut p = Echo {
+            typed: String::new(),
+        };
+        let k = Key {
+            name: KeyName::Char('x'),
+            ctrl: false,
+            meta: false,
+        };
+        assert_eq!(p.keypress(&k), Transition::Continue);
+        assert_eq!(p.typed, "x");
+        let reset = Key {
+            name: KeyName::Char('g'),
+            ctrl: true,
+            meta: false,
+        };
+        assert_eq!(p.keypress(&reset), Transition::Continue);
+        assert!(p.typed.is_empty());
+    }
+
+    #[test]
+    fn keypress_unmapped_ctrl_rings_bell() {
+        let mut p = Echo {
+            typed: String::new(),
+        };
+        let k = Key {
+            name: KeyName::Char('z'),
+            ctrl: true,
+            meta: false,
+        };
+        assert_eq!(p.keypress(&k), Transition::Bell);
+    }
+
+    #[test]
+    fn drive_line_mode_submits_on_enter() {
+        let mut p = Echo {
+            typed: String::new(),
+        };
+        let mut term = MemoryTerminal::new("abc\nrest");
+        let r = drive(&mut p, &mut term, &Check::default()).unwrap();
+        assert_eq!(r, Outcome::Submitted("abc".into()));
+        assert!(term.output().ends_with("abc\n"));
+        assert_eq!(term.remaining_input(), b"rest");
+    }
+
+    #[test]
+    fn drive_line_mode_eof_cancels() {
+        let mut p = Echo {
+            typed: String::new(),
+        };
+        let mut term = MemoryTerminal::new("abc");
+        assert_eq!(
+            drive(&mut p, &mut term, &Check::default()).unwrap(),
+            Outcome::Cancelled(State::Exited)
+        );
+        assert!(term.output().contains(&style::symbol(false, false, true)));
+        assert!(term.output().ends_with('\n'));
+    }
+
+    #[test]
+    fn drive_ctrl_c_aborts() {
+        let mut p = Echo {
+            typed: String::new(),
+        };
+        let mut term = MemoryTerminal::new("ab\x03cd\n");
+        assert_eq!(
+            drive(&mut p, &mut term, &Check::default()).unwrap(),
+            Outcome::Cancelled(State::Aborted)
+        );
+        assert_eq!(term.remaining_input(), b"cd\n");
+    }
+
+    #[test]
+    fn drive_escape_exits_with_yellow_cross() {
+        let mut p = Echo {
+            typed: String::new(),
+        };
+        let mut term = MemoryTerminal::new("ab\x1b").tty(true);
+        assert_eq!(
+            drive(&mut p, &mut term, &Check::default()).unwrap(),
+            Outcome::Cancelled(State::Exited)
+        );
+        assert!(term.output().contains(&style::symbol(false, false, true)));
+        assert!(!term.is_raw());
+    }
+
+    #[test]
+    fn keypress_abort_and_exit_cancel() {
+        let mut p = Echo {
+            typed: String::new(),
+        };
+        assert_eq!(
+            p.keypress(&Key {
+                name: KeyName::Char('d'),
+                ctrl: true,
+                meta: false
+            }),
+            Transition::Abort
+        );
+        assert_eq!(p.keypress(&Key::new(KeyName::Escape)), Transition::Exit);
+    }
+
+    #[test]
+    fn outcome_ok() {
+        assert_eq!(Outcome::Submitted(1).ok(), Some(1));
+        assert_eq!(Outcome::<i32>::Cancelled(State::Exited).ok(), None);
+    }
+
+    #[test]
+    fn drive_invalid_returns_err() {
+        let mut p = Echo {
+            typed: String::new(),
+        };
+        let mut term = MemoryTerminal::new("bad\n");
+        let e = drive(&mut p, &mut term, &Check::default()).unwrap_err();
+        assert_eq!(e.to_string(), "no");
+    }
+
+    #[test]
+    fn drive_interactive_redraws_on_each_key() {
+        let mut p = Echo {
+            typed: String::new(),
+        };
+        let mut term = MemoryTerminal::new("ab\r").tty(true);
+        assert_eq!(
+            drive(&mut p, &mut term, &Check::default()).unwrap(),
+            Outcome::Submitted("ab".into())
+        );
+        assert_eq!(term.output().matches("Echo?").count(), 4);
+        assert!(term.output().ends_with("\r\n"));
+        assert!(!term.is_raw());
+    }
+
+    #[test]
+    fn drive_interactive_restores_raw_mode_on_error() {
+        let mut p = Echo {
+            typed: String::new(),
+        };
+        let mut term = MemoryTerminal::new("bad\r").tty(true);
+        assert!(drive(&mut p, &mut term, &Check::default()).is_err());
+        assert!(!term.is_raw());
+    }
+
+    #[test]
+    fn drive_interactive_uses_terminal_width() {
+        let mut p = Echo {
+            typed: String::new(),
+        };
+        let mut term = MemoryTerminal::new("abcdefghij\r")
+            .tty(true)
+            .with_size(10, 5);
+        drive(&mut p, &mut term, &Check::default()).unwrap();
+        // The frame wraps at 10 columns, so clearing erases more than one line.
+        assert!(
+            term.output()
+                .contains(&ansi_escapes::EraseLines(2).to_string())
+        );
+    }
+
+    fn reject_short() -> Validator {
+        Arc::new(|v, _| match v {
+            PromptValue::String(s) if s.len() < 3 => Err(format!("'{}' is too short", s)),
+            _ => Ok(()),
+        })
+    }
+
+    #[test]
+    fn drive_line_mode_reprompts_after_rejection() {
+        let mut p = Echo {
+            typed: String::new(),
+        };
+        let validate = reject_short();
+        let check = Check {
+            validate: Some(&validate),
+            ..Default::default()
+        };
+        let mut term = MemoryTerminal::new("ab\nabc\n");
+        assert_eq!(
+            drive(&mut p, &mut term, &check).unwrap(),
+            Outcome::Submitted("abc".into())
+        );
+        assert!(term.output().contains("'ab' is too short"));
+        assert_eq!(term.output().matches("Echo?").count(), 3);
+    }
+
+    #[test]
+    fn drive_interactive_shows_error_under_input_until_next_key() {
+        let mut p = Echo {
+            typed: String::new(),
+        };
+        let validate = reject_short();
+        let check = Check {
+            validate: Some(&validate),
+            ..Default::default()
+        };
+        let mut term = MemoryTerminal::new("ab\rc\r").tty(true);
+        drive(&mut p, &mut term, &check).unwrap();
+        let out = term.output();
+        let shown = out.find("'ab' is too short").unwrap();
+        // The next key redraws the frame without the error line.
+        let redraw = out[shown..].find(" abc").unwrap();
+        assert!(!out[shown + redraw..].contains("too short"));
+        assert!(!term.is_raw());
+    }
+
+    #[test]
+    fn drive_stops_after_max_attempts() {
+        let mut p = Echo {
+            typed: String::new(),
+        };
+        let validate = reject_short();
+        let check = Check {
+            validate: Some(&validate),
+            max_attempts: Some(1),
+            ..Default::default()
+        };
+        let mut term = MemoryTerminal::new("ab\nabc\n");
+        let e = drive(&mut p, &mut term, &check).unwrap_err();
+        assert_eq!(e.to_string(), "'ab' is too short");
+        assert_eq!(term.remaining_input(), b"abc\n");
+    }
+
+    #[test]
+    fn drive_validator_sees_answers() {
+        let mut p = Echo {
+            typed: String::new(),
+        };
+        let validate: Validator = Arc::new(|v, answers| match answers.get("taken") {
+            Some(taken) if taken == v => Err("taken".into()),
+            _ => Ok(()),
+        });
+        let answers = Answers::from([("taken".to_string(), PromptValue::from("ab"))]);
+        let check = Check {
+            validate: Some(&validate),
+            answers: Some(&answers),
+            max_attempts: Some(1),
+            ..Default::default()
+        };
+        let mut term = MemoryTerminal::new("ab\n");
+        assert_eq!(
+            drive(&mut p, &mut term, &check).unwrap_err().to_string(),
+            "taken"
+        );
+    }
+
+    #[test]
+    fn drive_display_replaces_done_line() {
+        let mut p = Echo {
+            typed: String::new(),
+        };
+        let display = |v: &PromptValue| format!("<{:?}>", v);
+        let check = Check {
+            display: Some(&display),
+            message: "Shown?",
+            ..Default::default()
+        };
+        let mut term = MemoryTerminal::new("ab\n");
+        drive(&mut p, &mut term, &check).unwrap();
+        let done = term.output().rsplit('\r').next().unwrap();
+        assert!(done.contains("Shown?"));
+        assert!(done.contains("<String(\"ab\")>"));
+        assert!(!done.contains("Echo?"));
+    }
+
+    fn timing_out(on_timeout: OnTimeout) -> Check<'static> {
+        Check {
+            timeout: Some(Duration::from_secs(3)),
+            on_timeout,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn drive_timeout_submits_the_initial_value() {
+        let mut p = Echo { typed: "x".into() };
+        let mut term = MemoryTerminal::new("").tty(true);
+        let check = timing_out(OnTimeout::Initial);
+        assert_eq!(
+            drive(&mut p, &mut term, &check).unwrap(),
+            Outcome::Submitted("x".into())
+        );
+        for left in ["3s", "2s", "1s"] {
+            assert!(
+                term.output()
+                    .contains(&format!("Answering with the default in {}", left))
+            );
+        }
+        assert!(term.output().contains("(timed out, took the default)"));
+        assert!(!term.is_raw());
+    }
+
+    #[test]
+    fn drive_key_stops_the_countdown() {
+        let mut p = Echo {
+            typed: String::new(),
+        };
+        let mut term = MemoryTerminal::new("ab\r").tty(true);
+        let check = timing_out(OnTimeout::Initial);
+        assert_eq!(
+            drive(&mut p, &mut term, &check).unwrap(),
+            Outcome::Submitted("ab".into())
+        );
+        assert_eq!(
+            term.output().matches("Answering with the default").count(),
+            1
+        );
+        assert!(!term.output().contains("timed out"));
+    }
+
+    #[test]
+    fn drive_timeout_can_cancel() {
+        let mut p = Echo { typed: "x".into() };
+        let mut term = MemoryTerminal::new("").tty(true);
+        let check = timing_out(OnTimeout::Cancel);
+        assert_eq!(drive(&mut p, &mut term, &check).unwrap(), Outcome::TimedOut);
+        assert!(term.output().contains("Cancelling in 3s"));
+        assert!(term.output().contains(&style::symbol(false, true, false)));
+        assert!(term.output().contains("(timed out)"));
+    }
+
+    #[test]
+    fn drive_timeout_with_rejected_default_fails() {
+        let mut p = Echo {
+            typed: "bad".into(),
+        };
+        let mut term = MemoryTerminal::new("").tty(true);
+        let e = drive(&mut p, &mut term, &timing_out(OnTimeout::Initial)).unwrap_err();
+        assert!(matches!(e, Error::Timeout { .. }));
+        assert!(!term.is_raw());
     }
 }
@@ -1,199 +1,82 @@
 //! Select prompt.
 
+use crate::elements::prompt::{self, Check, Frame, Prompt, State, Transition};
+use crate::error::Result;
+use crate::prompts::{OnTimeout, Validator};
+#[cfg(feature = "tokio")]
+use crate::terminal::AsyncTerminal;
+use crate::terminal::Terminal;
+use crate::util::action::{Key, PromptAction};
 use crate::util::figures::Figures;
-use crate::util::style;
-use ansi_escapes::EraseLines;
-use colour::{write_bold, write_cyan, write_gray};
-use std::io::{self, BufRead, Write};
+use colour::{write_cyan, write_gray};
+use std::io::{self, Write};
+use std::time::Duration;
 
 /// Single choice option.
 #[derive(Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(deny_unknown_fields)
+)]
 pub struct Choice {
     pub title: String,
     pub value: String,
+    #[cfg_attr(feature = "serde", serde(default))]
     pub description: Option<String>,
+    #[cfg_attr(feature = "serde", serde(default))]
     pub disabled: bool,
+    /// Starts checked in a multiselect prompt.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub selected: bool,
 }
 
 impl Choice {
-    /// Builds a choice with the given title and value; description and disabled are defaulted.
+    /// Builds a choice with the given title and value; the other fields are defaulted.
     pub fn new(title: impl Into<String>, value: impl Into<String>) -> Self {
         Self {
             title: title.into(),
             value: value.into(),
             description: None,
             disabled: false,
+            selected: false,
         }
     }
 }
 
 /// Select prompt options.
+#[derive(Clone, Default)]
 pub struct SelectPromptOptions {
     pub message: String,
     pub choices: Vec<Choice>,
     pub initial: Option<usize>,
     pub hint: Option<String>,
-}
-
-/// Read a single byte from a BufRead. Blocks until one is available.
-pub(crate) fn read_byte<R: BufRead>(r: &mut R) -> io::Result<u8> {
-    let buf = r.fill_buf()?;
-    if buf.is_empty() {
-        return Err(io::Error::new(
-            io::ErrorKind::UnexpectedEof,
-            "unexpected end of input",
-        ));
-    }
-    let b = buf[0];
-    r.consume(1);
-    Ok(b)
+    /// Rejects an answer with a message; the prompt is then asked again.
+    pub validate: Option<Validator>,
+    /// Rejected answers allowed before giving up; `None` retries forever.
+    pub max_attempts: Option<u32>,
+    /// Time left to answer; a countdown is shown and any key stops it.
+    pub timeout: Option<Duration>,
+    /// What happens when `timeout` runs out.
+    pub on_timeout: OnTimeout,
 }
 
 /// Returns next enabled index when moving down, or same if none.
 pub(crate) fn next_enabled(choices: &[Choice], current: usize) -> usize {
-    for i in (current + 1)..choices.len() {
-        if !choices[i].disabled {
-            return i;
-        }
-    }
-    current
+    choices
+        .iter()
+        .enumerate()
+        .skip(current + 1)
+        .find(|(_, c)| !c.disabled)
+        .map_or(current, |(i, _)| i)
 }
 
 /// Returns previous enabled index when moving up, or same if none.
 pub(crate) fn prev_enabled(choices: &[Choice], current: usize) -> usize {
-    for i in (0..current).rev() {
-        if !choices[i].disabled {
-            return i;
-        }
-    }
-    current
-}
-
-/// Runs select prompt. Returns value of selected choice.
-pub fn run_select<R: BufRead, W: Write>(
-    opts: &SelectPromptOptions,
-    stdin: &mut R,
-    stdout: &mut W,
-) -> io::Result<String> {
-    let fig = Figures::default();
-    let mut buf = Vec::with_capacity(opts.message.len() + 32);
-    write_bold!(&mut buf, "{}", opts.message).ok();
-    let msg = String::from_utf8_lossy(&buf).into_owned();
-    let symbol = style::symbol(false, false, false);
-    let delim = style::delimiter(false);
-    let hint = opts
-        .hint
-        .as_deref()
-        .unwrap_or("Use arrow-keys or type number. Return to submit.");
-    let mut gray_buf = Vec::with_capacity(hint.len() + 16);
-    write_gray!(&mut gray_buf, "{}", hint).ok();
-    let hint_styled = String::from_utf8_lossy(&gray_buf).into_owned();
-
-    let mut selected = opts.initial.unwrap_or(0);
-    if selected >= opts.choices.len() {
-        selected = 0;
-    }
-    while opts
-        .choices
-        .get(selected)
-        .map(|c| c.disabled)
-        .unwrap_or(true)
-    {
-        let next = next_enabled(&opts.choices, selected);
-        if next == selected {
-            break;
-        }
-        selected = next;
-    }
-
-    let n_lines = opts.choices.len() + 3; // message, choices, hint, answer line
-
-    let run_interactive = std::io::IsTerminal::is_terminal(&std::io::stdin());
-
-    if run_interactive {
-        let _guard = crossterm::terminal::enable_raw_mode()
-            .map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
-
-        let result = run_select_interactive(
-            opts,
-            stdin,
-            stdout,
-            &fig,
-            &msg,
-            &symbol,
-            &delim,
-            &hint_styled,
-            &mut selected,
-            n_lines,
-        );
-
-        crossterm::terminal::disable_raw_mode()
-            .map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
-
-        if let Err(e) = result {
-            return Err(e);
-        }
-    } else {
-        writeln!(stdout, "{} {} {}", symbol, msg, delim)?;
-        for (i, c) in opts.choices.iter().enumerate() {
-            let prefix = if c.disabled { " " } else { fig.pointer_small };
-            let mut line_buf = Vec::new();
-            write_cyan!(&mut line_buf, " {} ", (i + 1)).ok();
-            let num = String::from_utf8_lossy(&line_buf).into_owned();
-            let line = format!("  {} {} {}", num, prefix, c.title);
-            writeln!(stdout, "{}", line)?;
-        }
-        writeln!(stdout, "  {}", hint_styled)?;
-        write!(stdout, "  Answer (number or name): ")?;
-        stdout.flush()?;
-        let mut line = String::new();
-        stdin.read_line(&mut line)?;
-        let raw = strip_arrow_escapes(line.trim());
-        selected = parse_selection(opts, &raw)?;
-    }
-
-    let choice = opts
-        .choices
-        .get(selected)
-        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "invalid choice"))?;
-    if choice.disabled {
-        return Err(io::Error::new(
-            io::ErrorKind::InvalidInput,
-            "selected option is disabled",
-        ));
-    }
-    let done_symbol = style::symbol(true, false, false);
-    let done_delim = style::delimiter(true);
-    writeln!(
-        stdout,
-        "\r{} {} {} {}",
-        done_symbol, msg, done_delim, choice.title
-    )?;
-    stdout.flush()?;
-    Ok(choice.value.clone())
-}
-
-/// Strip ANSI arrow key sequences from input so "5^[[B^[[A" becomes "5".
-pub(crate) fn strip_arrow_escapes(s: &str) -> String {
-    let mut out = String::with_capacity(s.len());
-    let mut bytes = s.bytes();
-    while let Some(b) = bytes.next() {
-        if b == 0x1b {
-            if let (Some(0x5b), Some(c)) = (bytes.next(), bytes.next()) {
-                if c == b'A' || c == b'B' || c == b'C' || c == b'D' {
-                    continue;
-                }
-                out.push(0x1b as char);
-                out.push(0x5b as char);
-                out.push(c as char);
-            } else {
-                out.push(0x1b as char);
-            }
-        } else {
-            out.push(b as char);
-        }
-    }
-    out
+    choices[..current.min(choices.len())]
+        .iter()
+        .rposition(|c| !c.disabled)
+        .unwrap_or(current)
 }
 
 /// Parse "number" or "name" into choice index.
@@ -212,99 +95,291 @@ pub(crate) fn parse_selection(opts: &SelectPromptOptions, raw: &str) -> io::Resu
     Ok(idx.or(opts.initial).unwrap_or(0))
 }
 
-fn run_select_interactive<R: BufRead, W: Write>(
-    opts: &SelectPromptOptions,
-    stdin: &mut R,
-    stdout: &mut W,
-    fig: &Figures,
-    msg: &str,
-    symbol: &str,
-    delim: &str,
-    hint_styled: &str,
-    selected: &mut usize,
-    n_lines: usize,
-) -> io::Result<()> {
-    // In raw mode, \n alone does not move to column 0; use \r\n so each line starts at column 0.
-    const NL: &str = "\r\n";
-
-    fn write_choices(
-        opts: &SelectPromptOptions,
-        fig: &Figures,
-        selected: usize,
-        stdout: &mut dyn Write,
-        nl: &str,
-    ) -> io::Result<()> {
-        for (i, c) in opts.choices.iter().enumerate() {
-            let prefix = if c.disabled {
-                " "
-            } else if i == selected {
-                fig.pointer_small
+/// Select prompt state: highlighted choice and any typed number or name.
+pub struct SelectPrompt {
+    opts: SelectPromptOptions,
+    fig: Figures,
+    selected: usize,
+    typed: String,
+}
+
+impl SelectPrompt {
+    pub fn new(opts: SelectPromptOptions) -> Self {
+        let selected = Self::initial_index(&opts);
+        Self {
+            opts,
+            fig: Figures::default(),
+            selected,
+            typed: String::new(),
+        }
+    }
+
+    fn initial_index(opts: &SelectPromptOptions) -> usize {
+        let mut selected = opts.initial.unwrap_or(0);
+        if selected >= opts.choices.len() {
+            selected = 0;
+        }
+        while opts
+            .choices
+            .get(selected)
+            .map(|c| c.disabled)
+            .unwrap_or(true)
+        {
+            let next = next_enabled(&opts.choices, selected);
+            if next == selected {
+                break;
+            }
+            selected = next;
+        }
+        selected
+    }
+
+    fn select(&mut self, index: usize) -> Transition<String> {
+        if index == self.selected {
+            return Transition::Bell;
+        }
+        self.selected = index;
+        Transition::Continue
+    }
+
+    fn submit(&mut self) -> Transition<String> {
+        if !self.typed.is_empty() {
+            // Parsing falls back to initial/0, so this always yields an index.
+            if let Ok(idx) = parse_selection(&self.opts, self.typed.trim()) {
+                self.selected = idx;
+            }
+        }
+        match self.opts.choices.get(self.selected) {
+            None => Transition::Invalid("invalid choice".into()),
+            Some(c) if c.disabled => Transition::Invalid("selected option is disabled".into()),
+            Some(c) => Transition::Submit(c.value.clone()),
+        }
+    }
+}
+
+impl Prompt for SelectPrompt {
+    type Output = String;
+
+    fn render(&self, state: &State) -> Frame {
+        let head = prompt::title(&self.opts.message, state);
+        if *state != State::Active {
+            let title = self
+                .opts
+                .choices
+                .get(self.selected)
+                .map_or("", |c| c.title.as_str());
+            return Frame::line(format!("{} {}", head, title));
+        }
+        let mut lines = Vec::with_capacity(self.opts.choices.len() + 3);
+        lines.push(head);
+        for (i, c) in self.opts.choices.iter().enumerate() {
+            let prefix = if !c.disabled && i == self.selected {
+                self.fig.pointer_small
             } else {
                 " "
             };
             let mut line_buf = Vec::new();
             write_cyan!(&mut line_buf, " {} ", (i + 1)).ok();
             let num = String::from_utf8_lossy(&line_buf).into_owned();
-            write!(stdout, "  {} {} {}{}", num, prefix, c.title, nl)?;
+            let title = if c.disabled {
+                let mut gray_buf = Vec::new();
+                write_gray!(&mut gray_buf, "{}", c.title).ok();
+                String::from_utf8_lossy(&gray_buf).into_owned()
+            } else {
+                c.title.clone()
+            };
+            lines.push(format!("  {} {} {}", num, prefix, title));
         }
-        Ok(())
-    }
-
-    write!(stdout, "{} {} {}{}", symbol, msg, delim, NL)?;
-    write_choices(opts, fig, *selected, stdout, NL)?;
-    write!(stdout, "  {}{}", hint_styled, NL)?;
-    write!(stdout, "  Answer (number or name): ")?;
-    stdout.flush()?;
-
-    let mut typed = String::new();
-
-    loop {
-        let b = read_byte(stdin)?;
-        if b == 0x0d || b == 0x0a {
-            if !typed.is_empty() {
-                let raw = strip_arrow_escapes(typed.trim());
-                if let Ok(idx) = parse_selection(opts, &raw) {
-                    if idx < opts.choices.len() && !opts.choices[idx].disabled {
-                        *selected = idx;
-                    }
-                }
-            }
-            break;
+        let hint = self
+            .opts
+            .hint
+            .as_deref()
+            .unwrap_or("Use arrow-keys or type number. Return to submit.");
+        let mut gray_buf = Vec::with_capacity(hint.len() + 16);
+        write_gray!(&mut gray_buf, "{}", hint).ok();
+        lines.push(format!("  {}", String::from_utf8_lossy(&gray_buf)));
+        let answer = "  Answer (number or name): ";
+        let cursor = (lines.len(), answer.len() + self.typed.chars().count());
+        lines.push(format!("{}{}", answer, self.typed));
+        Frame {
+            lines,
+            cursor: Some(cursor),
         }
-        if b == 0x1b {
-            let b2 = read_byte(stdin).unwrap_or(0);
-            let b3 = read_byte(stdin).unwrap_or(0);
-            if b2 == 0x5b {
-                match b3 {
-                    b'A' => {
-                        *selected = prev_enabled(&opts.choices, *selected);
-                    }
-                    b'B' => {
-                        *selected = next_enabled(&opts.choices, *selected);
-                    }
-                    _ => {}
+    }
+
+    fn handle(&mut self, action: PromptAction) -> Transition<String> {
+        let choices = &self.opts.choices;
+        match action {
+            PromptAction::Submit => self.submit(),
+            PromptAction::Up => self.select(prev_enabled(choices, self.selected)),
+            PromptAction::Down => self.select(next_enabled(choices, self.selected)),
+            PromptAction::Home | PromptAction::First => {
+                let first = choices.iter().position(|c| !c.disabled);
+                self.select(first.unwrap_or(self.selected))
+            }
+            PromptAction::End | PromptAction::Last => {
+                let last = choices.iter().rposition(|c| !c.disabled);
+                self.select(last.unwrap_or(self.selected))
+            }
+            PromptAction::Next => {
+                let next = next_enabled(choices, self.selected);
+                if next == self.selected {
+                    let first = choices.iter().position(|c| !c.disabled);
+                    self.select(first.unwrap_or(self.selected))
+                } else {
+                    self.select(next)
                 }
             }
-            // EraseLines erases the current line then moves up. We are on the "Answer" line,
-            // so erase upward to clear the whole block. Cursor ends at top line, column 0.
-            let up = n_lines as u16;
-            write!(stdout, "{}", EraseLines(up))?;
-            write!(stdout, "{} {} {}{}", symbol, msg, delim, NL)?;
-            write_choices(opts, fig, *selected, stdout, NL)?;
-            write!(stdout, "  {}{}", hint_styled, NL)?;
-            write!(stdout, "  Answer (number or name): ")?;
-            stdout.flush()?;
-        } else if b.is_ascii_graphic() || b == b' ' {
-            typed.push(b as char);
+            PromptAction::Delete if !self.typed.is_empty() => {
+                self.typed.pop();
+                Transition::Continue
+            }
+            PromptAction::Reset => {
+                self.selected = Self::initial_index(&self.opts);
+                self.typed.clear();
+                Transition::Continue
+            }
+            _ => Transition::Bell,
         }
     }
-    Ok(())
+
+    fn input(&mut self, c: char) -> Transition<String> {
+        self.typed.push(c);
+        Transition::Continue
+    }
+
+    fn keypress(&mut self, key: &Key) -> Transition<String> {
+        // j/k navigate only until the user starts typing a name.
+        let is_select = self.typed.is_empty();
+        prompt::dispatch(self, key, is_select)
+    }
+}
+
+/// Runs select prompt. Returns value of selected choice; `None` when cancelled.
+pub fn run_select<T: Terminal + ?Sized>(
+    opts: &SelectPromptOptions,
+    term: &mut T,
+) -> Result<Option<String>> {
+    let check = Check {
+        validate: opts.validate.as_ref(),
+        max_attempts: opts.max_attempts,
+        timeout: opts.timeout,
+        on_timeout: opts.on_timeout,
+        ..Default::default()
+    };
+    prompt::drive(&mut SelectPrompt::new(opts.clone()), term, &check).map(|o| o.ok())
+}
+
+/// `run_select` for async code: waits for keys without blocking the runtime.
+#[cfg(feature = "tokio")]
+pub async fn run_select_async<T: AsyncTerminal + ?Sized>(
+    opts: &SelectPromptOptions,
+    term: &mut T,
+) -> Result<Option<String>> {
+    let check = Check {
+        validate: opts.validate.as_ref(),
+        max_attempts: opts.max_attempts,
+        timeout: opts.timeout,
+        on_timeout: opts.on_timeout,
+        ..Default::default()
+    };
+    prompt::drive_async(&mut SelectPrompt::new(opts.clone()), term, &check)
+        .await
+        .map(|o| o.ok())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::io::Cursor;
+    use crate::terminal::MemoryTerminal;
+    use crate::util::action::KeyName;
+
+    fn abc() -> SelectPromptOptions {
+        SelectPromptOptions {
+            message: "Pick".into(),
+            choices: vec![
+                Choice::new("A", "a"),
+                Choice::new("B", "b"),
+                Choice::new("C", "c"),
+            ],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn select_prompt_arrows_move_selection() {
+        let mut p = SelectPrompt::new(abc());
+        assert_eq!(p.handle(PromptAction::Up), Transition::Bell);
+        assert_eq!(p.handle(PromptAction::Down), Transition::Continue);
+        assert_eq!(
+            p.handle(PromptAction::Submit),
+            Transition::Submit("b".into())
+        );
+    }
+
+    #[test]
+    fn select_prompt_home_end_and_tab_wraps() {
+        let mut p = SelectPrompt::new(abc());
+        p.handle(PromptAction::End);
+        assert_eq!(p.selected, 2);
+        p.handle(PromptAction::Next);
+        assert_eq!(p.selected, 0);
+        p.handle(PromptAction::Last);
+        p.handle(PromptAction::Home);
+        assert_eq!(p.selected, 0);
+    }
+
+    #[test]
+    fn select_prompt_reset_restores_initial() {
+        let mut p = SelectPrompt::new(SelectPromptOptions {
+            initial: Some(1),
+            ..abc()
+        });
+        p.handle(PromptAction::Down);
+        p.input('3');
+        p.handle(PromptAction::Reset);
+        assert_eq!(
+            p.handle(PromptAction::Submit),
+            Transition::Submit("b".into())
+        );
+    }
+
+    #[test]
+    fn select_prompt_j_k_navigate_until_typing() {
+        let mut p = SelectPrompt::new(abc());
+        let j = Key {
+            name: KeyName::Char('j'),
+            ctrl: false,
+            meta: false,
+        };
+        p.keypress(&j);
+        assert_eq!(p.selected, 1);
+        p.input('x');
+        p.keypress(&j);
+        assert_eq!(p.selected, 1);
+        assert_eq!(p.typed, "xj");
+    }
+
+    #[test]
+    fn select_prompt_disabled_choice_is_invalid() {
+        let mut opts = abc();
+        opts.choices[1].disabled = true;
+        let mut p = SelectPrompt::new(opts);
+        p.input('2');
+        assert!(matches!(
+            p.handle(PromptAction::Submit),
+            Transition::Invalid(_)
+        ));
+    }
+
+    #[test]
+    fn select_prompt_render_lists_choices_and_answer_line() {
+        let p = SelectPrompt::new(abc());
+        let frame = p.render(&State::Active);
+        assert_eq!(frame.lines.len(), 6);
+        assert_eq!(frame.cursor.map(|(line, _)| line), Some(5));
+        assert_eq!(p.render(&State::Done).lines.len(), 1);
+    }
 
     #[test]
     fn choice_new() {
@@ -313,6 +388,7 @@ mod tests {
         assert_eq!(c.value, "value");
         assert!(c.description.is_none());
         assert!(!c.disabled);
+        assert!(!c.selected);
     }
 
     #[test]
@@ -326,12 +402,12 @@ mod tests {
             ],
             initial: None,
             hint: None,
+            ..Default::default()
         };
-        let mut stdin = Cursor::new(b"2\n");
-        let mut stdout = Vec::new();
-        let r = run_select(&opts, &mut stdin, &mut stdout);
+        let mut term = MemoryTerminal::new("2\n");
+        let r = run_select(&opts, &mut term);
         assert!(r.is_ok());
-        assert_eq!(r.unwrap(), "2");
+        assert_eq!(r.unwrap().unwrap(), "2");
     }
 
     #[test]
@@ -344,12 +420,12 @@ mod tests {
             ],
             initial: None,
             hint: None,
+            ..Default::default()
         };
-        let mut stdin = Cursor::new(b"BANANA\n");
-        let mut stdout = Vec::new();
-        let r = run_select(&opts, &mut stdin, &mut stdout);
+        let mut term = MemoryTerminal::new("BANANA\n");
+        let r = run_select(&opts, &mut term);
         assert!(r.is_ok());
-        assert_eq!(r.unwrap(), "banana");
+        assert_eq!(r.unwrap().unwrap(), "banana");
     }
 
     #[test]
@@ -359,12 +435,12 @@ mod tests {
             choices: vec![Choice::new("A", "a"), Choice::new("B", "b")],
             initial: Some(1),
             hint: None,
+            ..Default::default()
         };
-        let mut stdin = Cursor::new(b"xyz\n");
-        let mut stdout = Vec::new();
-        let r = run_select(&opts, &mut stdin, &mut stdout);
+        let mut term = MemoryTerminal::new("xyz\n");
+        let r = run_select(&opts, &mut term);
         assert!(r.is_ok());
-        assert_eq!(r.unwrap(), "b");
+        assert_eq!(r.unwrap().unwrap(), "b");
     }
 
     #[test]
@@ -376,10 +452,10 @@ mod tests {
             choices: vec![Choice::new("A", "a"), c],
             initial: Some(1),
             hint: None,
+            ..Default::default()
         };
-        let mut stdin = Cursor::new(b"2\n");
-        let mut stdout = Vec::new();
-        let r = run_select(&opts, &mut stdin, &mut stdout);
+        let mut term = MemoryTerminal::new("2\n");
+        let r = run_select(&opts, &mut term);
         assert!(r.is_err());
     }
 
@@ -390,12 +466,12 @@ mod tests {
             choices: vec![Choice::new("One", "val1"), Choice::new("Two", "val2")],
             initial: None,
             hint: None,
+            ..Default::default()
         };
-        let mut stdin = Cursor::new(b"val2\n");
-        let mut stdout = Vec::new();
-        let r = run_select(&opts, &mut stdin, &mut stdout);
+        let mut term = MemoryTerminal::new("val2\n");
+        let r = run_select(&opts, &mut term);
         assert!(r.is_ok());
-        assert_eq!(r.unwrap(), "val2");
+        assert_eq!(r.unwrap().unwrap(), "val2");
     }
 
     #[test]
@@ -405,12 +481,12 @@ mod tests {
             choices: vec![Choice::new("A", "a")],
             initial: None,
             hint: Some("Custom hint".into()),
+            ..Default::default()
         };
-        let mut stdin = Cursor::new(b"1\n");
-        let mut stdout = Vec::new();
-        let r = run_select(&opts, &mut stdin, &mut stdout);
+        let mut term = MemoryTerminal::new("1\n");
+        let r = run_select(&opts, &mut term);
         assert!(r.is_ok());
-        let out = String::from_utf8(stdout).unwrap();
+        let out = term.output();
         assert!(out.contains("Custom hint"));
     }
 
@@ -421,25 +497,12 @@ mod tests {
             choices: vec![Choice::new("One", "1"), Choice::new("Two", "2")],
             initial: None,
             hint: None,
+            ..Default::default()
         };
-        let mut stdin = Cursor::new(b"2\x1b[B\x1b[A\n");
-        let mut stdout = Vec::new();
-        let r = run_select(&opts, &mut stdin, &mut stdout);
+        let mut term = MemoryTerminal::new("2\x1b[B\x1b[A\n");
+        let r = run_select(&opts, &mut term);
         assert!(r.is_ok());
-        assert_eq!(r.unwrap(), "2");
-    }
-
-    #[test]
-    fn strip_arrow_escapes_removes_ansi_sequences() {
-        assert_eq!(strip_arrow_escapes("5"), "5");
-        assert_eq!(strip_arrow_escapes("5\x1b[B\x1b[A"), "5");
-        assert_eq!(strip_arrow_escapes("1\x1b[C\x1b[D"), "1");
-    }
-
-    #[test]
-    fn strip_arrow_escapes_keeps_incomplete_escape() {
-        let s = "a\x1b";
-        assert_eq!(strip_arrow_escapes(s), "a\x1b");
+        assert_eq!(r.unwrap().unwrap(), "2");
     }
 
     #[test]
@@ -449,6 +512,7 @@ mod tests {
             choices: vec![Choice::new("A", "a"), Choice::new("B", "b")],
             initial: None,
             hint: None,
+            ..Default::default()
         };
         assert_eq!(parse_selection(&opts, "1").unwrap(), 0);
         assert_eq!(parse_selection(&opts, "2").unwrap(), 1);
@@ -461,6 +525,7 @@ mod tests {
             choices: vec![Choice::new("Apple", "a"), Choice::new("Banana", "b")],
             initial: None,
             hint: None,
+            ..Default::default()
         };
         assert_eq!(parse_selection(&opts, "BANANA").unwrap(), 1);
     }
@@ -472,6 +537,7 @@ mod tests {
             choices: vec![Choice::new("One", "v1"), Choice::new("Two", "v2")],
             initial: None,
             hint: None,
+            ..Default::default()
         };
         assert_eq!(parse_selection(&opts, "v2").unwrap(), 1);
     }
@@ -483,6 +549,7 @@ mod tests {
             choices: vec![Choice::new("A", "a"), Choice::new("B", "b")],
             initial: Some(1),
             hint: None,
+            ..Default::default()
         };
         assert_eq!(parse_selection(&opts, "xyz").unwrap(), 1);
     }
@@ -494,6 +561,7 @@ mod tests {
             choices: vec![Choice::new("A", "a")],
             initial: Some(0),
             hint: None,
+            ..Default::default()
         };
         assert_eq!(parse_selection(&opts, "99").unwrap(), 0);
     }
@@ -520,19 +588,6 @@ mod tests {
         assert_eq!(prev_enabled(&choices, 0), 0);
     }
 
-    #[test]
-    fn read_byte_returns_first_byte() {
-        let mut r = Cursor::new(b"ab");
-        assert_eq!(read_byte(&mut r).unwrap(), b'a');
-        assert_eq!(read_byte(&mut r).unwrap(), b'b');
-    }
-
-    #[test]
-    fn read_byte_eof_returns_err() {
-        let mut r = Cursor::new(b"");
-        assert!(read_byte(&mut r).is_err());
-    }
-
     #[test]
     fn run_select_empty_choices_returns_err() {
         let opts = SelectPromptOptions {
@@ -540,10 +595,10 @@ mod tests {
             choices: vec![],
             initial: None,
             hint: None,
+            ..Default::default()
         };
-        let mut stdin = Cursor::new(b"1\n");
-        let mut stdout = Vec::new();
-        let r = run_select(&opts, &mut stdin, &mut stdout);
+        let mut term = MemoryTerminal::new("1\n");
+        let r = run_select(&opts, &mut term);
         assert!(r.is_err());
     }
 
@@ -554,12 +609,12 @@ mod tests {
             choices: vec![Choice::new("Only", "only")],
             initial: None,
             hint: None,
+            ..Default::default()
         };
-        let mut stdin = Cursor::new(b"1\n");
-        let mut stdout = Vec::new();
-        let r = run_select(&opts, &mut stdin, &mut stdout);
+        let mut term = MemoryTerminal::new("1\n");
+        let r = run_select(&opts, &mut term);
         assert!(r.is_ok());
-        assert_eq!(r.unwrap(), "only");
+        assert_eq!(r.unwrap().unwrap(), "only");
     }
 
     #[test]
@@ -569,11 +624,11 @@ mod tests {
             choices: vec![Choice::new("A", "a"), Choice::new("B", "b")],
             initial: Some(99),
             hint: None,
+            ..Default::default()
         };
-        let mut stdin = Cursor::new(b"1\n");
-        let mut stdout = Vec::new();
-        let r = run_select(&opts, &mut stdin, &mut stdout);
+        let mut term = MemoryTerminal::new("1\n");
+        let r = run_select(&opts, &mut term);
         assert!(r.is_ok());
-        assert_eq!(r.unwrap(), "a");
+        assert_eq!(r.unwrap().unwrap(), "a");
     }
 }
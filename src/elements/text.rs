@@ -1,15 +1,43 @@
 //! Text prompt.
 
+use crate::elements::prompt::{self, Check, Frame, Prompt, State, Transition};
+use crate::error::Result;
+use crate::prompts::{OnTimeout, Validator};
+#[cfg(feature = "tokio")]
+use crate::terminal::AsyncTerminal;
+use crate::terminal::Terminal;
+use crate::util::action::{Key, KeyName, PromptAction};
+use crate::util::line_editor::LineEditor;
+use crate::util::lines::display_width;
 use crate::util::style::{self, InputStyle};
-use colour::write_bold;
-use std::io::{self, BufRead, Write};
+use std::sync::Arc;
+use std::time::Duration;
 
 /// Text prompt options.
+#[derive(Clone)]
 pub struct TextPromptOptions {
     pub message: String,
     pub initial: Option<String>,
+    /// Starts the input with `initial`, ready to edit; Ctrl-G restores it.
+    pub prefill: bool,
+    /// Earlier answers, oldest first, that Up and Down recall.
+    pub history: Vec<String>,
+    /// Accepts several lines: Enter starts a new line and Ctrl-D submits. Up and Down move
+    /// between lines instead of recalling `history`.
+    pub multiline: bool,
+    /// Another key that submits multiline input, besides Ctrl-D.
+    pub submit_key: Option<Key>,
     pub style: InputStyle,
+    /// Message shown when `validate` rejects an answer with an empty message.
     pub error_msg: Option<String>,
+    /// Rejects an answer with a message; the prompt is then asked again.
+    pub validate: Option<Validator>,
+    /// Rejected answers allowed before giving up; `None` retries forever.
+    pub max_attempts: Option<u32>,
+    /// Time left to answer; a countdown is shown and any key stops it.
+    pub timeout: Option<Duration>,
+    /// What happens when `timeout` runs out.
+    pub on_timeout: OnTimeout,
 }
 
 impl Default for TextPromptOptions {
@@ -17,55 +45,290 @@ impl Default for TextPromptOptions {
         Self {
             message: String::new(),
             initial: None,
+            prefill: false,
+            history: Vec::new(),
+            multiline: false,
+            submit_key: None,
             style: InputStyle::Default,
             error_msg: Some("Please Enter A Valid Value".into()),
+            validate: None,
+            max_attempts: None,
+            timeout: None,
+            on_timeout: OnTimeout::default(),
         }
     }
 }
 
-/// Runs text prompt. Returns input or initial when empty.
-pub fn run_text<R: BufRead, W: Write>(
+/// Text prompt state: the line being edited.
+pub struct TextPrompt {
+    opts: TextPromptOptions,
+    input: LineEditor,
+    /// History entry shown and the line typed before recalling it.
+    recalled: Option<(usize, String)>,
+}
+
+impl TextPrompt {
+    pub fn new(opts: TextPromptOptions) -> Self {
+        let mut prompt = Self {
+            opts,
+            input: LineEditor::new(),
+            recalled: None,
+        };
+        prompt.reset();
+        prompt
+    }
+
+    /// Empties the input, or refills it with the initial value when `prefill` is set.
+    fn reset(&mut self) {
+        self.recalled = None;
+        match &self.opts.initial {
+            Some(initial) if self.opts.prefill => self.input.set(initial),
+            _ => self.input.set(""),
+        }
+    }
+
+    /// Shows the previous (`up`) or next history entry; going past the newest brings back the
+    /// line typed before.
+    fn recall(&mut self, up: bool) -> Transition<String> {
+        let len = self.opts.history.len();
+        let current = self.recalled.as_ref().map_or(len, |(i, _)| *i);
+        let next = match up {
+            true => current.checked_sub(1),
+            false => (current < len).then_some(current + 1),
+        };
+        let Some(next) = next else {
+            return Transition::Bell;
+        };
+        let draft = match self.recalled.take() {
+            Some((_, draft)) => draft,
+            None => self.input.text(),
+        };
+        if next == len {
+            self.input.set(&draft);
+        } else {
+            self.input.set(&self.opts.history[next]);
+            self.recalled = Some((next, draft));
+        }
+        Transition::Continue
+    }
+
+    /// Returns the answer: trimmed input, or initial when empty. Multiline input keeps the
+    /// indentation of its first line.
+    pub fn value(&self) -> String {
+        let typed = self.input.text();
+        let typed = match self.opts.multiline {
+            true => typed.trim_end().trim_start_matches(['\r', '\n']),
+            false => typed.trim(),
+        };
+        if typed.is_empty() {
+            self.opts.initial.clone().unwrap_or_default()
+        } else {
+            typed.to_string()
+        }
+    }
+
+    /// Renders multiline input under the message, with a hint on how to submit.
+    fn render_lines(&self, head: String, state: &State) -> Frame {
+        let (transform, _scale) = style::render_style(self.opts.style);
+        if *state != State::Active {
+            let value = self.value();
+            let first = value.lines().next().unwrap_or_default();
+            let first = transform.render(first, self.opts.style);
+            return Frame::line(format!("{} {}{}", head, first, more_lines(&value)));
+        }
+        let submit = match &self.opts.submit_key {
+            Some(key) => format!("Ctrl-D or {}", key),
+            None => "Ctrl-D".into(),
+        };
+        let mut lines = vec![format!(
+            "{} {}",
+            head,
+            prompt::gray(&format!("({} to submit)", submit))
+        )];
+        let typed = self.input.text();
+        lines.extend(
+            typed
+                .split('\n')
+                .map(|line| transform.render(line, self.opts.style)),
+        );
+        let before = self.input.before_cursor();
+        let (row, line) = before
+            .rsplit_once('\n')
+            .map_or((0, before.as_str()), |(above, line)| {
+                (above.matches('\n').count() + 1, line)
+            });
+        let col = display_width(&transform.render(line, self.opts.style));
+        Frame {
+            lines,
+            cursor: Some((row + 1, col)),
+        }
+    }
+}
+
+impl Prompt for TextPrompt {
+    type Output = String;
+
+    fn render(&self, state: &State) -> Frame {
+        let (transform, _scale) = style::render_style(self.opts.style);
+        let head = prompt::title(&self.opts.message, state);
+        if self.opts.multiline {
+            return self.render_lines(head, state);
+        }
+        if *state != State::Active {
+            let rendered = transform.render(&self.value(), self.opts.style);
+            return Frame::line(format!("{} {}", head, rendered));
+        }
+        // Without `prefill` the initial value is not shown; it is used when the input is empty.
+        let typed = self.input.text();
+        let before = self.input.before_cursor();
+        let col =
+            display_width(&head) + 1 + display_width(&transform.render(&before, self.opts.style));
+        Frame {
+            lines: vec![format!(
+                "{} {}",
+                head,
+                transform.render(&typed, self.opts.style)
+            )],
+            cursor: Some((0, col)),
+        }
+    }
+
+    fn handle(&mut self, action: PromptAction) -> Transition<String> {
+        if let Some(changed) = self.input.apply(&action) {
+            return if changed {
+                Transition::Continue
+            } else {
+                Transition::Bell
+            };
+        }
+        match action {
+            PromptAction::Submit => Transition::Submit(self.value()),
+            PromptAction::Reset => {
+                self.reset();
+                Transition::Continue
+            }
+            PromptAction::Up | PromptAction::Down if self.opts.multiline => {
+                match self.input.move_line(action == PromptAction::Up) {
+                    true => Transition::Continue,
+                    false => Transition::Bell,
+                }
+            }
+            PromptAction::Up => self.recall(true),
+            PromptAction::Down => self.recall(false),
+            PromptAction::Next => match &self.opts.initial {
+                Some(initial) if self.input.is_empty() && !initial.is_empty() => {
+                    self.input.set(initial);
+                    Transition::Continue
+                }
+                _ => Transition::Bell,
+            },
+            _ => Transition::Bell,
+        }
+    }
+
+    fn input(&mut self, c: char) -> Transition<String> {
+        self.input.insert(c);
+        Transition::Continue
+    }
+
+    /// In multiline mode Enter starts a new line, and Ctrl-D or `submit_key` submits.
+    fn keypress(&mut self, key: &Key) -> Transition<String> {
+        if self.opts.multiline {
+            let ctrl_d = key.ctrl && key.name == KeyName::Char('d');
+            if ctrl_d || self.opts.submit_key.as_ref() == Some(key) {
+                return self.handle(PromptAction::Submit);
+            }
+            if matches!(key.name, KeyName::Return | KeyName::Enter) && !key.ctrl && !key.meta {
+                return self.input('\n');
+            }
+        }
+        prompt::dispatch(self, key, false)
+    }
+
+    /// Multiline input ends like Ctrl-D when typed without a terminal; otherwise input ending
+    /// cancels.
+    fn input_end(&mut self) -> Transition<String> {
+        match self.opts.multiline {
+            true => self.handle(PromptAction::Submit),
+            false => Transition::Exit,
+        }
+    }
+
+    /// A pre-filled value cannot be edited on a line, so the input starts empty.
+    fn line_mode(&mut self) {
+        self.opts.prefill = false;
+        self.reset();
+    }
+}
+
+/// Runs text prompt. Returns input or initial when empty; `None` when cancelled.
+pub fn run_text<T: Terminal + ?Sized>(
+    opts: &TextPromptOptions,
+    term: &mut T,
+) -> Result<Option<String>> {
+    let validate = validator(opts);
+    let check = Check {
+        validate: validate.as_ref(),
+        max_attempts: opts.max_attempts,
+        timeout: opts.timeout,
+        on_timeout: opts.on_timeout,
+        ..Default::default()
+    };
+    prompt::drive(&mut TextPrompt::new(opts.clone()), term, &check).map(|o| o.ok())
+}
+
+/// `run_text` for async code: waits for keys without blocking the runtime.
+#[cfg(feature = "tokio")]
+pub async fn run_text_async<T: AsyncTerminal + ?Sized>(
     opts: &TextPromptOptions,
-    stdin: &mut R,
-    stdout: &mut W,
-) -> io::Result<String> {
-    let (transform, _scale) = style::render_style(opts.style);
-    let initial = opts.initial.as_deref().unwrap_or("");
-    let mut output = Vec::with_capacity(opts.message.len() + 32);
-    write_bold!(&mut output, "{}", opts.message).ok();
-    let msg_styled = String::from_utf8_lossy(&output).into_owned();
-    let symbol = style::symbol(false, false, false);
-    let delim = style::delimiter(false);
-    // Do not pre-display initial value: it is not editable, so users could not change it.
-    // Use initial only when the user submits with empty input.
-    let prompt_line = format!("{} {} {} ", symbol, msg_styled, delim);
-    write!(stdout, "{}", prompt_line)?;
-    stdout.flush()?;
-    let mut line = String::new();
-    stdin.read_line(&mut line)?;
-    let value = line.trim().to_string();
-    let value = if value.is_empty() {
-        initial.to_string()
-    } else {
-        value
+    term: &mut T,
+) -> Result<Option<String>> {
+    let validate = validator(opts);
+    let check = Check {
+        validate: validate.as_ref(),
+        max_attempts: opts.max_attempts,
+        timeout: opts.timeout,
+        on_timeout: opts.on_timeout,
+        ..Default::default()
     };
-    let rendered = transform.render(&value, opts.style);
-    let done_symbol = style::symbol(true, false, false);
-    let done_delim = style::delimiter(true);
-    writeln!(
-        stdout,
-        "\r{} {} {} {}",
-        done_symbol, msg_styled, done_delim, rendered
-    )?;
-    stdout.flush()?;
-    Ok(value)
+    prompt::drive_async(&mut TextPrompt::new(opts.clone()), term, &check)
+        .await
+        .map(|o| o.ok())
+}
+
+/// Returns the gray ` (+N lines)` shown after the first line of a multiline answer.
+pub(crate) fn more_lines(text: &str) -> String {
+    match text.lines().count().saturating_sub(1) {
+        0 => String::new(),
+        n => prompt::gray(&format!(" (+{} lines)", n)),
+    }
+}
+
+/// `opts.validate`, showing `opts.error_msg` for an empty message.
+fn validator(opts: &TextPromptOptions) -> Option<Validator> {
+    opts.validate.clone().map(|f| -> Validator {
+        let fallback = opts.error_msg.clone();
+        Arc::new(move |value, answers| {
+            f(value, answers).map_err(|msg| match &fallback {
+                Some(fallback) if msg.is_empty() => fallback.clone(),
+                _ => msg,
+            })
+        })
+    })
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::prompts::PromptValue;
+    use crate::terminal::MemoryTerminal;
     use crate::util::style::InputStyle;
-    use std::io::Cursor;
+
+    fn typed(p: &mut TextPrompt, s: &str) {
+        for c in s.chars() {
+            p.input(c);
+        }
+    }
 
     #[test]
     fn text_prompt_options_default() {
@@ -76,6 +339,131 @@ mod tests {
         assert!(opts.error_msg.is_some());
     }
 
+    #[test]
+    fn text_prompt_cursor_editing() {
+        let mut p = TextPrompt::new(TextPromptOptions::default());
+        typed(&mut p, "ac");
+        assert_eq!(p.handle(PromptAction::Left), Transition::Continue);
+        p.input('b');
+        assert_eq!(p.value(), "abc");
+        p.handle(PromptAction::Home);
+        assert_eq!(p.handle(PromptAction::Left), Transition::Bell);
+        p.handle(PromptAction::DeleteForward);
+        assert_eq!(p.value(), "bc");
+        p.handle(PromptAction::End);
+        p.handle(PromptAction::Delete);
+        assert_eq!(p.value(), "b");
+    }
+
+    #[test]
+    fn text_prompt_reset_clears_input() {
+        let mut p = TextPrompt::new(TextPromptOptions {
+            initial: Some("init".into()),
+            ..Default::default()
+        });
+        typed(&mut p, "abc");
+        p.handle(PromptAction::Reset);
+        assert_eq!(p.value(), "init");
+    }
+
+    #[test]
+    fn text_prompt_tab_fills_initial() {
+        let mut p = TextPrompt::new(TextPromptOptions {
+            initial: Some("init".into()),
+            ..Default::default()
+        });
+        assert_eq!(p.handle(PromptAction::Next), Transition::Continue);
+        p.input('!');
+        assert_eq!(p.value(), "init!");
+        assert_eq!(p.handle(PromptAction::Next), Transition::Bell);
+    }
+
+    #[test]
+    fn text_prompt_prefill_is_editable_and_reset_restores_it() {
+        let mut p = TextPrompt::new(TextPromptOptions {
+            message: "Version?".into(),
+            initial: Some("1.2.3".into()),
+            prefill: true,
+            ..Default::default()
+        });
+        let active = p.render(&State::Active);
+        assert!(active.lines[0].ends_with(" 1.2.3"));
+        p.handle(PromptAction::Delete);
+        p.input('4');
+        assert_eq!(p.value(), "1.2.4");
+        assert_eq!(p.handle(PromptAction::Reset), Transition::Continue);
+        assert_eq!(p.input.text(), "1.2.3");
+    }
+
+    #[test]
+    fn text_prompt_up_down_recall_history() {
+        let mut p = TextPrompt::new(TextPromptOptions {
+            history: vec!["main".into(), "dev".into()],
+            ..Default::default()
+        });
+        typed(&mut p, "fe");
+        assert_eq!(p.handle(PromptAction::Up), Transition::Continue);
+        assert_eq!(p.input.text(), "dev");
+        p.handle(PromptAction::Up);
+        assert_eq!(p.input.text(), "main");
+        assert_eq!(p.handle(PromptAction::Up), Transition::Bell);
+        p.handle(PromptAction::Down);
+        assert_eq!(p.input.text(), "dev");
+        p.handle(PromptAction::Down);
+        assert_eq!(p.input.text(), "fe");
+        assert_eq!(p.handle(PromptAction::Down), Transition::Bell);
+        p.handle(PromptAction::Up);
+        p.handle(PromptAction::Reset);
+        assert!(p.input.is_empty());
+        assert_eq!(p.handle(PromptAction::Up), Transition::Continue);
+        assert_eq!(p.input.text(), "dev");
+    }
+
+    #[test]
+    fn text_prompt_without_history_rings_on_up() {
+        let mut p = TextPrompt::new(TextPromptOptions::default());
+        assert_eq!(p.handle(PromptAction::Up), Transition::Bell);
+        assert_eq!(p.handle(PromptAction::Down), Transition::Bell);
+    }
+
+    #[test]
+    fn text_prompt_render_cursor_follows_input() {
+        let mut p = TextPrompt::new(TextPromptOptions {
+            message: "Q".into(),
+            ..Default::default()
+        });
+        let (_, start) = p.render(&State::Active).cursor.unwrap();
+        typed(&mut p, "abc");
+        p.handle(PromptAction::Left);
+        let (_, col) = p.render(&State::Active).cursor.unwrap();
+        assert_eq!(col, start + 2);
+    }
+
+    #[test]
+    fn text_prompt_cursor_counts_columns() {
+        let mut p = TextPrompt::new(TextPromptOptions {
+            message: "Q".into(),
+            ..Default::default()
+        });
+        let (_, start) = p.render(&State::Active).cursor.unwrap();
+        typed(&mut p, "日e\u{301}");
+        let (_, col) = p.render(&State::Active).cursor.unwrap();
+        assert_eq!(col, start + 3);
+        p.handle(PromptAction::Left);
+        let (_, col) = p.render(&State::Active).cursor.unwrap();
+        assert_eq!(col, start + 2);
+    }
+
+    #[test]
+    fn text_prompt_submit() {
+        let mut p = TextPrompt::new(TextPromptOptions::default());
+        typed(&mut p, " x ");
+        assert_eq!(
+            p.handle(PromptAction::Submit),
+            Transition::Submit("x".into())
+        );
+    }
+
     #[test]
     fn run_text_returns_entered_value() {
         let opts = TextPromptOptions {
@@ -83,12 +471,12 @@ mod tests {
             initial: None,
             style: InputStyle::Default,
             error_msg: None,
+            ..Default::default()
         };
-        let mut stdin = Cursor::new(b"Bob\n");
-        let mut stdout = Vec::new();
-        let r = run_text(&opts, &mut stdin, &mut stdout);
+        let mut term = MemoryTerminal::new("Bob\n");
+        let r = run_text(&opts, &mut term);
         assert!(r.is_ok());
-        assert_eq!(r.unwrap(), "Bob");
+        assert_eq!(r.unwrap().unwrap(), "Bob");
     }
 
     #[test]
@@ -98,12 +486,12 @@ mod tests {
             initial: Some("default".into()),
             style: InputStyle::Default,
             error_msg: None,
+            ..Default::default()
         };
-        let mut stdin = Cursor::new(b"\n");
-        let mut stdout = Vec::new();
-        let r = run_text(&opts, &mut stdin, &mut stdout);
+        let mut term = MemoryTerminal::new("\n");
+        let r = run_text(&opts, &mut term);
         assert!(r.is_ok());
-        assert_eq!(r.unwrap(), "default");
+        assert_eq!(r.unwrap().unwrap(), "default");
     }
 
     #[test]
@@ -113,12 +501,23 @@ mod tests {
             initial: None,
             style: InputStyle::Default,
             error_msg: None,
+            ..Default::default()
         };
-        let mut stdin = Cursor::new(b"  spaced  \n");
-        let mut stdout = Vec::new();
-        let r = run_text(&opts, &mut stdin, &mut stdout);
+        let mut term = MemoryTerminal::new("  spaced  \n");
+        let r = run_text(&opts, &mut term);
         assert!(r.is_ok());
-        assert_eq!(r.unwrap(), "spaced");
+        assert_eq!(r.unwrap().unwrap(), "spaced");
+    }
+
+    #[test]
+    fn run_text_reads_crlf_lines_one_answer_each() {
+        let opts = TextPromptOptions {
+            message: "Name?".into(),
+            ..Default::default()
+        };
+        let mut term = MemoryTerminal::new("alice\r\nbob\r\n");
+        assert_eq!(run_text(&opts, &mut term).unwrap().unwrap(), "alice");
+        assert_eq!(run_text(&opts, &mut term).unwrap().unwrap(), "bob");
     }
 
     #[test]
@@ -128,16 +527,135 @@ mod tests {
             initial: None,
             style: InputStyle::Password,
             error_msg: None,
+            ..Default::default()
         };
-        let mut stdin = Cursor::new(b"hello\n");
-        let mut stdout = Vec::new();
-        let r = run_text(&opts, &mut stdin, &mut stdout);
+        let mut term = MemoryTerminal::new("hello\n");
+        let r = run_text(&opts, &mut term);
         assert!(r.is_ok());
-        assert_eq!(r.unwrap(), "hello");
-        let out = String::from_utf8(stdout).unwrap();
+        assert_eq!(r.unwrap().unwrap(), "hello");
+        let out = term.output();
         assert!(out.contains("*****"));
     }
 
+    #[test]
+    fn run_text_ctrl_keys_delete_words_and_line_parts() {
+        let opts = TextPromptOptions {
+            message: "Cmd?".into(),
+            ..Default::default()
+        };
+        let mut term = MemoryTerminal::new("git commit\x17push\r").tty(true);
+        assert_eq!(run_text(&opts, &mut term).unwrap().unwrap(), "git push");
+        let left = "\x1b[D".repeat(5);
+        let input = format!("hello world{}\x0b\x15bye\r", left);
+        let mut term = MemoryTerminal::new(input).tty(true);
+        assert_eq!(run_text(&opts, &mut term).unwrap().unwrap(), "bye");
+    }
+
+    #[test]
+    fn run_text_prefill_edits_and_ctrl_g_restores() {
+        let opts = TextPromptOptions {
+            message: "Version?".into(),
+            initial: Some("1.2.3".into()),
+            prefill: true,
+            ..Default::default()
+        };
+        let mut term = MemoryTerminal::new("\x7f4\r").tty(true);
+        assert_eq!(run_text(&opts, &mut term).unwrap().unwrap(), "1.2.4");
+        let mut term = MemoryTerminal::new("\x7f4\x07\r").tty(true);
+        assert_eq!(run_text(&opts, &mut term).unwrap().unwrap(), "1.2.3");
+    }
+
+    #[test]
+    fn run_text_prefill_is_dropped_in_line_mode() {
+        let opts = TextPromptOptions {
+            message: "Version?".into(),
+            initial: Some("1.2.3".into()),
+            prefill: true,
+            ..Default::default()
+        };
+        let mut term = MemoryTerminal::new("2.0\n");
+        assert_eq!(run_text(&opts, &mut term).unwrap().unwrap(), "2.0");
+        let mut term = MemoryTerminal::new("\n");
+        assert_eq!(run_text(&opts, &mut term).unwrap().unwrap(), "1.2.3");
+    }
+
+    #[test]
+    fn text_prompt_multiline_renders_lines_under_message() {
+        let mut p = TextPrompt::new(TextPromptOptions {
+            message: "Body?".into(),
+            multiline: true,
+            ..Default::default()
+        });
+        typed(&mut p, "ab\nc");
+        let frame = p.render(&State::Active);
+        assert!(frame.lines[0].contains("(Ctrl-D to submit)"));
+        assert_eq!(frame.lines[1..], ["ab", "c"]);
+        assert_eq!(frame.cursor, Some((2, 1)));
+        let done = p.render(&State::Done);
+        assert!(done.lines[0].contains(" ab"));
+        assert!(done.lines[0].contains("(+1 lines)"));
+    }
+
+    #[test]
+    fn run_text_multiline_enter_adds_lines_and_ctrl_d_submits() {
+        let opts = TextPromptOptions {
+            message: "Body?".into(),
+            multiline: true,
+            ..Default::default()
+        };
+        let mut term = MemoryTerminal::new("\rab\rcd\x1b[Ax\r\r\x04").tty(true);
+        assert_eq!(run_text(&opts, &mut term).unwrap().unwrap(), "abx\n\n\ncd");
+        assert!(!term.is_raw());
+    }
+
+    #[test]
+    fn run_text_multiline_submit_key() {
+        let opts = TextPromptOptions {
+            message: "Body?".into(),
+            multiline: true,
+            submit_key: Some(Key {
+                name: KeyName::Char('s'),
+                ctrl: true,
+                meta: false,
+            }),
+            ..Default::default()
+        };
+        let mut term = MemoryTerminal::new("a\rb\x13").tty(true);
+        assert_eq!(run_text(&opts, &mut term).unwrap().unwrap(), "a\nb");
+        assert!(term.output().contains("(Ctrl-D or Ctrl-S to submit)"));
+    }
+
+    #[test]
+    fn run_text_multiline_reads_to_end_of_input_without_terminal() {
+        let opts = TextPromptOptions {
+            message: "Body?".into(),
+            multiline: true,
+            ..Default::default()
+        };
+        let mut term = MemoryTerminal::new("first\n  second\n");
+        assert_eq!(
+            run_text(&opts, &mut term).unwrap().unwrap(),
+            "first\n  second"
+        );
+    }
+
+    #[test]
+    fn run_text_password_redraws_one_star_per_grapheme() {
+        let opts = TextPromptOptions {
+            message: "Secret?".into(),
+            style: InputStyle::Password,
+            ..Default::default()
+        };
+        let mut term = MemoryTerminal::new("pässwörd\r").tty(true);
+        assert_eq!(run_text(&opts, &mut term).unwrap().unwrap(), "pässwörd");
+        let out = term.output();
+        // Redrawn after every key, with the cursor right after the last star.
+        assert!(out.contains(" ***\x1b[16G"));
+        assert!(out.contains("********"));
+        assert!(!out.contains("*********"));
+        assert!(!out.contains("pä"));
+    }
+
     #[test]
     fn run_text_empty_input_no_initial_returns_empty_string() {
         let opts = TextPromptOptions {
@@ -145,12 +663,12 @@ mod tests {
             initial: None,
             style: InputStyle::Default,
             error_msg: None,
+            ..Default::default()
         };
-        let mut stdin = Cursor::new(b"\n");
-        let mut stdout = Vec::new();
-        let r = run_text(&opts, &mut stdin, &mut stdout);
+        let mut term = MemoryTerminal::new("\n");
+        let r = run_text(&opts, &mut term);
         assert!(r.is_ok());
-        assert_eq!(r.unwrap(), "");
+        assert_eq!(r.unwrap().unwrap(), "");
     }
 
     #[test]
@@ -160,13 +678,13 @@ mod tests {
             initial: None,
             style: InputStyle::Invisible,
             error_msg: None,
+            ..Default::default()
         };
-        let mut stdin = Cursor::new(b"secret\n");
-        let mut stdout = Vec::new();
-        let r = run_text(&opts, &mut stdin, &mut stdout);
+        let mut term = MemoryTerminal::new("secret\n");
+        let r = run_text(&opts, &mut term);
         assert!(r.is_ok());
-        assert_eq!(r.unwrap(), "secret");
-        let out = String::from_utf8(stdout).unwrap();
+        assert_eq!(r.unwrap().unwrap(), "secret");
+        let out = term.output();
         assert!(!out.contains("secret"));
     }
 
@@ -177,11 +695,26 @@ mod tests {
             initial: Some("default".into()),
             style: InputStyle::Default,
             error_msg: None,
+            ..Default::default()
         };
-        let mut stdin = Cursor::new(b"   \n");
-        let mut stdout = Vec::new();
-        let r = run_text(&opts, &mut stdin, &mut stdout);
+        let mut term = MemoryTerminal::new("   \n");
+        let r = run_text(&opts, &mut term);
         assert!(r.is_ok());
-        assert_eq!(r.unwrap(), "default");
+        assert_eq!(r.unwrap().unwrap(), "default");
+    }
+
+    #[test]
+    fn run_text_empty_rejection_uses_error_msg() {
+        let opts = TextPromptOptions {
+            message: "Name?".into(),
+            validate: Some(Arc::new(|v, _| match v {
+                PromptValue::String(s) if s.is_empty() => Err(String::new()),
+                _ => Ok(()),
+            })),
+            ..Default::default()
+        };
+        let mut term = MemoryTerminal::new("\nBob\n");
+        assert_eq!(run_text(&opts, &mut term).unwrap().unwrap(), "Bob");
+        assert!(term.output().contains("Please Enter A Valid Value"));
     }
 }
@@ -1,15 +1,31 @@
 //! Toggle prompt.
 
-use crate::util::style;
-use colour::write_bold;
-use std::io::{self, BufRead, Write};
+use crate::elements::prompt::{self, Check, Frame, Prompt, State, Transition};
+use crate::error::Result;
+use crate::prompts::{OnTimeout, Validator};
+#[cfg(feature = "tokio")]
+use crate::terminal::AsyncTerminal;
+use crate::terminal::Terminal;
+use crate::util::action::PromptAction;
+use colour::{write_cyan, write_gray};
+use std::io::Write;
+use std::time::Duration;
 
 /// Toggle prompt options.
+#[derive(Clone)]
 pub struct TogglePromptOptions {
     pub message: String,
     pub initial: bool,
     pub active: String,
     pub inactive: String,
+    /// Rejects an answer with a message; the prompt is then asked again.
+    pub validate: Option<Validator>,
+    /// Rejected answers allowed before giving up; `None` retries forever.
+    pub max_attempts: Option<u32>,
+    /// Time left to answer; a countdown is shown and any key stops it.
+    pub timeout: Option<Duration>,
+    /// What happens when `timeout` runs out.
+    pub on_timeout: OnTimeout,
 }
 
 impl Default for TogglePromptOptions {
@@ -19,48 +35,179 @@ impl Default for TogglePromptOptions {
             initial: false,
             active: "on".into(),
             inactive: "off".into(),
+            validate: None,
+            max_attempts: None,
+            timeout: None,
+            on_timeout: OnTimeout::default(),
         }
     }
 }
 
-/// Runs toggle prompt. Returns true for active, false for inactive.
-pub fn run_toggle<R: BufRead, W: Write>(
-    opts: &TogglePromptOptions,
-    stdin: &mut R,
-    stdout: &mut W,
-) -> io::Result<bool> {
-    let mut buf = Vec::with_capacity(opts.message.len() + 32);
-    write_bold!(&mut buf, "{}", opts.message).ok();
-    let msg = String::from_utf8_lossy(&buf).into_owned();
-    let hint = if opts.initial { "(Y/n)" } else { "(y/N)" };
-    let symbol = style::symbol(false, false, false);
-    let delim = style::delimiter(false);
-    write!(stdout, "{} {} {} {}", symbol, msg, delim, hint)?;
-    stdout.flush()?;
-    let mut line = String::new();
-    stdin.read_line(&mut line)?;
-    let raw = line.trim().to_lowercase();
-    let value = if raw.is_empty() {
-        opts.initial
+/// Toggle prompt state: the highlighted side and any typed reply.
+pub struct TogglePrompt {
+    opts: TogglePromptOptions,
+    value: bool,
+    typed: String,
+}
+
+impl TogglePrompt {
+    pub fn new(opts: TogglePromptOptions) -> Self {
+        let value = opts.initial;
+        Self {
+            opts,
+            value,
+            typed: String::new(),
+        }
+    }
+
+    /// Returns the answer: `y`/`yes`/`on` when typed, the highlighted side when empty.
+    pub fn answer(&self) -> bool {
+        let raw = self.typed.trim().to_lowercase();
+        if raw.is_empty() {
+            self.value
+        } else {
+            raw == "y" || raw == "yes" || raw == "on"
+        }
+    }
+
+    fn set(&mut self, value: bool) -> Transition<bool> {
+        self.value = value;
+        self.typed.clear();
+        Transition::Continue
+    }
+}
+
+fn highlight(text: &str, on: bool) -> String {
+    let mut buf = Vec::with_capacity(text.len() + 16);
+    if on {
+        write_cyan!(&mut buf, "{}", text).ok();
     } else {
-        raw == "y" || raw == "yes" || raw == "on"
+        write_gray!(&mut buf, "{}", text).ok();
+    }
+    String::from_utf8_lossy(&buf).into_owned()
+}
+
+impl Prompt for TogglePrompt {
+    type Output = bool;
+
+    fn render(&self, state: &State) -> Frame {
+        let head = prompt::title(&self.opts.message, state);
+        if *state != State::Active {
+            let result: &str = if self.answer() {
+                &self.opts.active
+            } else {
+                &self.opts.inactive
+            };
+            return Frame::line(format!("{} {}", head, result));
+        }
+        let mut line = format!(
+            "{} {} / {}",
+            head,
+            highlight(&self.opts.inactive, !self.value),
+            highlight(&self.opts.active, self.value)
+        );
+        if !self.typed.is_empty() {
+            line.push(' ');
+            line.push_str(&self.typed);
+        }
+        Frame::line(line)
+    }
+
+    fn handle(&mut self, action: PromptAction) -> Transition<bool> {
+        match action {
+            PromptAction::Submit => Transition::Submit(self.answer()),
+            PromptAction::Left | PromptAction::Home | PromptAction::First => self.set(false),
+            PromptAction::Right | PromptAction::End | PromptAction::Last => self.set(true),
+            PromptAction::Next => self.set(!self.value),
+            PromptAction::Reset => self.set(self.opts.initial),
+            PromptAction::Delete if !self.typed.is_empty() => {
+                self.typed.pop();
+                Transition::Continue
+            }
+            _ => Transition::Bell,
+        }
+    }
+
+    fn input(&mut self, c: char) -> Transition<bool> {
+        self.typed.push(c);
+        Transition::Continue
+    }
+}
+
+/// Runs toggle prompt. Returns true for active, false for inactive; `None` when cancelled.
+pub fn run_toggle<T: Terminal + ?Sized>(
+    opts: &TogglePromptOptions,
+    term: &mut T,
+) -> Result<Option<bool>> {
+    let check = Check {
+        validate: opts.validate.as_ref(),
+        max_attempts: opts.max_attempts,
+        timeout: opts.timeout,
+        on_timeout: opts.on_timeout,
+        ..Default::default()
+    };
+    prompt::drive(&mut TogglePrompt::new(opts.clone()), term, &check).map(|o| o.ok())
+}
+
+/// `run_toggle` for async code: waits for keys without blocking the runtime.
+#[cfg(feature = "tokio")]
+pub async fn run_toggle_async<T: AsyncTerminal + ?Sized>(
+    opts: &TogglePromptOptions,
+    term: &mut T,
+) -> Result<Option<bool>> {
+    let check = Check {
+        validate: opts.validate.as_ref(),
+        max_attempts: opts.max_attempts,
+        timeout: opts.timeout,
+        on_timeout: opts.on_timeout,
+        ..Default::default()
     };
-    let result_str: &str = if value { &opts.active } else { &opts.inactive };
-    let done_symbol = style::symbol(true, false, false);
-    let done_delim = style::delimiter(true);
-    writeln!(
-        stdout,
-        "\r{} {} {} {}",
-        done_symbol, msg, done_delim, result_str
-    )?;
-    stdout.flush()?;
-    Ok(value)
+    prompt::drive_async(&mut TogglePrompt::new(opts.clone()), term, &check)
+        .await
+        .map(|o| o.ok())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::io::Cursor;
+    use crate::terminal::MemoryTerminal;
+
+    #[test]
+    fn toggle_prompt_arrows_and_tab() {
+        let mut p = TogglePrompt::new(TogglePromptOptions::default());
+        p.handle(PromptAction::Right);
+        assert_eq!(p.handle(PromptAction::Submit), Transition::Submit(true));
+        p.handle(PromptAction::Home);
+        assert_eq!(p.handle(PromptAction::Submit), Transition::Submit(false));
+        p.handle(PromptAction::Next);
+        assert_eq!(p.handle(PromptAction::Submit), Transition::Submit(true));
+        p.handle(PromptAction::Reset);
+        assert_eq!(p.handle(PromptAction::Submit), Transition::Submit(false));
+    }
+
+    #[test]
+    fn toggle_prompt_typed_reply_wins() {
+        let mut p = TogglePrompt::new(TogglePromptOptions {
+            initial: true,
+            ..Default::default()
+        });
+        p.input('o');
+        p.input('f');
+        assert_eq!(p.handle(PromptAction::Submit), Transition::Submit(false));
+        p.handle(PromptAction::Left);
+        assert!(p.typed.is_empty());
+    }
+
+    #[test]
+    fn toggle_prompt_render_shows_both_labels() {
+        let p = TogglePrompt::new(TogglePromptOptions {
+            active: "Yes".into(),
+            inactive: "No".into(),
+            ..Default::default()
+        });
+        let line = &p.render(&State::Active).lines[0];
+        assert!(line.contains("Yes") && line.contains("No"));
+    }
 
     #[test]
     fn toggle_prompt_options_default() {
@@ -78,10 +225,10 @@ mod tests {
             initial: false,
             active: "on".into(),
             inactive: "off".into(),
+            ..Default::default()
         };
-        let mut stdin = Cursor::new(b"y\n");
-        let mut stdout = Vec::new();
-        assert_eq!(run_toggle(&opts, &mut stdin, &mut stdout).unwrap(), true);
+        let mut term = MemoryTerminal::new("y\n");
+        assert!(run_toggle(&opts, &mut term).unwrap().unwrap());
     }
 
     #[test]
@@ -91,10 +238,10 @@ mod tests {
             initial: false,
             active: "yes".into(),
             inactive: "no".into(),
+            ..Default::default()
         };
-        let mut stdin = Cursor::new(b"yes\n");
-        let mut stdout = Vec::new();
-        assert_eq!(run_toggle(&opts, &mut stdin, &mut stdout).unwrap(), true);
+        let mut term = MemoryTerminal::new("yes\n");
+        assert!(run_toggle(&opts, &mut term).unwrap().unwrap());
     }
 
     #[test]
@@ -104,10 +251,10 @@ mod tests {
             initial: true,
             active: "on".into(),
             inactive: "off".into(),
+            ..Default::default()
         };
-        let mut stdin = Cursor::new(b"n\n");
-        let mut stdout = Vec::new();
-        assert_eq!(run_toggle(&opts, &mut stdin, &mut stdout).unwrap(), false);
+        let mut term = MemoryTerminal::new("n\n");
+        assert!(!run_toggle(&opts, &mut term).unwrap().unwrap());
     }
 
     #[test]
@@ -117,10 +264,10 @@ mod tests {
             initial: true,
             active: "on".into(),
             inactive: "off".into(),
+            ..Default::default()
         };
-        let mut stdin = Cursor::new(b"\n");
-        let mut stdout = Vec::new();
-        assert_eq!(run_toggle(&opts, &mut stdin, &mut stdout).unwrap(), true);
+        let mut term = MemoryTerminal::new("\n");
+        assert!(run_toggle(&opts, &mut term).unwrap().unwrap());
     }
 
     #[test]
@@ -130,10 +277,10 @@ mod tests {
             initial: false,
             active: "on".into(),
             inactive: "off".into(),
+            ..Default::default()
         };
-        let mut stdin = Cursor::new(b"on\n");
-        let mut stdout = Vec::new();
-        assert_eq!(run_toggle(&opts, &mut stdin, &mut stdout).unwrap(), true);
+        let mut term = MemoryTerminal::new("on\n");
+        assert!(run_toggle(&opts, &mut term).unwrap().unwrap());
     }
 
     #[test]
@@ -143,10 +290,10 @@ mod tests {
             initial: true,
             active: "on".into(),
             inactive: "off".into(),
+            ..Default::default()
         };
-        let mut stdin = Cursor::new(b"no\n");
-        let mut stdout = Vec::new();
-        assert_eq!(run_toggle(&opts, &mut stdin, &mut stdout).unwrap(), false);
+        let mut term = MemoryTerminal::new("no\n");
+        assert!(!run_toggle(&opts, &mut term).unwrap().unwrap());
     }
 
     #[test]
@@ -156,10 +303,10 @@ mod tests {
             initial: true,
             active: "on".into(),
             inactive: "off".into(),
+            ..Default::default()
         };
-        let mut stdin = Cursor::new(b"maybe\n");
-        let mut stdout = Vec::new();
-        assert_eq!(run_toggle(&opts, &mut stdin, &mut stdout).unwrap(), false);
+        let mut term = MemoryTerminal::new("maybe\n");
+        assert!(!run_toggle(&opts, &mut term).unwrap().unwrap());
     }
 
     #[test]
@@ -169,9 +316,9 @@ mod tests {
             initial: false,
             active: "on".into(),
             inactive: "off".into(),
+            ..Default::default()
         };
-        let mut stdin = Cursor::new(b"maybe\n");
-        let mut stdout = Vec::new();
-        assert_eq!(run_toggle(&opts, &mut stdin, &mut stdout).unwrap(), false);
+        let mut term = MemoryTerminal::new("maybe\n");
+        assert!(!run_toggle(&opts, &mut term).unwrap().unwrap());
     }
 }
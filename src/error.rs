@@ -0,0 +1,249 @@
+//! Error type returned by prompts.
+
+use std::fmt;
+use std::io;
+
+/// Everything that can go wrong while asking questions.
+///
+/// Variants tied to a question carry its `Question::name` (empty when a prompt is run
+/// directly through a `run_*` function).
+#[derive(Debug)]
+pub enum Error {
+    /// Reading from or writing to the terminal failed.
+    Io(io::Error),
+    /// The question was cancelled with Escape or end of input.
+    Cancelled { name: String },
+    /// The question was interrupted with Ctrl-C or Ctrl-D.
+    Interrupted { name: String },
+    /// No prompt is registered for the question's `type_name`.
+    UnknownPromptType { name: String, type_name: String },
+    /// The question itself is malformed.
+    InvalidQuestion { name: String, reason: String },
+    /// The answer was rejected.
+    Validation { name: String, message: String },
+    /// An interactive terminal is required but not available.
+    NotATerminal,
+    /// The question was not answered in time.
+    Timeout { name: String },
+    /// Questions that could not be answered without a terminal, in order.
+    Unanswered { names: Vec<String> },
+    /// An answer could not be converted to the requested type.
+    Conversion { name: String, message: String },
+    /// A questionnaire document does not match the schema; `line` and `column` are 1-based.
+    Questionnaire {
+        line: usize,
+        column: usize,
+        message: String,
+    },
+}
+
+/// Result alias used throughout the crate.
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl Error {
+    /// Returns the name of the question the error belongs to, if any.
+    pub fn name(&self) -> Option<&str> {
+        match self {
+            Error::Cancelled { name }
+            | Error::Interrupted { name }
+            | Error::UnknownPromptType { name, .. }
+            | Error::InvalidQuestion { name, .. }
+            | Error::Validation { name, .. }
+            | Error::Conversion { name, .. }
+            | Error::Timeout { name } => Some(name),
+            Error::Io(_)
+            | Error::NotATerminal
+            | Error::Unanswered { .. }
+            | Error::Questionnaire { .. } => None,
+        }
+    }
+
+    /// Fills in the question name when the error does not carry one yet.
+    pub(crate) fn with_name(mut self, question: &str) -> Self {
+        match &mut self {
+            Error::Cancelled { name }
+            | Error::Interrupted { name }
+            | Error::UnknownPromptType { name, .. }
+            | Error::InvalidQuestion { name, .. }
+            | Error::Validation { name, .. }
+            | Error::Conversion { name, .. }
+            | Error::Timeout { name }
+                if name.is_empty() =>
+            {
+                *name = question.to_string();
+            }
+            _ => {}
+        }
+        self
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Io(e) => write!(f, "{}", e),
+            Error::Cancelled { name } => write!(f, "prompt '{}' was cancelled", name),
+            Error::Interrupted { name } => write!(f, "prompt '{}' was interrupted", name),
+            Error::UnknownPromptType { type_name, .. } => {
+                write!(f, "prompt type '{}' is not defined", type_name)
+            }
+            Error::InvalidQuestion { name, reason } => {
+                write!(f, "invalid question '{}': {}", name, reason)
+            }
+            Error::Validation { name, message } if name.is_empty() => write!(f, "{}", message),
+            Error::Validation { name, message } => write!(f, "{}: {}", name, message),
+            Error::Conversion { name, message } => write!(f, "answer '{}': {}", name, message),
+            Error::NotATerminal => write!(f, "not a terminal"),
+            Error::Timeout { name } => write!(f, "prompt '{}' timed out", name),
+            Error::Unanswered { names } => {
+                let names: Vec<String> = names.iter().map(|n| format!("'{}'", n)).collect();
+                write!(f, "no answer without a terminal for {}", names.join(", "))
+            }
+            Error::Questionnaire {
+                line,
+                column,
+                message,
+            } => write!(
+                f,
+                "questionnaire line {}, column {}: {}",
+                line, column, message
+            ),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Self {
+        Error::Io(e)
+    }
+}
+
+impl From<Error> for io::Error {
+    fn from(e: Error) -> Self {
+        let kind = match &e {
+            Error::Io(inner) => inner.kind(),
+            Error::Cancelled { .. } | Error::Interrupted { .. } => io::ErrorKind::Interrupted,
+            Error::NotATerminal | Error::Unanswered { .. } => io::ErrorKind::Unsupported,
+            Error::Timeout { .. } => io::ErrorKind::TimedOut,
+            Error::Questionnaire { .. } | Error::Conversion { .. } => io::ErrorKind::InvalidData,
+            _ => io::ErrorKind::InvalidInput,
+        };
+        match e {
+            Error::Io(inner) => inner,
+            e => io::Error::new(kind, e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn display_names_the_question() {
+        let e = Error::Cancelled {
+            name: "port".into(),
+        };
+        assert_eq!(e.to_string(), "prompt 'port' was cancelled");
+        let e = Error::InvalidQuestion {
+            name: "port".into(),
+            reason: "message is required".into(),
+        };
+        assert_eq!(
+            e.to_string(),
+            "invalid question 'port': message is required"
+        );
+    }
+
+    #[test]
+    fn validation_without_name_shows_message_only() {
+        let e = Error::Validation {
+            name: String::new(),
+            message: "invalid number".into(),
+        };
+        assert_eq!(e.to_string(), "invalid number");
+        assert_eq!(e.with_name("n").to_string(), "n: invalid number");
+    }
+
+    #[test]
+    fn with_name_keeps_existing_name() {
+        let e = Error::Timeout { name: "a".into() }.with_name("b");
+        assert_eq!(e.name(), Some("a"));
+        assert_eq!(Error::NotATerminal.with_name("b").name(), None);
+    }
+
+    #[test]
+    fn io_errors_convert_both_ways() {
+        let e: Error = io::Error::new(io::ErrorKind::BrokenPipe, "pipe").into();
+        assert!(matches!(e, Error::Io(_)));
+        assert!(std::error::Error::source(&e).is_some());
+        let back: io::Error = e.into();
+        assert_eq!(back.kind(), io::ErrorKind::BrokenPipe);
+    }
+
+    #[test]
+    fn prompt_errors_map_to_io_kinds() {
+        let e: io::Error = Error::Interrupted { name: "x".into() }.into();
+        assert_eq!(e.kind(), io::ErrorKind::Interrupted);
+        let e: io::Error = Error::Timeout { name: "x".into() }.into();
+        assert_eq!(e.kind(), io::ErrorKind::TimedOut);
+        let e: io::Error = Error::UnknownPromptType {
+            name: "x".into(),
+            type_name: "bad".into(),
+        }
+        .into();
+        assert_eq!(e.kind(), io::ErrorKind::InvalidInput);
+        assert_eq!(e.to_string(), "prompt type 'bad' is not defined");
+    }
+
+    #[test]
+    fn unanswered_lists_every_question() {
+        let e = Error::Unanswered {
+            names: vec!["host".into(), "port".into()],
+        };
+        assert_eq!(
+            e.to_string(),
+            "no answer without a terminal for 'host', 'port'"
+        );
+        assert_eq!(e.name(), None);
+        let e: io::Error = e.into();
+        assert_eq!(e.kind(), io::ErrorKind::Unsupported);
+    }
+
+    #[test]
+    fn questionnaire_error_shows_position() {
+        let e = Error::Questionnaire {
+            line: 3,
+            column: 9,
+            message: "invalid type".into(),
+        };
+        assert_eq!(
+            e.to_string(),
+            "questionnaire line 3, column 9: invalid type"
+        );
+        let e: io::Error = e.into();
+        assert_eq!(e.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn conversion_error_names_the_answer() {
+        let e = Error::Conversion {
+            name: String::new(),
+            message: "invalid type".into(),
+        }
+        .with_name("port");
+        assert_eq!(e.to_string(), "answer 'port': invalid type");
+        assert_eq!(e.name(), Some("port"));
+        let e: io::Error = e.into();
+        assert_eq!(e.kind(), io::ErrorKind::InvalidData);
+    }
+}
@@ -0,0 +1,194 @@
+//! Input history kept per question in a file.
+
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+/// Where and how much input is remembered for questions with `Question::history` set.
+///
+/// The file holds one `name<TAB>entry` line per entry, oldest first. History is best effort:
+/// a file that cannot be read or written is treated as empty.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct History {
+    /// File holding the history; `None` keeps none. Defaults to `promptt/history` in the
+    /// user's data directory.
+    pub path: Option<PathBuf>,
+    /// Entries kept per question; the oldest are dropped first.
+    pub limit: usize,
+}
+
+impl Default for History {
+    fn default() -> Self {
+        Self {
+            path: dirs::data_dir().map(|dir| dir.join("promptt").join("history")),
+            limit: 100,
+        }
+    }
+}
+
+impl History {
+    /// History kept in `path`.
+    pub fn at(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: Some(path.into()),
+            ..Default::default()
+        }
+    }
+
+    /// Entries for the question `name`, oldest first.
+    pub fn load(&self, name: &str) -> Vec<String> {
+        self.read()
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|(n, _)| n == name)
+            .map(|(_, entry)| entry)
+            .collect()
+    }
+
+    /// Adds `entry` as the newest entry for `name`. An older copy of it is removed, as are
+    /// the entries past `limit`. Blank entries are not kept.
+    pub fn record(&self, name: &str, entry: &str) -> io::Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        if entry.trim().is_empty() {
+            return Ok(());
+        }
+        let mut entries = self.read()?;
+        entries.retain(|(n, e)| n != name || e != entry);
+        entries.push((name.to_string(), entry.to_string()));
+        let mut excess = entries
+            .iter()
+            .filter(|(n, _)| n == name)
+            .count()
+            .saturating_sub(self.limit);
+        entries.retain(|(n, _)| {
+            let drop = excess > 0 && n == name;
+            excess -= drop as usize;
+            !drop
+        });
+        let text: String = entries
+            .iter()
+            .map(|(n, e)| format!("{}\t{}\n", escape(n), escape(e)))
+            .collect();
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let tmp = path.with_extension("tmp");
+        fs::write(&tmp, text)?;
+        fs::rename(&tmp, path)
+    }
+
+    /// All `(name, entry)` pairs in file order; a missing file has none.
+    fn read(&self) -> io::Result<Vec<(String, String)>> {
+        let Some(path) = &self.path else {
+            return Ok(Vec::new());
+        };
+        let text = match fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e),
+        };
+        Ok(text
+            .lines()
+            .filter_map(|line| line.split_once('\t'))
+            .map(|(n, e)| (unescape(n), unescape(e)))
+            .collect())
+    }
+}
+
+/// Escapes the characters that separate names, entries and lines.
+fn escape(s: &str) -> String {
+    s.replace('\\', "\\\\")
+        .replace('\t', "\\t")
+        .replace('\n', "\\n")
+}
+
+fn unescape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('t') => out.push('\t'),
+            Some('n') => out.push('\n'),
+            Some(other) => out.push(other),
+            None => out.push('\\'),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn history(dir: &tempfile::TempDir) -> History {
+        History::at(dir.path().join("nested").join("history"))
+    }
+
+    #[test]
+    fn missing_file_is_empty() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(history(&dir).load("branch").is_empty());
+    }
+
+    #[test]
+    fn record_keeps_entries_per_name_oldest_first() {
+        let dir = tempfile::tempdir().unwrap();
+        let h = history(&dir);
+        h.record("branch", "main").unwrap();
+        h.record("ticket", "OPS-1").unwrap();
+        h.record("branch", "dev").unwrap();
+        assert_eq!(h.load("branch"), ["main", "dev"]);
+        assert_eq!(h.load("ticket"), ["OPS-1"]);
+    }
+
+    #[test]
+    fn record_moves_duplicates_to_the_end() {
+        let dir = tempfile::tempdir().unwrap();
+        let h = history(&dir);
+        for entry in ["a", "b", "a"] {
+            h.record("q", entry).unwrap();
+        }
+        assert_eq!(h.load("q"), ["b", "a"]);
+    }
+
+    #[test]
+    fn record_drops_the_oldest_past_limit() {
+        let dir = tempfile::tempdir().unwrap();
+        let h = History {
+            limit: 2,
+            ..history(&dir)
+        };
+        h.record("other", "x").unwrap();
+        for entry in ["1", "2", "3"] {
+            h.record("q", entry).unwrap();
+        }
+        assert_eq!(h.load("q"), ["2", "3"]);
+        assert_eq!(h.load("other"), ["x"]);
+    }
+
+    #[test]
+    fn record_skips_blank_entries_and_round_trips_separators() {
+        let dir = tempfile::tempdir().unwrap();
+        let h = history(&dir);
+        h.record("q", "  ").unwrap();
+        h.record("q", "tab\there\\n").unwrap();
+        h.record("q", "two\nlines").unwrap();
+        assert_eq!(h.load("q"), ["tab\there\\n", "two\nlines"]);
+    }
+
+    #[test]
+    fn no_path_keeps_nothing() {
+        let h = History {
+            path: None,
+            ..Default::default()
+        };
+        h.record("q", "a").unwrap();
+        assert!(h.load("q").is_empty());
+    }
+}
@@ -1,51 +1,228 @@
 //! Interactive CLI prompts: text, confirm, number, select, toggle, list, password, invisible.
 
+// Lets `#[derive(Prompt)]` output, which names `::promptt`, compile inside this crate.
+#[cfg(feature = "derive")]
+extern crate self as promptt;
+
+mod answers;
+#[cfg(feature = "tokio")]
+mod async_prompt;
+mod builders;
+#[cfg(feature = "serde")]
+mod de;
+#[cfg(feature = "derive")]
+mod derive;
 mod elements;
+mod error;
+mod history;
+mod non_interactive;
 mod prompts;
+#[cfg(any(feature = "json", feature = "toml", feature = "yaml"))]
+mod questionnaire;
+mod registry;
+mod terminal;
 mod util;
 
+pub use answers::Answers;
+#[cfg(feature = "tokio")]
+pub use async_prompt::{prompt_async, prompt_with_async, run_prompt_async};
+pub use builders::{
+    AutocompleteQuestion, ConfirmQuestion, DateQuestion, EditorQuestion, ListQuestion,
+    MultiselectQuestion, NumberQuestion, SelectQuestion, TextQuestion, ToggleQuestion,
+};
+pub use chrono::NaiveDateTime;
+#[cfg(feature = "serde")]
+pub use de::from_answers;
+#[cfg(feature = "derive")]
+pub use derive::PromptField;
 pub use elements::{
-    Choice, ConfirmPromptOptions, NumberPromptOptions, Prompt, SelectPromptOptions,
-    TextPromptOptions, TogglePromptOptions,
+    AutocompletePrompt, AutocompletePromptOptions, Choice, ConfirmPrompt, ConfirmPromptOptions,
+    DatePrompt, DatePromptOptions, EditorPromptOptions, Frame, Match, MatchFn, Matcher,
+    MultiselectPrompt, MultiselectPromptOptions, NumberPrompt, NumberPromptOptions, Prompt,
+    SelectPrompt, SelectPromptOptions, State, TextPrompt, TextPromptOptions, TogglePrompt,
+    TogglePromptOptions, Transition, run_autocomplete, run_confirm, run_date, run_editor,
+    run_multiselect, run_number, run_select, run_text, run_toggle,
+};
+#[cfg(feature = "tokio")]
+pub use elements::{
+    run_autocomplete_async, run_confirm_async, run_date_async, run_multiselect_async,
+    run_number_async, run_select_async, run_text_async, run_toggle_async,
+};
+pub use error::{Error, Result};
+pub use history::History;
+pub use non_interactive::{Env, EnvFn, Mode, env_var_name};
+pub use prompts::{
+    Computed, Displayer, Dynamic, Formatter, Injected, OnCancel, OnTimeout, PromptConfig,
+    PromptValue, Question, Validator, run_prompt,
 };
-pub use prompts::{PromptValue, Question, run_prompt};
-pub use util::{Figures, InputStyle, clear, key_action, lines_count, render_style, strip_ansi};
+#[cfg(feature = "derive")]
+pub use promptt_derive::Prompt;
+#[cfg(any(feature = "json", feature = "toml", feature = "yaml"))]
+pub use questionnaire::load_questions;
+#[cfg(feature = "json")]
+pub use questionnaire::questions_from_json;
+#[cfg(feature = "toml")]
+pub use questionnaire::questions_from_toml;
+#[cfg(feature = "yaml")]
+pub use questionnaire::questions_from_yaml;
+pub use registry::{PromptFactory, PromptRegistry, Runner};
+#[cfg(feature = "tokio")]
+pub use terminal::AsyncTerminal;
+pub use terminal::{CrosstermTerminal, MemoryTerminal, Terminal};
+#[cfg(feature = "tokio")]
+pub use tokio_util::sync::CancellationToken;
+pub use util::{
+    ByteSource, Figures, InputStyle, Key, KeyDecoder, KeyName, PromptAction, clear, key_action,
+    lines_count, read_key, render_style, strip_ansi,
+};
+
+use elements::{Input, Outcome, now, read_blocking};
+
+/// Runs questions in sequence on `term`. Returns the answers in question order.
+///
+/// When `term` is not a TTY, questions are answered from their initial values instead of
+/// asking; see `Mode`.
+///
+/// Dynamic fields (`Question::dynamic`) are computed from the answers given so far; a question
+/// whose type is empty, or computed as `None`, is skipped.
+///
+/// A cancelled question aborts the flow with `Error::Interrupted` (Ctrl-C, Ctrl-D) or
+/// `Error::Cancelled` (Escape, end of input); use `prompt_with` to skip it instead.
+pub fn prompt<T: Terminal + ?Sized>(questions: &[Question], term: &mut T) -> Result<Answers> {
+    let config = PromptConfig {
+        history: Some(History::default()),
+        ..Default::default()
+    };
+    prompt_with(questions, term, &config)
+}
+
+/// Runs questions in sequence on `term`, resolving `type_name` against `registry`.
+pub fn prompt_with_registry<T: Terminal + ?Sized>(
+    questions: &[Question],
+    term: &mut T,
+    registry: &PromptRegistry,
+) -> Result<Answers> {
+    let config = PromptConfig {
+        registry: registry.clone(),
+        history: Some(History::default()),
+        ..Default::default()
+    };
+    prompt_with(questions, term, &config)
+}
 
-use std::collections::HashMap;
-use std::io::{self, BufRead, Write};
+/// Runs questions in sequence on `term` with the given config.
+///
+/// Questions with an answer in `config.answers` or `config.inject` are not asked. When
+/// `config.mode` skips the terminal, the others are answered from the environment variable
+/// named by `env_var_name(prefix, name)`, then from their initial value; the questions left
+/// without an answer are reported together as `Error::Unanswered`.
+pub fn prompt_with<T: Terminal + ?Sized>(
+    questions: &[Question],
+    term: &mut T,
+    config: &PromptConfig,
+) -> Result<Answers> {
+    now(ask_all(
+        questions,
+        term,
+        config,
+        &mut async |t: &mut T, wait| read_blocking(t, wait),
+    ))
+}
 
-/// Runs questions in sequence. Returns a name-to-value map. I/O via stdin/stdout.
-pub fn prompt<R: BufRead, W: Write>(
+/// `prompt_with` with keys read by `read`.
+pub(crate) async fn ask_all<T, R>(
     questions: &[Question],
-    stdin: &mut R,
-    stdout: &mut W,
-) -> io::Result<HashMap<String, PromptValue>> {
-    let mut answers = HashMap::with_capacity(questions.len());
+    term: &mut T,
+    config: &PromptConfig,
+    read: &mut R,
+) -> Result<Answers>
+where
+    T: Terminal + ?Sized,
+    R: AsyncFnMut(&mut T, Option<std::time::Duration>) -> std::io::Result<Input>,
+{
+    let mut answers = Answers::with_capacity(questions.len());
+    let mut inject = config.inject.iter();
+    let unattended = config.mode.skips_terminal(term.is_tty());
+    let mut unanswered = Vec::new();
     for q in questions {
+        let q = &q.resolve(&answers);
         if q.type_name.is_empty() {
             continue;
         }
         if q.message.is_empty() {
-            return Err(io::Error::new(
-                io::ErrorKind::InvalidInput,
-                "prompt message is required",
-            ));
+            return Err(Error::InvalidQuestion {
+                name: q.name.clone(),
+                reason: "message is required".into(),
+            });
         }
-        match run_prompt(q, stdin, stdout) {
-            Ok(Some(value)) => {
+        let injected = match config.answers.get(&q.name) {
+            Some(injected) => Some(injected),
+            None => inject.next(),
+        };
+        let outcome = match injected {
+            Some(Injected::Answer(value)) => Outcome::Submitted(q.accept(value.clone(), &answers)?),
+            Some(Injected::Cancel) => Outcome::Cancelled(State::Aborted),
+            None if unattended => {
+                let env = config
+                    .env_prefix
+                    .as_ref()
+                    .and_then(|prefix| config.env.var(&env_var_name(prefix, &q.name)));
+                match q.unattended_answer(env.as_deref())? {
+                    Some(value) => Outcome::Submitted(q.accept(value, &answers)?),
+                    None => {
+                        unanswered.push(q.name.clone());
+                        continue;
+                    }
+                }
+            }
+            None => {
+                config
+                    .registry
+                    .ask_with(q, term, &answers, config.history.as_ref(), read)
+                    .await?
+            }
+        };
+        match outcome {
+            Outcome::Submitted(value) => {
                 answers.insert(q.name.clone(), value);
             }
-            Ok(None) => {}
-            Err(e) => return Err(e),
+            Outcome::Cancelled(_) | Outcome::TimedOut if config.on_cancel == OnCancel::Skip => {}
+            Outcome::Cancelled(State::Aborted) => {
+                return Err(Error::Interrupted {
+                    name: q.name.clone(),
+                });
+            }
+            Outcome::Cancelled(_) => {
+                return Err(Error::Cancelled {
+                    name: q.name.clone(),
+                });
+            }
+            Outcome::TimedOut => {
+                return Err(Error::Timeout {
+                    name: q.name.clone(),
+                });
+            }
         }
     }
+    if !unanswered.is_empty() {
+        return Err(Error::Unanswered { names: unanswered });
+    }
     Ok(answers)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::io::Cursor;
+    use std::collections::HashMap;
+    use std::sync::Arc;
+
+    /// Asks on a terminal that is not a TTY, as when input is piped.
+    fn piped() -> PromptConfig {
+        PromptConfig {
+            mode: Mode::Interactive,
+            ..Default::default()
+        }
+    }
 
     #[test]
     fn test_strip_ansi() {
@@ -75,9 +252,8 @@ mod tests {
             message: "Skipped?".into(),
             ..Default::default()
         }];
-        let mut stdin = Cursor::new(b"");
-        let mut stdout = Vec::new();
-        let r = prompt(&questions, &mut stdin, &mut stdout);
+        let mut term = MemoryTerminal::new("");
+        let r = prompt(&questions, &mut term);
         assert!(r.is_ok());
         let answers = r.unwrap();
         assert!(answers.is_empty());
@@ -91,10 +267,10 @@ mod tests {
             message: String::new(),
             ..Default::default()
         }];
-        let mut stdin = Cursor::new(b"");
-        let mut stdout = Vec::new();
-        let r = prompt(&questions, &mut stdin, &mut stdout);
+        let mut term = MemoryTerminal::new("");
+        let r = prompt(&questions, &mut term);
         assert!(r.is_err());
+        assert!(matches!(r.unwrap_err(), Error::InvalidQuestion { name, .. } if name == "x"));
     }
 
     #[test]
@@ -114,9 +290,8 @@ mod tests {
                 ..Default::default()
             },
         ];
-        let mut stdin = Cursor::new(b"Alice\ny\n");
-        let mut stdout = Vec::new();
-        let r = prompt(&questions, &mut stdin, &mut stdout);
+        let mut term = MemoryTerminal::new("Alice\ny\n").tty(true);
+        let r = prompt(&questions, &mut term);
         assert!(r.is_ok());
         let answers = r.unwrap();
         assert_eq!(answers.len(), 2);
@@ -138,9 +313,8 @@ mod tests {
             message: "Msg".into(),
             ..Default::default()
         }];
-        let mut stdin = Cursor::new(b"");
-        let mut stdout = Vec::new();
-        let r = prompt(&questions, &mut stdin, &mut stdout);
+        let mut term = MemoryTerminal::new("");
+        let r = prompt(&questions, &mut term);
         assert!(r.is_err());
     }
 
@@ -160,9 +334,8 @@ mod tests {
                 ..Default::default()
             },
         ];
-        let mut stdin = Cursor::new(b"");
-        let mut stdout = Vec::new();
-        let r = prompt(&questions, &mut stdin, &mut stdout);
+        let mut term = MemoryTerminal::new("");
+        let r = prompt(&questions, &mut term);
         assert!(r.is_ok());
         assert!(r.unwrap().is_empty());
     }
@@ -183,9 +356,8 @@ mod tests {
                 ..Default::default()
             },
         ];
-        let mut stdin = Cursor::new(b"one\ntwo\n");
-        let mut stdout = Vec::new();
-        let r = prompt(&questions, &mut stdin, &mut stdout);
+        let mut term = MemoryTerminal::new("one\ntwo\n").tty(true);
+        let r = prompt(&questions, &mut term);
         assert!(r.is_ok());
         let answers = r.unwrap();
         assert_eq!(
@@ -214,9 +386,369 @@ mod tests {
                 ..Default::default()
             },
         ];
-        let mut stdin = Cursor::new(b"ignored\n");
-        let mut stdout = Vec::new();
-        let r = prompt(&questions, &mut stdin, &mut stdout);
+        let mut term = MemoryTerminal::new("ignored\n");
+        let r = prompt(&questions, &mut term);
         assert!(r.is_err());
     }
+
+    fn two_text_questions() -> Vec<Question> {
+        vec![
+            Question {
+                name: "first".into(),
+                type_name: "text".into(),
+                message: "First?".into(),
+                ..Default::default()
+            },
+            Question {
+                name: "second".into(),
+                type_name: "text".into(),
+                message: "Second?".into(),
+                ..Default::default()
+            },
+        ]
+    }
+
+    #[test]
+    fn prompt_cancel_aborts_flow_by_default() {
+        let mut term = MemoryTerminal::new("\x03one\n").tty(true);
+        let e = prompt(&two_text_questions(), &mut term).unwrap_err();
+        assert!(matches!(&e, Error::Interrupted { name } if name == "first"));
+        assert!(!term.output().contains("Second?"));
+    }
+
+    #[test]
+    fn prompt_with_skip_leaves_cancelled_question_unanswered() {
+        let config = PromptConfig {
+            on_cancel: OnCancel::Skip,
+            ..Default::default()
+        };
+        let mut term = MemoryTerminal::new("\x03two\n").tty(true);
+        let answers = prompt_with(&two_text_questions(), &mut term, &config).unwrap();
+        assert!(!answers.contains_key("first"));
+        assert_eq!(
+            answers.get("second"),
+            Some(&PromptValue::String("two".into()))
+        );
+    }
+
+    #[test]
+    fn prompt_timeout_cancel_follows_on_cancel() {
+        let questions: Vec<Question> = vec![
+            Question::text("first", "First?")
+                .timeout(std::time::Duration::from_secs(2))
+                .on_timeout(OnTimeout::Cancel)
+                .into(),
+        ];
+        let mut term = MemoryTerminal::new("").tty(true);
+        let e = prompt(&questions, &mut term).unwrap_err();
+        assert!(matches!(&e, Error::Timeout { name } if name == "first"));
+        let config = PromptConfig {
+            on_cancel: OnCancel::Skip,
+            ..Default::default()
+        };
+        let mut term = MemoryTerminal::new("").tty(true);
+        let answers = prompt_with(&questions, &mut term, &config).unwrap();
+        assert!(!answers.contains_key("first"));
+    }
+
+    #[test]
+    fn prompt_with_history_remembers_and_recalls_answers() {
+        let dir = tempfile::tempdir().unwrap();
+        let history = History::at(dir.path().join("history"));
+        let config = PromptConfig {
+            history: Some(history.clone()),
+            ..Default::default()
+        };
+        let questions: Vec<Question> = vec![
+            Question::text("branch", "Branch?").history().into(),
+            Question::list("tags", "Tags?").history().into(),
+            Question::password("token", "Token?").history().into(),
+        ];
+        let mut term = MemoryTerminal::new("main\na,b\nsecret\n").tty(true);
+        prompt_with(&questions, &mut term, &config).unwrap();
+        let mut term = MemoryTerminal::new("dev\n\x1b[A\r\x1b[A\r").tty(true);
+        let answers = prompt_with(&questions, &mut term, &config).unwrap();
+        assert_eq!(
+            answers["tags"],
+            PromptValue::List(vec!["a".into(), "b".into()])
+        );
+        assert_eq!(answers["token"], PromptValue::from(""));
+        assert_eq!(history.load("branch"), ["main", "dev"]);
+        assert_eq!(history.load("tags"), ["a, b"]);
+        assert!(history.load("token").is_empty());
+        let text = std::fs::read_to_string(dir.path().join("history")).unwrap();
+        assert!(!text.contains("secret"));
+    }
+
+    #[test]
+    fn prompt_with_default_config_keeps_no_history() {
+        assert!(PromptConfig::default().history.is_none());
+        let questions: Vec<Question> = vec![Question::text("branch", "Branch?").history().into()];
+        let mut term = MemoryTerminal::new(
+            "main
+",
+        )
+        .tty(true);
+        let answers = prompt_with(&questions, &mut term, &PromptConfig::default()).unwrap();
+        assert_eq!(answers["branch"], PromptValue::from("main"));
+    }
+
+    #[test]
+    fn prompt_eof_cancels_instead_of_using_initial() {
+        let questions = vec![Question {
+            name: "name".into(),
+            type_name: "text".into(),
+            message: "Name?".into(),
+            initial_text: Some("anon".into()),
+            ..Default::default()
+        }];
+        let mut term = MemoryTerminal::new("").tty(true);
+        assert!(matches!(
+            prompt(&questions, &mut term),
+            Err(Error::Cancelled { name }) if name == "name"
+        ));
+    }
+
+    #[test]
+    fn prompt_with_registry_resolves_custom_types() {
+        let mut registry = PromptRegistry::new();
+        registry.register_runner("region", |_, _| Ok(Some("eu-west-1".into())));
+        let questions = vec![
+            Question {
+                name: "region".into(),
+                type_name: "region".into(),
+                message: "Region?".into(),
+                ..Default::default()
+            },
+            Question::text("name", "Name?").into(),
+        ];
+        let mut term = MemoryTerminal::new("Bob\n").tty(true);
+        let answers = prompt_with_registry(&questions, &mut term, &registry).unwrap();
+        assert_eq!(
+            answers.get("region"),
+            Some(&PromptValue::String("eu-west-1".into()))
+        );
+        assert_eq!(
+            answers.get("name"),
+            Some(&PromptValue::String("Bob".into()))
+        );
+        assert!(prompt(&questions, &mut MemoryTerminal::new("").tty(true)).is_err());
+    }
+
+    #[test]
+    fn prompt_validator_sees_earlier_answers() {
+        let questions = vec![
+            Question::password("password", "Password?").into(),
+            Question::password("confirm", "Again?")
+                .validate(|v, answers| match answers.get("password") {
+                    Some(first) if first == v => Ok(()),
+                    _ => Err("passwords do not match".into()),
+                })
+                .into(),
+        ];
+        let mut term = MemoryTerminal::new("secret\nsecrt\nsecret\n");
+        let answers = prompt_with(&questions, &mut term, &piped()).unwrap();
+        assert_eq!(
+            answers.get("confirm"),
+            Some(&PromptValue::String("secret".into()))
+        );
+        assert!(term.output().contains("passwords do not match"));
+    }
+
+    #[test]
+    fn prompt_stores_formatted_answer_after_validation() {
+        let questions = vec![
+            Question::list("ports", "Ports?")
+                .validate(|v, _| match v {
+                    PromptValue::List(items) if items.iter().all(|p| p.parse::<u16>().is_ok()) => {
+                        Ok(())
+                    }
+                    _ => Err("ports must be numbers".into()),
+                })
+                .format(|v, _| match v {
+                    PromptValue::List(items) => PromptValue::Float(items.len() as f64),
+                    v => v,
+                })
+                .into(),
+        ];
+        let mut term = MemoryTerminal::new("80,http\n80, 443\n");
+        let answers = prompt_with(&questions, &mut term, &piped()).unwrap();
+        assert_eq!(answers.get("ports"), Some(&PromptValue::Float(2.0)));
+        assert!(term.output().contains("ports must be numbers"));
+    }
+
+    #[test]
+    fn prompt_computes_dynamic_fields_from_earlier_answers() {
+        let questions = vec![
+            Question::select("cloud", "Cloud?")
+                .choice("AWS", "aws")
+                .choice("GCP", "gcp")
+                .into(),
+            Question::confirm("needs_db", "Needs a database?").into(),
+            Question::select("db", "Which database?")
+                .when(|a| a.get("needs_db") == Some(&PromptValue::Bool(true)))
+                .choices_with(|a| match a.get("cloud") {
+                    Some(PromptValue::String(c)) if c == "gcp" => {
+                        vec![Choice::new("Cloud SQL", "cloudsql")]
+                    }
+                    _ => vec![Choice::new("RDS", "rds"), Choice::new("Aurora", "aurora")],
+                })
+                .message_with(|a| format!("Which database on {:?}?", a["cloud"]))
+                .into(),
+        ];
+        let mut term = MemoryTerminal::new("1\ny\n2\n").tty(true);
+        let answers = prompt(&questions, &mut term).unwrap();
+        assert_eq!(
+            answers.get("db"),
+            Some(&PromptValue::String("aurora".into()))
+        );
+        assert!(term.output().contains("Which database on String(\"aws\")?"));
+
+        let mut term = MemoryTerminal::new("2\nn\n").tty(true);
+        let answers = prompt(&questions, &mut term).unwrap();
+        assert!(!answers.contains_key("db"));
+        assert!(!term.output().contains("database on"));
+    }
+
+    #[test]
+    fn prompt_with_injected_answers_does_not_read_input() {
+        let config = PromptConfig {
+            answers: HashMap::from([("second".to_string(), Injected::from("by name"))]),
+            inject: vec!["queued".into()],
+            ..Default::default()
+        };
+        let mut term = MemoryTerminal::new("");
+        let answers = prompt_with(&two_text_questions(), &mut term, &config).unwrap();
+        assert_eq!(
+            answers.get("first"),
+            Some(&PromptValue::String("queued".into()))
+        );
+        assert_eq!(
+            answers.get("second"),
+            Some(&PromptValue::String("by name".into()))
+        );
+        assert_eq!(term.output(), "");
+    }
+
+    #[test]
+    fn injected_queue_falls_back_to_prompting_when_empty() {
+        let config = PromptConfig {
+            inject: vec!["one".into()],
+            ..Default::default()
+        };
+        let mut term = MemoryTerminal::new("two\n").tty(true);
+        let answers = prompt_with(&two_text_questions(), &mut term, &config).unwrap();
+        assert_eq!(
+            answers.get("second"),
+            Some(&PromptValue::String("two".into()))
+        );
+        assert!(!term.output().contains("First?"));
+    }
+
+    #[test]
+    fn injected_answers_are_validated_and_formatted() {
+        let questions: Vec<Question> = vec![
+            Question::text("slug", "Slug?")
+                .validate(|v, _| match v {
+                    PromptValue::String(s) if s.contains(' ') => Err("no spaces".into()),
+                    _ => Ok(()),
+                })
+                .format(|v, _| match v {
+                    PromptValue::String(s) => PromptValue::String(s.to_lowercase()),
+                    v => v,
+                })
+                .into(),
+        ];
+        let config = PromptConfig {
+            inject: vec!["My-App".into()],
+            ..Default::default()
+        };
+        let answers = prompt_with(&questions, &mut MemoryTerminal::new(""), &config).unwrap();
+        assert_eq!(
+            answers.get("slug"),
+            Some(&PromptValue::String("my-app".into()))
+        );
+        let config = PromptConfig {
+            inject: vec!["my app".into()],
+            ..Default::default()
+        };
+        let e = prompt_with(&questions, &mut MemoryTerminal::new(""), &config).unwrap_err();
+        assert!(
+            matches!(e, Error::Validation { name, message } if name == "slug" && message == "no spaces")
+        );
+    }
+
+    #[test]
+    fn injected_cancel_aborts_like_ctrl_c() {
+        let config = PromptConfig {
+            inject: vec!["one".into(), Injected::Cancel],
+            ..Default::default()
+        };
+        let e =
+            prompt_with(&two_text_questions(), &mut MemoryTerminal::new(""), &config).unwrap_err();
+        assert!(matches!(&e, Error::Interrupted { name } if name == "second"));
+        let config = PromptConfig {
+            on_cancel: OnCancel::Skip,
+            ..config
+        };
+        let answers =
+            prompt_with(&two_text_questions(), &mut MemoryTerminal::new(""), &config).unwrap();
+        assert_eq!(answers.len(), 1);
+    }
+
+    #[test]
+    fn non_interactive_mode_uses_env_then_initial() {
+        let questions = vec![
+            Question::text("first", "First?").into(),
+            Question::text("second", "Second?")
+                .initial("initial")
+                .into(),
+        ];
+        let config = PromptConfig {
+            mode: Mode::Auto,
+            env_prefix: Some("APP_".into()),
+            env: Env::Custom(Arc::new(|name| {
+                (name == "APP_FIRST").then(|| "from env".to_string())
+            })),
+            ..Default::default()
+        };
+        let mut term = MemoryTerminal::new("typed\n");
+        let answers = prompt_with(&questions, &mut term, &config).unwrap();
+        assert_eq!(
+            answers.get("first"),
+            Some(&PromptValue::String("from env".into()))
+        );
+        assert_eq!(
+            answers.get("second"),
+            Some(&PromptValue::String("initial".into()))
+        );
+        assert_eq!(term.output(), "");
+    }
+
+    #[test]
+    fn non_interactive_mode_names_every_unanswered_question() {
+        let questions = vec![
+            Question::text("host", "Host?").into(),
+            Question::confirm("tls", "TLS?").initial(true).into(),
+            Question::number("port", "Port?").into(),
+        ];
+        let config = PromptConfig {
+            mode: Mode::NonInteractive,
+            ..Default::default()
+        };
+        let mut term = MemoryTerminal::new("").tty(true);
+        let e = prompt_with(&questions, &mut term, &config).unwrap_err();
+        assert!(matches!(&e, Error::Unanswered { names } if names == &["host", "port"]));
+    }
+
+    #[test]
+    fn auto_mode_prompts_on_a_tty() {
+        let config = PromptConfig {
+            mode: Mode::Auto,
+            ..Default::default()
+        };
+        let mut term = MemoryTerminal::new("one\rtwo\r").tty(true);
+        let answers = prompt_with(&two_text_questions(), &mut term, &config).unwrap();
+        assert_eq!(answers.len(), 2);
+    }
 }
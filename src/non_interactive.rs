@@ -0,0 +1,358 @@
+//! Answers for questions asked without a terminal: environment variables, then initial values.
+
+use crate::elements::{
+    DatePromptOptions, NumberPromptOptions, out_of_range, parse_date, parse_number,
+};
+use crate::error::{Error, Result};
+use crate::prompts::{PromptValue, Question, split_list};
+use chrono::Local;
+use std::fmt;
+use std::sync::Arc;
+
+/// Whether `prompt_with` asks the user or resolves answers on its own.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Mode {
+    /// Always ask, reading answers from the terminal, even when it is not a TTY.
+    Interactive,
+    /// Ask on a TTY; otherwise behave like `NonInteractive`.
+    #[default]
+    Auto,
+    /// Never ask: answer from the environment, then from the initial value.
+    NonInteractive,
+}
+
+impl Mode {
+    /// Whether questions are answered without asking on a terminal with the given `is_tty`.
+    pub(crate) fn skips_terminal(self, is_tty: bool) -> bool {
+        match self {
+            Mode::Interactive => false,
+            Mode::Auto => !is_tty,
+            Mode::NonInteractive => true,
+        }
+    }
+}
+
+/// Function returning the value of the environment variable with the given name.
+pub type EnvFn = Arc<dyn Fn(&str) -> Option<String> + Send + Sync>;
+
+/// Where answers are looked up when questions are not asked.
+#[derive(Clone, Default)]
+pub enum Env {
+    /// The process environment.
+    #[default]
+    Process,
+    /// Variables returned by a function, for example a map in tests.
+    Custom(EnvFn),
+}
+
+impl fmt::Debug for Env {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Env::Process => f.write_str("Process"),
+            Env::Custom(_) => f.write_str("Custom(..)"),
+        }
+    }
+}
+
+impl Env {
+    /// Value of the variable `name`, or `None` when it is unset or not unicode.
+    pub fn var(&self, name: &str) -> Option<String> {
+        match self {
+            Env::Process => std::env::var(name).ok(),
+            Env::Custom(var) => var(name),
+        }
+    }
+}
+
+/// Environment variable holding the answer to the question named `name`.
+///
+/// The name is upper-cased and `-` becomes `_`: prefix `APP_` and `db-host` give `APP_DB_HOST`.
+pub fn env_var_name(prefix: &str, name: &str) -> String {
+    format!("{}{}", prefix, name.to_uppercase().replace('-', "_"))
+}
+
+impl Question {
+    /// Answer without asking: `env` parsed for the question's type, else the initial value.
+    ///
+    /// Returns `None` when there is neither; an `env` value the type cannot parse is an
+    /// `Error::Validation`.
+    pub(crate) fn unattended_answer(&self, env: Option<&str>) -> Result<Option<PromptValue>> {
+        match env {
+            Some(raw) => self.parse_answer(raw).map(Some),
+            None => Ok(self.initial_answer()),
+        }
+    }
+
+    fn parse_answer(&self, raw: &str) -> Result<PromptValue> {
+        let invalid = |message: String| Error::Validation {
+            name: self.name.clone(),
+            message,
+        };
+        let trimmed = raw.trim();
+        match self.type_name.as_str() {
+            "number" => {
+                let opts = NumberPromptOptions {
+                    min: self.min,
+                    max: self.max,
+                    float: self.float,
+                    round: self.round,
+                    error_msg: Some(format!("'{}' is not a valid number", trimmed)),
+                    ..Default::default()
+                };
+                parse_number(&opts, trimmed)
+                    .map(PromptValue::Float)
+                    .map_err(invalid)
+            }
+            "confirm" | "toggle" => match trimmed.to_ascii_lowercase().as_str() {
+                "y" | "yes" | "true" | "on" | "1" => Ok(PromptValue::Bool(true)),
+                "n" | "no" | "false" | "off" | "0" => Ok(PromptValue::Bool(false)),
+                _ => Err(invalid(format!("'{}' is not yes or no", trimmed))),
+            },
+            "select" | "autocomplete" => {
+                let choices = self.choices.as_deref().unwrap_or_default();
+                let found = match trimmed.parse::<usize>() {
+                    Ok(n) if self.type_name == "select" && (1..=choices.len()).contains(&n) => {
+                        Some(&choices[n - 1])
+                    }
+                    _ => choices.iter().find(|c| {
+                        c.value.eq_ignore_ascii_case(trimmed)
+                            || c.title.eq_ignore_ascii_case(trimmed)
+                    }),
+                };
+                match found {
+                    Some(c) if !c.disabled => Ok(PromptValue::String(c.value.clone())),
+                    Some(c) => Err(invalid(format!("'{}' is disabled", c.title))),
+                    None if self.allow_custom => Ok(PromptValue::String(trimmed.to_string())),
+                    None => Err(invalid(format!("'{}' is not one of the choices", trimmed))),
+                }
+            }
+            "multiselect" => {
+                let choices = self.choices.as_deref().unwrap_or_default();
+                let mut values = Vec::new();
+                for item in split_list(trimmed, ",")
+                    .into_iter()
+                    .filter(|i| !i.is_empty())
+                {
+                    let found = choices.iter().find(|c| {
+                        c.value.eq_ignore_ascii_case(&item) || c.title.eq_ignore_ascii_case(&item)
+                    });
+                    match found {
+                        Some(c) if !c.disabled => values.push(c.value.clone()),
+                        Some(c) => return Err(invalid(format!("'{}' is disabled", c.title))),
+                        None => {
+                            return Err(invalid(format!("'{}' is not one of the choices", item)));
+                        }
+                    }
+                }
+                let count = values.len() as f64;
+                if self.min.is_some_and(|min| count < min)
+                    || self.max.is_some_and(|max| count > max)
+                {
+                    return Err(invalid(format!(
+                        "'{}' has the wrong number of choices",
+                        trimmed
+                    )));
+                }
+                Ok(PromptValue::List(values))
+            }
+            "date" => {
+                let mask = self
+                    .mask
+                    .clone()
+                    .unwrap_or_else(|| DatePromptOptions::default().mask);
+                let date = parse_date(trimmed, &mask, Local::now().naive_local())
+                    .ok_or_else(|| invalid(format!("'{}' is not a date", trimmed)))?;
+                match out_of_range(date, self.min_date, self.max_date, &mask) {
+                    Some(message) => Err(invalid(message)),
+                    None => Ok(PromptValue::Date(date)),
+                }
+            }
+            "list" => Ok(PromptValue::List(split_list(
+                trimmed,
+                self.separator.as_deref().unwrap_or(","),
+            ))),
+            _ => Ok(PromptValue::String(raw.to_string())),
+        }
+    }
+
+    fn initial_answer(&self) -> Option<PromptValue> {
+        match self.type_name.as_str() {
+            "number" => self.initial_number.map(PromptValue::Float),
+            "confirm" | "toggle" => self.initial_bool.map(PromptValue::Bool),
+            "select" | "autocomplete" => {
+                let choice = self.choices.as_ref()?.get(self.initial_choice?)?;
+                Some(PromptValue::String(choice.value.clone()))
+            }
+            "multiselect" => self.choices.as_ref().map(|choices| {
+                PromptValue::List(
+                    choices
+                        .iter()
+                        .filter(|c| c.selected)
+                        .map(|c| c.value.clone())
+                        .collect(),
+                )
+            }),
+            "date" => self.initial_date.map(PromptValue::Date),
+            "list" => self.initial_text.as_deref().map(|text| {
+                PromptValue::List(split_list(text, self.separator.as_deref().unwrap_or(",")))
+            }),
+            _ => self.initial_text.clone().map(PromptValue::String),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::elements::Choice;
+
+    #[test]
+    fn mode_auto_follows_tty() {
+        assert_eq!(Mode::default(), Mode::Auto);
+        assert!(!Mode::Interactive.skips_terminal(false));
+        assert!(Mode::Auto.skips_terminal(false));
+        assert!(!Mode::Auto.skips_terminal(true));
+        assert!(Mode::NonInteractive.skips_terminal(true));
+    }
+
+    #[test]
+    fn env_var_name_upper_cases_name() {
+        assert_eq!(env_var_name("APP_", "db-host"), "APP_DB_HOST");
+        assert_eq!(env_var_name("", "port"), "PORT");
+    }
+
+    #[test]
+    fn env_value_wins_over_initial() {
+        let q: Question = Question::text("name", "Name?").initial("anon").into();
+        assert_eq!(
+            q.unattended_answer(Some("Bob")).unwrap(),
+            Some(PromptValue::String("Bob".into()))
+        );
+        assert_eq!(
+            q.unattended_answer(None).unwrap(),
+            Some(PromptValue::String("anon".into()))
+        );
+    }
+
+    #[test]
+    fn missing_initial_is_unanswered() {
+        let q: Question = Question::confirm("ok", "Ok?").into();
+        assert_eq!(q.unattended_answer(None).unwrap(), None);
+        let q: Question = Question::select("s", "S?").choice("A", "a").into();
+        assert_eq!(q.unattended_answer(None).unwrap(), None);
+    }
+
+    #[test]
+    fn env_values_are_parsed_per_type() {
+        let q: Question = Question::number("port", "Port?").max(9000).into();
+        assert_eq!(
+            q.unattended_answer(Some(" 8080 ")).unwrap(),
+            Some(PromptValue::Float(8080.0))
+        );
+        assert_eq!(
+            q.unattended_answer(Some("99999")).unwrap(),
+            Some(PromptValue::Float(9000.0))
+        );
+        let e = q.unattended_answer(Some("http")).unwrap_err();
+        assert_eq!(e.to_string(), "port: 'http' is not a valid number");
+
+        let q: Question = Question::toggle("t", "T?").into();
+        assert_eq!(
+            q.unattended_answer(Some("off")).unwrap(),
+            Some(PromptValue::Bool(false))
+        );
+        assert!(q.unattended_answer(Some("maybe")).is_err());
+
+        let q: Question = Question::list("l", "L?").separator(";").into();
+        assert_eq!(
+            q.unattended_answer(Some("a; b")).unwrap(),
+            Some(PromptValue::List(vec!["a".into(), "b".into()]))
+        );
+    }
+
+    #[test]
+    fn select_env_value_matches_value_title_or_index() {
+        let mut disabled = Choice::new("Gamma", "g");
+        disabled.disabled = true;
+        let q: Question = Question::select("s", "S?")
+            .choice("Alpha", "a")
+            .choice("Beta", "b")
+            .with_choice(disabled)
+            .initial(1)
+            .into();
+        for raw in ["b", "BETA", "2"] {
+            assert_eq!(
+                q.unattended_answer(Some(raw)).unwrap(),
+                Some(PromptValue::String("b".into()))
+            );
+        }
+        assert!(q.unattended_answer(Some("g")).is_err());
+        assert!(q.unattended_answer(Some("delta")).is_err());
+        assert_eq!(
+            q.unattended_answer(None).unwrap(),
+            Some(PromptValue::String("b".into()))
+        );
+    }
+
+    #[test]
+    fn autocomplete_env_value_may_be_custom() {
+        let q: Question = Question::autocomplete("a", "A?")
+            .choice("Alpha", "a")
+            .choice("Beta", "b")
+            .into();
+        assert_eq!(
+            q.unattended_answer(Some("beta")).unwrap(),
+            Some(PromptValue::String("b".into()))
+        );
+        assert!(q.unattended_answer(Some("2")).is_err());
+        let q: Question = Question::autocomplete("a", "A?")
+            .choice("Alpha", "a")
+            .allow_custom()
+            .into();
+        assert_eq!(
+            q.unattended_answer(Some("gamma")).unwrap(),
+            Some(PromptValue::String("gamma".into()))
+        );
+    }
+
+    #[test]
+    fn multiselect_env_value_lists_choices() {
+        let mut basil = Choice::new("Basil", "basil");
+        basil.selected = true;
+        let q: Question = Question::multiselect("t", "T?")
+            .choice("Cheese", "cheese")
+            .with_choice(basil)
+            .max(1)
+            .into();
+        assert_eq!(
+            q.unattended_answer(Some("CHEESE")).unwrap(),
+            Some(PromptValue::List(vec!["cheese".into()]))
+        );
+        assert!(q.unattended_answer(Some("cheese, basil")).is_err());
+        assert!(q.unattended_answer(Some("ham")).is_err());
+        assert_eq!(
+            q.unattended_answer(None).unwrap(),
+            Some(PromptValue::List(vec!["basil".into()]))
+        );
+    }
+
+    #[test]
+    fn date_env_value_is_parsed_and_bounded() {
+        let at = |s| chrono::NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M").unwrap();
+        let q: Question = Question::date("d", "D?")
+            .initial(at("2024-03-01 09:00"))
+            .max(at("2024-12-31 00:00"))
+            .into();
+        assert_eq!(
+            q.unattended_answer(Some("2024-06-01 12:00")).unwrap(),
+            Some(PromptValue::Date(at("2024-06-01 12:00")))
+        );
+        let e = q.unattended_answer(Some("2025-01-01 00:00")).unwrap_err();
+        assert_eq!(e.to_string(), "d: Enter 2024-12-31 00:00 or earlier");
+        assert!(q.unattended_answer(Some("someday")).is_err());
+        assert_eq!(
+            q.unattended_answer(None).unwrap(),
+            Some(PromptValue::Date(at("2024-03-01 09:00")))
+        );
+    }
+}
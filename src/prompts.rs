@@ -1,36 +1,239 @@
-//! Prompt type registry and runners.
+//! Questions, answers and the built-in prompt types.
 
+use crate::answers::Answers;
 use crate::elements::*;
+use crate::error::{Error, Result};
+use crate::history::History;
+use crate::non_interactive::{Env, Mode};
+use crate::registry::PromptRegistry;
+use crate::terminal::Terminal;
+use crate::util::action::Key;
 use crate::util::style::InputStyle;
-use std::io::{self, BufRead, Write};
+use chrono::NaiveDateTime;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
 
-/// Result value of a single prompt (string, bool, float, or list).
+/// Result value of a single prompt (string, bool, float, list, or date).
+///
+/// With the `serde` feature it (de)serializes untagged, as a plain string, bool, number or array;
+/// a date is written as an ISO 8601 string and read back as a `String`.
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(untagged))]
 pub enum PromptValue {
     String(String),
     Bool(bool),
     Float(f64),
     List(Vec<String>),
+    Date(NaiveDateTime),
+}
+
+impl From<String> for PromptValue {
+    fn from(s: String) -> Self {
+        PromptValue::String(s)
+    }
+}
+
+impl From<&str> for PromptValue {
+    fn from(s: &str) -> Self {
+        PromptValue::String(s.to_string())
+    }
+}
+
+impl From<bool> for PromptValue {
+    fn from(b: bool) -> Self {
+        PromptValue::Bool(b)
+    }
+}
+
+impl From<f64> for PromptValue {
+    fn from(n: f64) -> Self {
+        PromptValue::Float(n)
+    }
+}
+
+impl From<Vec<String>> for PromptValue {
+    fn from(list: Vec<String>) -> Self {
+        PromptValue::List(list)
+    }
+}
+
+impl From<NaiveDateTime> for PromptValue {
+    fn from(date: NaiveDateTime) -> Self {
+        PromptValue::Date(date)
+    }
+}
+
+/// Validation hook: gets the answer and the answers given so far; `Err` holds the message shown
+/// under the input.
+pub type Validator =
+    Arc<dyn Fn(&PromptValue, &Answers) -> std::result::Result<(), String> + Send + Sync>;
+
+/// Format hook: turns the submitted answer into the value stored in the answers.
+pub type Formatter = Arc<dyn Fn(PromptValue, &Answers) -> PromptValue + Send + Sync>;
+
+/// Display hook: text shown after the message once the question is answered.
+pub type Displayer = Arc<dyn Fn(&PromptValue) -> String + Send + Sync>;
+
+/// Field computed from the answers given so far.
+pub type Computed<T> = Arc<dyn Fn(&Answers) -> T + Send + Sync>;
+
+/// Question fields computed from earlier answers, right before the question is asked.
+///
+/// A computed field replaces its static counterpart on `Question`.
+#[derive(Clone, Default)]
+pub struct Dynamic {
+    /// Prompt type; `None` skips the question.
+    pub type_name: Option<Computed<Option<String>>>,
+    pub message: Option<Computed<String>>,
+    /// Initial answer, stored in the `initial_*` field matching the value.
+    pub initial: Option<Computed<Option<PromptValue>>>,
+    pub choices: Option<Computed<Vec<Choice>>>,
+    pub min: Option<Computed<Option<f64>>>,
+    pub max: Option<Computed<Option<f64>>>,
+    pub hint: Option<Computed<Option<String>>>,
 }
 
 /// Question configuration for the sequential prompt flow.
+///
+/// Prefer the typed builders (`Question::text`, `Question::number`, ...); filling the struct
+/// directly remains supported.
+///
+/// With the `serde` feature the fields without closures (de)serialize, `type_name` as `type`;
+/// missing fields take their default.
+#[derive(Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(default, deny_unknown_fields)
+)]
 pub struct Question {
     pub name: String,
+    #[cfg_attr(feature = "serde", serde(rename = "type"))]
     pub type_name: String,
     pub message: String,
     pub initial_text: Option<String>,
     pub initial_number: Option<f64>,
     pub initial_bool: Option<bool>,
+    pub initial_date: Option<NaiveDateTime>,
     pub choices: Option<Vec<Choice>>,
+    pub initial_choice: Option<usize>,
     pub style: InputStyle,
     pub separator: Option<String>,
     pub float: bool,
     pub round: u32,
+    /// Smallest `number`, or fewest `multiselect` choices.
     pub min: Option<f64>,
+    /// Largest `number`, or most `multiselect` choices.
     pub max: Option<f64>,
+    /// Earliest `date`.
+    pub min_date: Option<NaiveDateTime>,
+    /// Latest `date`.
+    pub max_date: Option<NaiveDateTime>,
+    /// Layout of a `date`, e.g. `DD.MM.YYYY`; defaults to `YYYY-MM-DD HH:mm`.
+    pub mask: Option<String>,
     pub active: Option<String>,
     pub inactive: Option<String>,
     pub hint: Option<String>,
+    /// How `autocomplete` input filters the choices.
+    pub matcher: Matcher,
+    /// Lets `autocomplete` accept input that is not one of the choices.
+    pub allow_custom: bool,
+    /// Starts text, number and list input with the initial value, ready to edit.
+    pub prefill: bool,
+    /// Remembers text and list answers in `PromptConfig::history`; Up and Down recall them.
+    /// Password and invisible input is never remembered.
+    pub history: bool,
+    /// Lets `text` input span several lines: Enter starts a new line and Ctrl-D submits.
+    pub multiline: bool,
+    /// Another key that submits multiline input, besides Ctrl-D.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub submit_key: Option<Key>,
+    /// Command the `editor` type runs, e.g. `code --wait`; defaults to `$VISUAL`, then `$EDITOR`.
+    pub editor: Option<String>,
+    /// Lines of `editor` input starting with this prefix, e.g. `#`, are dropped.
+    pub comment: Option<String>,
+    /// Rejects an answer with a message; the question is then asked again.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub validate: Option<Validator>,
+    /// Rejected answers allowed before giving up with `Error::Validation`; `None` retries forever.
+    pub max_attempts: Option<u32>,
+    /// Converts the answer after validation, before it is stored.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub format: Option<Formatter>,
+    /// Replaces the answer printed on the done line; gets the formatted value.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub display: Option<Displayer>,
+    /// Fields computed from earlier answers.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub dynamic: Dynamic,
+    /// Time left to answer; a countdown is shown and any key stops it. The `editor` type has none.
+    #[cfg_attr(feature = "serde", serde(with = "secs"))]
+    pub timeout: Option<Duration>,
+    /// What happens when `timeout` runs out.
+    pub on_timeout: OnTimeout,
+}
+
+/// (De)serializes a timeout as a number of seconds.
+#[cfg(feature = "serde")]
+mod secs {
+    use serde::{Deserialize, Deserializer, Serializer};
+    use std::time::Duration;
+
+    pub fn serialize<S: Serializer>(timeout: &Option<Duration>, s: S) -> Result<S::Ok, S::Error> {
+        match timeout {
+            Some(d) => s.serialize_some(&d.as_secs_f64()),
+            None => s.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<Option<Duration>, D::Error> {
+        Option::<f64>::deserialize(d)?
+            .map(|secs| Duration::try_from_secs_f64(secs).map_err(serde::de::Error::custom))
+            .transpose()
+    }
+}
+
+impl fmt::Debug for Question {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Question")
+            .field("name", &self.name)
+            .field("type_name", &self.type_name)
+            .field("message", &self.message)
+            .field("initial_text", &self.initial_text)
+            .field("initial_number", &self.initial_number)
+            .field("initial_bool", &self.initial_bool)
+            .field("initial_date", &self.initial_date)
+            .field("choices", &self.choices.as_ref().map(Vec::len))
+            .field("initial_choice", &self.initial_choice)
+            .field("style", &self.style)
+            .field("separator", &self.separator)
+            .field("float", &self.float)
+            .field("round", &self.round)
+            .field("min", &self.min)
+            .field("max", &self.max)
+            .field("min_date", &self.min_date)
+            .field("max_date", &self.max_date)
+            .field("mask", &self.mask)
+            .field("active", &self.active)
+            .field("inactive", &self.inactive)
+            .field("hint", &self.hint)
+            .field("matcher", &self.matcher)
+            .field("allow_custom", &self.allow_custom)
+            .field("prefill", &self.prefill)
+            .field("history", &self.history)
+            .field("multiline", &self.multiline)
+            .field("submit_key", &self.submit_key)
+            .field("editor", &self.editor)
+            .field("comment", &self.comment)
+            .field("max_attempts", &self.max_attempts)
+            .field("timeout", &self.timeout)
+            .field("on_timeout", &self.on_timeout)
+            .finish_non_exhaustive()
+    }
 }
 
 impl Default for Question {
@@ -42,66 +245,282 @@ impl Default for Question {
             initial_text: None,
             initial_number: None,
             initial_bool: None,
+            initial_date: None,
             choices: None,
+            initial_choice: None,
             style: InputStyle::Default,
             separator: None,
             float: false,
             round: 2,
             min: None,
             max: None,
+            min_date: None,
+            max_date: None,
+            mask: None,
             active: None,
             inactive: None,
             hint: None,
+            matcher: Matcher::default(),
+            allow_custom: false,
+            prefill: false,
+            history: false,
+            multiline: false,
+            submit_key: None,
+            editor: None,
+            comment: None,
+            validate: None,
+            max_attempts: None,
+            format: None,
+            display: None,
+            dynamic: Dynamic::default(),
+            timeout: None,
+            on_timeout: OnTimeout::default(),
         }
     }
 }
 
+impl Question {
+    /// Whether answers are remembered: `history` is set and the input is shown as typed.
+    pub(crate) fn keeps_history(&self) -> bool {
+        self.history
+            && match self.type_name.as_str() {
+                "text" => self.style == InputStyle::Default && !self.multiline,
+                "list" => true,
+                _ => false,
+            }
+    }
+
+    /// Returns the question with its dynamic fields computed from `answers`.
+    ///
+    /// A computed type of `None` yields an empty `type_name`, so the question is skipped.
+    pub(crate) fn resolve(&self, answers: &Answers) -> Question {
+        let d = &self.dynamic;
+        let mut q = Question {
+            dynamic: Dynamic::default(),
+            ..self.clone()
+        };
+        if let Some(f) = &d.type_name {
+            q.type_name = f(answers).unwrap_or_default();
+        }
+        if let Some(f) = &d.message {
+            q.message = f(answers);
+        }
+        if let Some(f) = &d.choices {
+            q.choices = Some(f(answers));
+        }
+        if let Some(f) = &d.initial {
+            q.set_initial(f(answers));
+        }
+        if let Some(f) = &d.min {
+            q.min = f(answers);
+        }
+        if let Some(f) = &d.max {
+            q.max = f(answers);
+        }
+        if let Some(f) = &d.hint {
+            q.hint = f(answers);
+        }
+        q
+    }
+
+    /// Checks an answer given without prompting, then converts it like a typed one.
+    pub(crate) fn accept(&self, value: PromptValue, answers: &Answers) -> Result<PromptValue> {
+        if let Some(validate) = &self.validate {
+            validate(&value, answers).map_err(|message| Error::Validation {
+                name: self.name.clone(),
+                message,
+            })?;
+        }
+        Ok(self.formatted(value, answers))
+    }
+
+    /// Converts a validated answer with `format`.
+    pub(crate) fn formatted(&self, value: PromptValue, answers: &Answers) -> PromptValue {
+        match &self.format {
+            Some(format) => format(value, answers),
+            None => value,
+        }
+    }
+
+    /// Stores `initial` in the field its type uses; a select gets the index of the matching
+    /// choice value, and a multiselect checks the choices whose values are listed.
+    pub fn set_initial(&mut self, initial: Option<PromptValue>) {
+        match initial {
+            Some(PromptValue::String(s))
+                if matches!(self.type_name.as_str(), "select" | "autocomplete") =>
+            {
+                self.initial_choice = self.choices.iter().flatten().position(|c| c.value == s);
+            }
+            Some(PromptValue::String(s)) => self.initial_text = Some(s),
+            Some(PromptValue::Bool(b)) => self.initial_bool = Some(b),
+            Some(PromptValue::Float(n)) => self.initial_number = Some(n),
+            Some(PromptValue::Date(d)) => self.initial_date = Some(d),
+            Some(PromptValue::List(items)) if self.type_name == "multiselect" => {
+                for c in self.choices.iter_mut().flatten() {
+                    c.selected = items.contains(&c.value);
+                }
+            }
+            Some(PromptValue::List(items)) => {
+                let sep = self.separator.as_deref().unwrap_or(",");
+                self.initial_text = Some(items.join(sep));
+            }
+            None => {
+                self.initial_text = None;
+                self.initial_bool = None;
+                self.initial_number = None;
+                self.initial_date = None;
+                self.initial_choice = None;
+            }
+        }
+    }
+}
+
+/// What `prompt_with` does when a question is cancelled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OnCancel {
+    /// Stop and return an error; later questions are not asked.
+    #[default]
+    Abort,
+    /// Leave the question unanswered and continue with the next one.
+    Skip,
+}
+
+/// What a prompt does when its timeout runs out.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(rename_all = "lowercase")
+)]
+pub enum OnTimeout {
+    /// Submit the initial value, as if Enter was pressed.
+    #[default]
+    Initial,
+    /// Cancel the prompt; `prompt_with` then follows `on_cancel`, failing with `Error::Timeout`.
+    Cancel,
+}
+
+/// Options for a `prompt_with` call.
+#[derive(Debug, Clone, Default)]
+pub struct PromptConfig {
+    pub on_cancel: OnCancel,
+    /// Prompt types available to the questions.
+    pub registry: PromptRegistry,
+    /// Answers by question name, used instead of asking.
+    pub answers: HashMap<String, Injected>,
+    /// Answers used in order for questions without an entry in `answers`.
+    pub inject: Vec<Injected>,
+    /// Whether questions are asked or answered from the environment and initial values.
+    pub mode: Mode,
+    /// Prefix of the environment variables read when not asking; `None` reads none.
+    pub env_prefix: Option<String>,
+    /// Where the variables named by `env_prefix` are read from.
+    pub env: Env,
+    /// Where answers to questions with `Question::history` are remembered; `None` keeps none.
+    /// `prompt` uses `History::default()`.
+    pub history: Option<History>,
+    /// Cancels the question `prompt_with_async` is asking, as Ctrl-C would.
+    #[cfg(feature = "tokio")]
+    pub cancel: Option<tokio_util::sync::CancellationToken>,
+}
+
+/// Answer given to `prompt_with` instead of asking the user.
+///
+/// Values still go through the question's `validate` and `format` hooks; a rejected value is
+/// an `Error::Validation`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Injected {
+    Answer(PromptValue),
+    /// Behaves as if the user aborted the question with Ctrl-C.
+    Cancel,
+}
+
+impl<T: Into<PromptValue>> From<T> for Injected {
+    fn from(value: T) -> Self {
+        Injected::Answer(value.into())
+    }
+}
+
 /// Runs a prompt by `type_name`. Returns `Some(value)` on success or `None` on cancel.
 #[inline]
-pub fn run_prompt<R: BufRead, W: Write>(
+pub fn run_prompt<T: Terminal + ?Sized>(q: &Question, term: &mut T) -> Result<Option<PromptValue>> {
+    PromptRegistry::new().run(q, term, &Answers::new())
+}
+
+/// Splits `list` input on `sep`, trimming each item.
+pub(crate) fn split_list(input: &str, sep: &str) -> Vec<String> {
+    input.split(sep).map(|x| x.trim().to_string()).collect()
+}
+
+/// Built-in prompt for `q.type_name`, or `None` when the type is not built in.
+///
+/// `history` holds the earlier answers recalled with Up and Down, oldest first.
+pub(crate) fn builtin(
     q: &Question,
-    stdin: &mut R,
-    stdout: &mut W,
-) -> io::Result<Option<PromptValue>> {
-    match q.type_name.as_str() {
+    history: &[String],
+) -> Option<Box<dyn Prompt<Output = PromptValue>>> {
+    let prompt: Box<dyn Prompt<Output = PromptValue>> = match q.type_name.as_str() {
         "text" => {
             let opts = TextPromptOptions {
                 message: q.message.clone(),
                 initial: q.initial_text.clone(),
+                prefill: q.prefill,
+                history: history.to_vec(),
+                multiline: q.multiline,
+                submit_key: q.submit_key.clone(),
                 style: q.style,
                 error_msg: None,
+                ..Default::default()
             };
-            run_text(&opts, stdin, stdout).map(|s| Some(PromptValue::String(s)))
+            Box::new(Map {
+                prompt: TextPrompt::new(opts),
+                f: PromptValue::String,
+            })
         }
         "password" => {
             let opts = TextPromptOptions {
                 message: q.message.clone(),
                 initial: q.initial_text.clone(),
+                prefill: q.prefill,
                 style: InputStyle::Password,
                 error_msg: None,
+                ..Default::default()
             };
-            run_text(&opts, stdin, stdout).map(|s| Some(PromptValue::String(s)))
+            Box::new(Map {
+                prompt: TextPrompt::new(opts),
+                f: PromptValue::String,
+            })
         }
         "invisible" => {
             let opts = TextPromptOptions {
                 message: q.message.clone(),
                 initial: q.initial_text.clone(),
+                prefill: q.prefill,
                 style: InputStyle::Invisible,
                 error_msg: None,
+                ..Default::default()
             };
-            run_text(&opts, stdin, stdout).map(|s| Some(PromptValue::String(s)))
+            Box::new(Map {
+                prompt: TextPrompt::new(opts),
+                f: PromptValue::String,
+            })
         }
         "number" => {
             let opts = NumberPromptOptions {
                 message: q.message.clone(),
                 initial: q.initial_number,
+                prefill: q.prefill,
                 min: q.min,
                 max: q.max,
                 float: q.float,
                 round: q.round,
                 error_msg: None,
+                ..Default::default()
             };
-            run_number(&opts, stdin, stdout).map(|n| Some(PromptValue::Float(n)))
+            Box::new(Map {
+                prompt: NumberPrompt::new(opts),
+                f: PromptValue::Float,
+            })
         }
         "confirm" => {
             let opts = ConfirmPromptOptions {
@@ -109,7 +528,10 @@ pub fn run_prompt<R: BufRead, W: Write>(
                 initial: q.initial_bool.unwrap_or(false),
                 ..Default::default()
             };
-            run_confirm(&opts, stdin, stdout).map(|b| Some(PromptValue::Bool(b)))
+            Box::new(Map {
+                prompt: ConfirmPrompt::new(opts),
+                f: PromptValue::Bool,
+            })
         }
         "toggle" => {
             let opts = TogglePromptOptions {
@@ -117,43 +539,112 @@ pub fn run_prompt<R: BufRead, W: Write>(
                 initial: q.initial_bool.unwrap_or(false),
                 active: q.active.clone().unwrap_or_else(|| "on".into()),
                 inactive: q.inactive.clone().unwrap_or_else(|| "off".into()),
+                ..Default::default()
             };
-            run_toggle(&opts, stdin, stdout).map(|b| Some(PromptValue::Bool(b)))
+            Box::new(Map {
+                prompt: TogglePrompt::new(opts),
+                f: PromptValue::Bool,
+            })
         }
         "select" => {
             let choices = q.choices.clone().unwrap_or_default();
             let opts = SelectPromptOptions {
                 message: q.message.clone(),
                 choices,
-                initial: None,
+                initial: q.initial_choice,
+                hint: q.hint.clone(),
+                ..Default::default()
+            };
+            Box::new(Map {
+                prompt: SelectPrompt::new(opts),
+                f: PromptValue::String,
+            })
+        }
+        "autocomplete" => {
+            let opts = AutocompletePromptOptions {
+                message: q.message.clone(),
+                choices: q.choices.clone().unwrap_or_default(),
+                initial: q.initial_choice,
+                matcher: q.matcher.clone(),
+                allow_custom: q.allow_custom,
+                hint: q.hint.clone(),
+                ..Default::default()
+            };
+            Box::new(Map {
+                prompt: AutocompletePrompt::new(opts),
+                f: PromptValue::String,
+            })
+        }
+        "multiselect" => {
+            let opts = MultiselectPromptOptions {
+                message: q.message.clone(),
+                choices: q.choices.clone().unwrap_or_default(),
+                initial: q.initial_choice,
+                min: q.min.map(|n| n as usize),
+                max: q.max.map(|n| n as usize),
                 hint: q.hint.clone(),
+                ..Default::default()
             };
-            run_select(&opts, stdin, stdout).map(|s| Some(PromptValue::String(s)))
+            Box::new(Map {
+                prompt: MultiselectPrompt::new(opts),
+                f: PromptValue::List,
+            })
+        }
+        "date" => {
+            let opts = DatePromptOptions {
+                message: q.message.clone(),
+                initial: q.initial_date,
+                mask: q
+                    .mask
+                    .clone()
+                    .unwrap_or_else(|| DatePromptOptions::default().mask),
+                min: q.min_date,
+                max: q.max_date,
+                hint: q.hint.clone(),
+                ..Default::default()
+            };
+            Box::new(Map {
+                prompt: DatePrompt::new(opts),
+                f: PromptValue::Date,
+            })
         }
         "list" => {
-            let sep = q.separator.as_deref().unwrap_or(",");
+            let sep = q.separator.clone().unwrap_or_else(|| ",".into());
             let opts = TextPromptOptions {
                 message: q.message.clone(),
                 initial: q.initial_text.clone(),
+                prefill: q.prefill,
+                history: history.to_vec(),
                 style: InputStyle::Default,
                 error_msg: None,
+                ..Default::default()
             };
-            run_text(&opts, stdin, stdout).map(|s| {
-                let list = s.split(sep).map(|x| x.trim().to_string()).collect();
-                Some(PromptValue::List(list))
+            Box::new(Map {
+                prompt: TextPrompt::new(opts),
+                f: move |s: String| PromptValue::List(split_list(&s, &sep)),
             })
         }
-        _ => Err(io::Error::new(
-            io::ErrorKind::InvalidInput,
-            format!("prompt type '{}' is not defined", q.type_name),
-        )),
+        _ => return None,
+    };
+    Some(prompt)
+}
+
+/// Options for an `editor` question; it is run by the registry rather than driven as a `Prompt`.
+pub(crate) fn editor_options(q: &Question) -> EditorPromptOptions {
+    EditorPromptOptions {
+        message: q.message.clone(),
+        initial: q.initial_text.clone(),
+        command: q.editor.clone(),
+        comment: q.comment.clone(),
+        ..Default::default()
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::io::Cursor;
+    use crate::error::Error;
+    use crate::terminal::MemoryTerminal;
 
     #[test]
     fn question_default_values() {
@@ -184,9 +675,8 @@ mod tests {
             initial_text: Some("default".into()),
             ..Default::default()
         };
-        let mut stdin = Cursor::new(b"Alice\n");
-        let mut stdout = Vec::new();
-        let out = run_prompt(&q, &mut stdin, &mut stdout);
+        let mut term = MemoryTerminal::new("Alice\n");
+        let out = run_prompt(&q, &mut term);
         assert!(out.is_ok());
         let val = out.unwrap();
         assert!(matches!(val, Some(PromptValue::String(s)) if s == "Alice"));
@@ -201,9 +691,8 @@ mod tests {
             initial_text: Some("init".into()),
             ..Default::default()
         };
-        let mut stdin = Cursor::new(b"\n");
-        let mut stdout = Vec::new();
-        let out = run_prompt(&q, &mut stdin, &mut stdout);
+        let mut term = MemoryTerminal::new("\n");
+        let out = run_prompt(&q, &mut term);
         assert!(out.is_ok());
         let val = out.unwrap();
         assert!(matches!(val, Some(PromptValue::String(s)) if s == "init"));
@@ -218,9 +707,8 @@ mod tests {
             initial_bool: Some(false),
             ..Default::default()
         };
-        let mut stdin = Cursor::new(b"y\n");
-        let mut stdout = Vec::new();
-        let out = run_prompt(&q, &mut stdin, &mut stdout);
+        let mut term = MemoryTerminal::new("y\n");
+        let out = run_prompt(&q, &mut term);
         assert!(out.is_ok());
         assert!(matches!(out.unwrap(), Some(PromptValue::Bool(true))));
     }
@@ -234,9 +722,8 @@ mod tests {
             initial_bool: Some(true),
             ..Default::default()
         };
-        let mut stdin = Cursor::new(b"n\n");
-        let mut stdout = Vec::new();
-        let out = run_prompt(&q, &mut stdin, &mut stdout);
+        let mut term = MemoryTerminal::new("n\n");
+        let out = run_prompt(&q, &mut term);
         assert!(out.is_ok());
         assert!(matches!(out.unwrap(), Some(PromptValue::Bool(false))));
     }
@@ -250,9 +737,8 @@ mod tests {
             float: false,
             ..Default::default()
         };
-        let mut stdin = Cursor::new(b"42\n");
-        let mut stdout = Vec::new();
-        let out = run_prompt(&q, &mut stdin, &mut stdout);
+        let mut term = MemoryTerminal::new("42\n");
+        let out = run_prompt(&q, &mut term);
         assert!(out.is_ok());
         assert!(matches!(out.unwrap(), Some(PromptValue::Float(x)) if x == 42.0));
     }
@@ -267,13 +753,12 @@ mod tests {
             round: 2,
             ..Default::default()
         };
-        let mut stdin = Cursor::new(b"3.14\n");
-        let mut stdout = Vec::new();
-        let out = run_prompt(&q, &mut stdin, &mut stdout);
+        let mut term = MemoryTerminal::new("1.23\n");
+        let out = run_prompt(&q, &mut term);
         assert!(out.is_ok());
         let v = out.unwrap().unwrap();
         if let PromptValue::Float(x) = v {
-            assert!((x - 3.14).abs() < 0.01);
+            assert!((x - 1.23).abs() < 0.01);
         } else {
             panic!("expected Float");
         }
@@ -290,9 +775,8 @@ mod tests {
             inactive: Some("off".into()),
             ..Default::default()
         };
-        let mut stdin = Cursor::new(b"y\n");
-        let mut stdout = Vec::new();
-        let out = run_prompt(&q, &mut stdin, &mut stdout);
+        let mut term = MemoryTerminal::new("y\n");
+        let out = run_prompt(&q, &mut term);
         assert!(out.is_ok());
         assert!(matches!(out.unwrap(), Some(PromptValue::Bool(true))));
     }
@@ -310,9 +794,8 @@ mod tests {
             ]),
             ..Default::default()
         };
-        let mut stdin = Cursor::new(b"2\n");
-        let mut stdout = Vec::new();
-        let out = run_prompt(&q, &mut stdin, &mut stdout);
+        let mut term = MemoryTerminal::new("2\n");
+        let out = run_prompt(&q, &mut term);
         assert!(out.is_ok());
         assert!(matches!(out.unwrap(), Some(PromptValue::String(s)) if s == "b"));
     }
@@ -329,9 +812,8 @@ mod tests {
             ]),
             ..Default::default()
         };
-        let mut stdin = Cursor::new(b"Apple\n");
-        let mut stdout = Vec::new();
-        let out = run_prompt(&q, &mut stdin, &mut stdout);
+        let mut term = MemoryTerminal::new("Apple\n");
+        let out = run_prompt(&q, &mut term);
         assert!(out.is_ok());
         assert!(matches!(out.unwrap(), Some(PromptValue::String(s)) if s == "apple"));
     }
@@ -345,9 +827,8 @@ mod tests {
             separator: Some(",".into()),
             ..Default::default()
         };
-        let mut stdin = Cursor::new(b"a, b , c\n");
-        let mut stdout = Vec::new();
-        let out = run_prompt(&q, &mut stdin, &mut stdout);
+        let mut term = MemoryTerminal::new("a, b , c\n");
+        let out = run_prompt(&q, &mut term);
         assert!(out.is_ok());
         assert!(matches!(out.unwrap(), Some(PromptValue::List(l)) if l == ["a", "b", "c"]));
     }
@@ -360,9 +841,8 @@ mod tests {
             message: "Password?".into(),
             ..Default::default()
         };
-        let mut stdin = Cursor::new(b"secret\n");
-        let mut stdout = Vec::new();
-        let out = run_prompt(&q, &mut stdin, &mut stdout);
+        let mut term = MemoryTerminal::new("secret\n");
+        let out = run_prompt(&q, &mut term);
         assert!(out.is_ok());
         assert!(matches!(out.unwrap(), Some(PromptValue::String(s)) if s == "secret"));
     }
@@ -375,9 +855,8 @@ mod tests {
             message: "Hidden?".into(),
             ..Default::default()
         };
-        let mut stdin = Cursor::new(b"value\n");
-        let mut stdout = Vec::new();
-        let out = run_prompt(&q, &mut stdin, &mut stdout);
+        let mut term = MemoryTerminal::new("value\n");
+        let out = run_prompt(&q, &mut term);
         assert!(out.is_ok());
         assert!(matches!(out.unwrap(), Some(PromptValue::String(s)) if s == "value"));
     }
@@ -390,10 +869,13 @@ mod tests {
             message: "Msg".into(),
             ..Default::default()
         };
-        let mut stdin = Cursor::new(b"");
-        let mut stdout = Vec::new();
-        let out = run_prompt(&q, &mut stdin, &mut stdout);
+        let mut term = MemoryTerminal::new("");
+        let out = run_prompt(&q, &mut term);
         assert!(out.is_err());
+        assert!(matches!(
+            out.unwrap_err(),
+            Error::UnknownPromptType { name, type_name } if name == "x" && type_name == "unknown_type"
+        ));
     }
 
     #[test]
@@ -405,9 +887,8 @@ mod tests {
             separator: None,
             ..Default::default()
         };
-        let mut stdin = Cursor::new(b"a,b,c\n");
-        let mut stdout = Vec::new();
-        let out = run_prompt(&q, &mut stdin, &mut stdout);
+        let mut term = MemoryTerminal::new("a,b,c\n");
+        let out = run_prompt(&q, &mut term);
         assert!(out.is_ok());
         assert!(matches!(
             out.unwrap(),
@@ -424,9 +905,8 @@ mod tests {
             separator: Some(";".into()),
             ..Default::default()
         };
-        let mut stdin = Cursor::new(b"x;y;z\n");
-        let mut stdout = Vec::new();
-        let out = run_prompt(&q, &mut stdin, &mut stdout);
+        let mut term = MemoryTerminal::new("x;y;z\n");
+        let out = run_prompt(&q, &mut term);
         assert!(out.is_ok());
         assert!(matches!(
             out.unwrap(),
@@ -444,9 +924,8 @@ mod tests {
             max: Some(10.0),
             ..Default::default()
         };
-        let mut stdin = Cursor::new(b"100\n");
-        let mut stdout = Vec::new();
-        let out = run_prompt(&q, &mut stdin, &mut stdout);
+        let mut term = MemoryTerminal::new("100\n");
+        let out = run_prompt(&q, &mut term);
         assert!(out.is_ok());
         assert!(matches!(out.unwrap(), Some(PromptValue::Float(x)) if x == 10.0));
     }
@@ -462,10 +941,157 @@ mod tests {
             inactive: Some("no".into()),
             ..Default::default()
         };
-        let mut stdin = Cursor::new(b"y\n");
-        let mut stdout = Vec::new();
-        let out = run_prompt(&q, &mut stdin, &mut stdout);
+        let mut term = MemoryTerminal::new("y\n");
+        let out = run_prompt(&q, &mut term);
         assert!(out.is_ok());
         assert!(matches!(out.unwrap(), Some(PromptValue::Bool(true))));
     }
+
+    #[test]
+    fn run_prompt_cancel_returns_none() {
+        let q = Question {
+            name: "c".into(),
+            type_name: "select".into(),
+            message: "Pick".into(),
+            choices: Some(vec![Choice::new("A", "a")]),
+            ..Default::default()
+        };
+        let mut term = MemoryTerminal::new("\x1b");
+        assert_eq!(run_prompt(&q, &mut term).unwrap(), None);
+        let mut term = MemoryTerminal::new("");
+        assert_eq!(run_prompt(&q, &mut term).unwrap(), None);
+    }
+
+    #[test]
+    fn ask_reports_how_prompt_was_cancelled() {
+        let q = Question {
+            name: "t".into(),
+            type_name: "text".into(),
+            message: "T?".into(),
+            ..Default::default()
+        };
+        let mut term = MemoryTerminal::new("\x03");
+        assert_eq!(
+            PromptRegistry::new()
+                .ask(&q, &mut term, &Answers::new(), None)
+                .unwrap(),
+            Outcome::Cancelled(State::Aborted)
+        );
+        let mut term = MemoryTerminal::new("\x1b");
+        assert_eq!(
+            PromptRegistry::new()
+                .ask(&q, &mut term, &Answers::new(), None)
+                .unwrap(),
+            Outcome::Cancelled(State::Exited)
+        );
+    }
+
+    #[test]
+    fn run_prompt_validation_error_carries_question_name() {
+        let q = Question {
+            name: "port".into(),
+            type_name: "number".into(),
+            message: "Port?".into(),
+            ..Default::default()
+        };
+        let mut term = MemoryTerminal::new("abc\n");
+        let e = run_prompt(&q, &mut term).unwrap_err();
+        assert!(matches!(&e, Error::Validation { name, .. } if name == "port"));
+        assert_eq!(e.name(), Some("port"));
+    }
+
+    #[test]
+    fn run_prompt_disabled_choice_is_validation_error() {
+        let mut disabled = Choice::new("B", "b");
+        disabled.disabled = true;
+        let q = Question {
+            name: "pick".into(),
+            type_name: "select".into(),
+            message: "Pick".into(),
+            choices: Some(vec![Choice::new("A", "a"), disabled]),
+            ..Default::default()
+        };
+        let mut term = MemoryTerminal::new("2\n");
+        let e = run_prompt(&q, &mut term).unwrap_err();
+        assert!(matches!(e, Error::Validation { name, .. } if name == "pick"));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn prompt_value_serializes_untagged() {
+        let values = [
+            PromptValue::from("a"),
+            PromptValue::Bool(true),
+            PromptValue::Float(1.5),
+            PromptValue::List(vec!["x".into(), "y".into()]),
+        ];
+        let json = serde_json::to_string(&values).unwrap();
+        assert_eq!(json, r#"["a",true,1.5,["x","y"]]"#);
+        let back: Vec<PromptValue> = serde_json::from_str(&json).unwrap();
+        assert_eq!(back, values);
+        let n: PromptValue = serde_json::from_str("3").unwrap();
+        assert_eq!(n, PromptValue::Float(3.0));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn question_deserializes_without_closures() {
+        let q: Question = serde_json::from_str(
+            r#"{
+                "name": "env",
+                "type": "select",
+                "message": "Environment?",
+                "choices": [
+                    {"title": "Staging", "value": "staging"},
+                    {"title": "Prod", "value": "prod", "description": "careful", "disabled": true}
+                ],
+                "initial_choice": 0,
+                "style": "password"
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(q.type_name, "select");
+        assert_eq!(q.round, 2);
+        assert_eq!(q.style, InputStyle::Password);
+        let choices = q.choices.as_ref().unwrap();
+        assert!(!choices[0].disabled);
+        assert!(choices[1].disabled);
+        assert_eq!(choices[1].description.as_deref(), Some("careful"));
+
+        let json = serde_json::to_value(&q).unwrap();
+        assert_eq!(json["type"], "select");
+        assert!(json.get("validate").is_none());
+        assert!(serde_json::from_str::<Question>(r#"{"kind": "text"}"#).is_err());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn question_timeout_is_in_seconds() {
+        let q: Question = serde_json::from_str(
+            r#"{"name": "n", "type": "text", "timeout": 1.5, "on_timeout": "cancel"}"#,
+        )
+        .unwrap();
+        assert_eq!(q.timeout, Some(Duration::from_millis(1500)));
+        assert_eq!(q.on_timeout, OnTimeout::Cancel);
+        let json = serde_json::to_value(&q).unwrap();
+        assert_eq!(json["timeout"], 1.5);
+        assert_eq!(json["on_timeout"], "cancel");
+        assert!(serde_json::from_str::<Question>(r#"{"timeout": -1}"#).is_err());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn question_matcher_is_named() {
+        let q: Question = serde_json::from_str(
+            r#"{"name": "r", "type": "autocomplete", "matcher": "substring", "allow_custom": true}"#,
+        )
+        .unwrap();
+        assert!(matches!(q.matcher, Matcher::Substring) && q.allow_custom);
+        assert_eq!(serde_json::to_value(&q).unwrap()["matcher"], "substring");
+        let custom = Question {
+            matcher: Matcher::Custom(Arc::new(|_, _| None)),
+            ..q
+        };
+        assert!(serde_json::to_value(&custom).is_err());
+    }
 }
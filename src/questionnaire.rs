@@ -0,0 +1,229 @@
+//! Questionnaire documents: questions loaded from JSON, TOML or YAML instead of code.
+//!
+//! Every format has the same shape, a `questions` list whose entries use `Question`'s field
+//! names, with `type` for `type_name`:
+//!
+//! ```toml
+//! [[questions]]
+//! name = "env"
+//! type = "select"
+//! message = "Environment?"
+//! choices = [{ title = "Staging", value = "staging" }, { title = "Prod", value = "prod" }]
+//! ```
+
+use crate::error::{Error, Result};
+use crate::prompts::Question;
+use serde::Deserialize;
+use std::path::Path;
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Questionnaire {
+    questions: Vec<Question>,
+}
+
+/// Parses a JSON questionnaire.
+#[cfg(feature = "json")]
+pub fn questions_from_json(input: &str) -> Result<Vec<Question>> {
+    serde_json::from_str::<Questionnaire>(input)
+        .map(|q| q.questions)
+        .map_err(|e| {
+            // serde_json appends " at line L column C" to its message.
+            let message = e.to_string();
+            let message = match message.rfind(" at line ") {
+                Some(at) => message[..at].to_string(),
+                None => message,
+            };
+            Error::Questionnaire {
+                line: e.line(),
+                column: e.column(),
+                message,
+            }
+        })
+}
+
+/// Parses a TOML questionnaire.
+#[cfg(feature = "toml")]
+pub fn questions_from_toml(input: &str) -> Result<Vec<Question>> {
+    toml::from_str::<Questionnaire>(input)
+        .map(|q| q.questions)
+        .map_err(|e| {
+            let (line, column) = e
+                .span()
+                .map_or((0, 0), |span| line_column(input, span.start));
+            Error::Questionnaire {
+                line,
+                column,
+                message: e.message().to_string(),
+            }
+        })
+}
+
+/// Parses a YAML questionnaire.
+#[cfg(feature = "yaml")]
+pub fn questions_from_yaml(input: &str) -> Result<Vec<Question>> {
+    serde_yaml::from_str::<Questionnaire>(input)
+        .map(|q| q.questions)
+        .map_err(|e| {
+            let (line, column) = e.location().map_or((0, 0), |l| (l.line(), l.column()));
+            let message = e.to_string();
+            let message = match message.rfind(" at line ") {
+                Some(at) => message[..at].to_string(),
+                None => message,
+            };
+            Error::Questionnaire {
+                line,
+                column,
+                message,
+            }
+        })
+}
+
+/// Reads a questionnaire file, picking the format from its extension (`json`, `toml`, `yaml`
+/// or `yml`). Formats whose feature is disabled are rejected.
+pub fn load_questions(path: impl AsRef<Path>) -> Result<Vec<Question>> {
+    let path = path.as_ref();
+    let input = std::fs::read_to_string(path)?;
+    let ext = path.extension().and_then(|e| e.to_str()).unwrap_or("");
+    match ext.to_ascii_lowercase().as_str() {
+        #[cfg(feature = "json")]
+        "json" => questions_from_json(&input),
+        #[cfg(feature = "toml")]
+        "toml" => questions_from_toml(&input),
+        #[cfg(feature = "yaml")]
+        "yaml" | "yml" => questions_from_yaml(&input),
+        _ => Err(Error::Io(std::io::Error::new(
+            std::io::ErrorKind::Unsupported,
+            format!("unsupported questionnaire format: '{}'", path.display()),
+        ))),
+    }
+}
+
+/// 1-based line and column of byte `offset` in `input`.
+#[cfg(feature = "toml")]
+fn line_column(input: &str, offset: usize) -> (usize, usize) {
+    let before = &input[..offset.min(input.len())];
+    let line = before.matches('\n').count() + 1;
+    let column = before
+        .rfind('\n')
+        .map_or(before, |nl| &before[nl + 1..])
+        .chars()
+        .count()
+        + 1;
+    (line, column)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "toml")]
+    #[test]
+    fn line_column_is_one_based() {
+        assert_eq!(line_column("abc", 0), (1, 1));
+        assert_eq!(line_column("ab\ncd", 4), (2, 2));
+        assert_eq!(line_column("ab\n", 99), (2, 1));
+    }
+
+    #[test]
+    fn load_rejects_unknown_extension() {
+        let e = load_questions("Cargo.lock").unwrap_err();
+        assert!(e.to_string().contains("unsupported questionnaire format"));
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn json_questionnaire_supports_all_fields() {
+        let questions = questions_from_json(
+            r#"{"questions": [
+                {"name": "name", "type": "text", "message": "Name?", "initial_text": "anon"},
+                {"name": "pw", "type": "password", "message": "Password?", "style": "password"},
+                {"name": "port", "type": "number", "message": "Port?", "initial_number": 80,
+                 "min": 1, "max": 65535, "float": false, "round": 0},
+                {"name": "ok", "type": "confirm", "message": "Ok?", "initial_bool": true},
+                {"name": "tls", "type": "toggle", "message": "TLS?", "active": "yes",
+                 "inactive": "no"},
+                {"name": "env", "type": "select", "message": "Env?", "initial_choice": 1,
+                 "hint": "pick one", "choices": [
+                    {"title": "Staging", "value": "staging", "description": "safe"},
+                    {"title": "Prod", "value": "prod", "disabled": true}]},
+                {"name": "tags", "type": "list", "message": "Tags?", "separator": ";"}
+            ]}"#,
+        )
+        .unwrap();
+        assert_eq!(questions.len(), 7);
+        assert_eq!(questions[0].initial_text.as_deref(), Some("anon"));
+        assert_eq!(questions[2].max, Some(65535.0));
+        assert_eq!(questions[2].round, 0);
+        assert_eq!(questions[4].inactive.as_deref(), Some("no"));
+        let choices = questions[5].choices.as_ref().unwrap();
+        assert_eq!(choices[0].description.as_deref(), Some("safe"));
+        assert!(choices[1].disabled);
+        assert_eq!(questions[6].separator.as_deref(), Some(";"));
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn json_schema_errors_have_line_and_column() {
+        let e =
+            questions_from_json("{\"questions\": [\n  {\"name\": \"a\", \"kind\": \"text\"}\n]}")
+                .unwrap_err();
+        match e {
+            Error::Questionnaire {
+                line,
+                column,
+                message,
+            } => {
+                assert_eq!(line, 2);
+                assert!(column > 1);
+                assert!(message.contains("unknown field `kind`"), "{}", message);
+            }
+            e => panic!("unexpected error: {}", e),
+        }
+    }
+
+    #[cfg(feature = "toml")]
+    #[test]
+    fn toml_questionnaire_parses() {
+        let questions = questions_from_toml(
+            r#"
+[[questions]]
+name = "env"
+type = "select"
+message = "Environment?"
+choices = [{ title = "Staging", value = "staging" }, { title = "Prod", value = "prod" }]
+
+[[questions]]
+name = "port"
+type = "number"
+message = "Port?"
+min = 1
+"#,
+        )
+        .unwrap();
+        assert_eq!(questions[0].choices.as_ref().unwrap().len(), 2);
+        assert_eq!(questions[1].min, Some(1.0));
+    }
+
+    #[cfg(feature = "toml")]
+    #[test]
+    fn toml_schema_errors_have_line_and_column() {
+        let e = questions_from_toml("[[questions]]\nname = \"a\"\nfloat = \"yes\"\n").unwrap_err();
+        match e {
+            Error::Questionnaire { line, column, .. } => assert_eq!((line, column), (3, 9)),
+            e => panic!("unexpected error: {}", e),
+        }
+    }
+
+    #[cfg(feature = "yaml")]
+    #[test]
+    fn yaml_questionnaire_parses_and_reports_position() {
+        let questions = questions_from_yaml(
+            "questions:\n  - name: ok\n    type: confirm\n    message: Ok?\n    initial_bool: true\n",
+        )
+        .unwrap();
+        assert_eq!(questions[0].initial_bool, Some(true));
+        let e = questions_from_yaml("questions:\n  - name: ok\n    round: many\n").unwrap_err();
+        assert!(matches!(e, Error::Questionnaire { line: 3, .. }), "{}", e);
+    }
+}
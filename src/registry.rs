@@ -0,0 +1,529 @@
+//! Registry resolving `Question::type_name` to a prompt.
+
+use crate::answers::Answers;
+#[cfg(feature = "tokio")]
+use crate::elements::read_async;
+use crate::elements::{
+    Check, Input, Map, Outcome, Prompt, State, drive_with, edit, error_line, now, read_blocking,
+};
+use crate::error::{Error, Result};
+use crate::history::History;
+use crate::prompts::{PromptValue, Question, builtin, editor_options};
+#[cfg(feature = "tokio")]
+use crate::terminal::AsyncTerminal;
+use crate::terminal::Terminal;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt;
+use std::io;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Function that asks a question itself. Returns `None` when the question was cancelled.
+pub type Runner =
+    Arc<dyn Fn(&Question, &mut dyn Terminal) -> Result<Option<PromptValue>> + Send + Sync>;
+
+/// Function that builds a prompt element for a question.
+pub type PromptFactory =
+    Arc<dyn Fn(&Question) -> Box<dyn Prompt<Output = PromptValue>> + Send + Sync>;
+
+#[derive(Clone)]
+enum Kind {
+    Runner(Runner),
+    Prompt(PromptFactory),
+}
+
+/// Prompt types by name: the built-in ones plus any registered by the application.
+///
+/// Registered types take precedence, so built-in names can be overridden. Cloning is cheap.
+#[derive(Clone, Default)]
+pub struct PromptRegistry {
+    kinds: HashMap<String, Kind>,
+}
+
+impl PromptRegistry {
+    /// Builds a registry with only the built-in types.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers a type run by `runner`, which gets the question and the terminal.
+    pub fn register_runner<F>(&mut self, type_name: impl Into<String>, runner: F) -> &mut Self
+    where
+        F: Fn(&Question, &mut dyn Terminal) -> Result<Option<PromptValue>> + Send + Sync + 'static,
+    {
+        self.kinds
+            .insert(type_name.into(), Kind::Runner(Arc::new(runner)));
+        self
+    }
+
+    /// Registers a type driven like the built-in ones: `factory` builds a `Prompt` per question.
+    pub fn register_prompt<P, F>(&mut self, type_name: impl Into<String>, factory: F) -> &mut Self
+    where
+        P: Prompt + 'static,
+        P::Output: Into<PromptValue>,
+        F: Fn(&Question) -> P + Send + Sync + 'static,
+    {
+        let factory: PromptFactory = Arc::new(move |q: &Question| {
+            Box::new(Map {
+                prompt: factory(q),
+                f: Into::into,
+            }) as Box<dyn Prompt<Output = PromptValue>>
+        });
+        self.kinds.insert(type_name.into(), Kind::Prompt(factory));
+        self
+    }
+
+    /// Whether `type_name` resolves to a prompt.
+    pub fn contains(&self, type_name: &str) -> bool {
+        self.kinds.contains_key(type_name)
+            || type_name == "editor"
+            || builtin(
+                &Question {
+                    type_name: type_name.into(),
+                    ..Default::default()
+                },
+                &[],
+            )
+            .is_some()
+    }
+
+    /// Runs `q` with the prompt registered for its type. Returns `None` on cancel.
+    ///
+    /// `answers` are the answers given so far: the question's dynamic fields are computed from
+    /// them and they are passed to its hooks. A question whose type resolves to none is skipped.
+    /// With `Question::history` set, answers are remembered in the default `History`.
+    pub fn run<T: Terminal + ?Sized>(
+        &self,
+        q: &Question,
+        term: &mut T,
+        answers: &Answers,
+    ) -> Result<Option<PromptValue>> {
+        let q = q.resolve(answers);
+        if q.type_name.is_empty() {
+            return Ok(None);
+        }
+        self.ask(&q, term, answers, Some(&History::default()))
+            .map(Outcome::ok)
+    }
+
+    /// `run` for async code: waits for keys without blocking the runtime.
+    ///
+    /// Types registered with `register_runner` and `editor` questions are still run
+    /// synchronously.
+    #[cfg(feature = "tokio")]
+    pub async fn run_async<T: AsyncTerminal + ?Sized>(
+        &self,
+        q: &Question,
+        term: &mut T,
+        answers: &Answers,
+    ) -> Result<Option<PromptValue>> {
+        let q = q.resolve(answers);
+        if q.type_name.is_empty() {
+            return Ok(None);
+        }
+        let history = History::default();
+        self.ask_with(
+            &q,
+            term,
+            answers,
+            Some(&history),
+            &mut async |t: &mut T, wait| read_async(t, wait).await,
+        )
+        .await
+        .map(Outcome::ok)
+    }
+
+    /// Runs `q`, keeping how it was cancelled. Errors carry `q.name`; answers are remembered
+    /// in `history` when given.
+    ///
+    /// The answer is validated, then converted by `q.format`; `q.display` gets the converted
+    /// value, which is computed once.
+    pub(crate) fn ask<T: Terminal + ?Sized>(
+        &self,
+        q: &Question,
+        term: &mut T,
+        answers: &Answers,
+        history: Option<&History>,
+    ) -> Result<Outcome<PromptValue>> {
+        now(
+            self.ask_with(q, term, answers, history, &mut async |t: &mut T, wait| {
+                read_blocking(t, wait)
+            }),
+        )
+    }
+
+    /// `ask` with keys read by `read`; custom runners read their own. Answers to questions
+    /// keeping history are recorded in `history`, when given, before `q.format` converts them.
+    pub(crate) async fn ask_with<T, R>(
+        &self,
+        q: &Question,
+        term: &mut T,
+        answers: &Answers,
+        history: Option<&History>,
+        read: &mut R,
+    ) -> Result<Outcome<PromptValue>>
+    where
+        T: Terminal + ?Sized,
+        R: AsyncFnMut(&mut T, Option<Duration>) -> io::Result<Input>,
+    {
+        let format = |value: PromptValue| q.formatted(value, answers);
+        let formatted = RefCell::new(None);
+        let display = |value: &PromptValue| {
+            let value = format(value.clone());
+            let shown = q.display.as_ref().map_or_else(String::new, |d| d(&value));
+            *formatted.borrow_mut() = Some(value);
+            shown
+        };
+        let check = Check {
+            validate: q.validate.as_ref(),
+            answers: Some(answers),
+            max_attempts: q.max_attempts,
+            timeout: q.timeout,
+            on_timeout: q.on_timeout,
+            display: q
+                .display
+                .as_ref()
+                .map(|_| &display as &dyn Fn(&PromptValue) -> String),
+            message: &q.message,
+        };
+        let history = history.filter(|_| q.keeps_history());
+        let recalled = history.map(|h| h.load(&q.name)).unwrap_or_default();
+        let outcome = match self.kinds.get(&q.type_name) {
+            Some(Kind::Runner(runner)) => run_validated(runner, q, term, &check),
+            Some(Kind::Prompt(factory)) => {
+                drive_with(factory(q).as_mut(), term, &check, read).await
+            }
+            None if q.type_name == "editor" => {
+                edit(&editor_options(q), term, &check).map(|outcome| match outcome {
+                    Outcome::Submitted(text) => Outcome::Submitted(PromptValue::String(text)),
+                    Outcome::Cancelled(state) => Outcome::Cancelled(state),
+                    Outcome::TimedOut => Outcome::TimedOut,
+                })
+            }
+            None => match builtin(q, &recalled) {
+                Some(mut prompt) => drive_with(prompt.as_mut(), term, &check, read).await,
+                None => Err(Error::UnknownPromptType {
+                    name: q.name.clone(),
+                    type_name: q.type_name.clone(),
+                }),
+            },
+        };
+        match outcome.map_err(|e| e.with_name(&q.name))? {
+            Outcome::Submitted(value) => {
+                if let Some(history) = history {
+                    remember(history, q, &value);
+                }
+                Ok(Outcome::Submitted(
+                    formatted.take().unwrap_or_else(|| format(value)),
+                ))
+            }
+            cancelled => Ok(cancelled),
+        }
+    }
+}
+
+/// Records `value` as typed: list items are joined with the question's separator.
+fn remember(history: &History, q: &Question, value: &PromptValue) {
+    let entry = match value {
+        PromptValue::String(s) => s.clone(),
+        PromptValue::List(items) => {
+            items.join(&format!("{} ", q.separator.as_deref().unwrap_or(",")))
+        }
+        _ => return,
+    };
+    // History is best effort; an unwritable file must not fail the prompt.
+    history.record(&q.name, &entry).ok();
+}
+
+/// Calls a runner until its answer passes `check`, printing each rejection.
+fn run_validated<T: Terminal + ?Sized>(
+    runner: &Runner,
+    q: &Question,
+    term: &mut T,
+    check: &Check,
+) -> Result<Outcome<PromptValue>> {
+    let mut failures = 0;
+    loop {
+        let value = match runner(q, &mut &mut *term)? {
+            Some(value) => value,
+            None => return Ok(Outcome::Cancelled(State::Exited)),
+        };
+        let (Some(validate), Some(answers)) = (check.validate, check.answers) else {
+            return Ok(Outcome::Submitted(value));
+        };
+        let Err(message) = validate(&value, answers) else {
+            return Ok(Outcome::Submitted(value));
+        };
+        term.write(&format!("{}\n", error_line(&message)))?;
+        failures += 1;
+        if check.max_attempts.is_some_and(|n| failures >= n) {
+            return Err(Error::Validation {
+                name: q.name.clone(),
+                message,
+            });
+        }
+    }
+}
+
+impl fmt::Debug for PromptRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut names: Vec<_> = self.kinds.keys().collect();
+        names.sort();
+        f.debug_struct("PromptRegistry")
+            .field("registered", &names)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::elements::{Frame, Transition};
+    use crate::terminal::MemoryTerminal;
+    use crate::util::action::PromptAction;
+
+    /// Cycles through regions with Tab; submits the current one.
+    struct RegionPicker {
+        regions: Vec<&'static str>,
+        current: usize,
+    }
+
+    impl Prompt for RegionPicker {
+        type Output = String;
+
+        fn render(&self, _state: &State) -> Frame {
+            Frame::line(format!("Region: {}", self.regions[self.current]))
+        }
+
+        fn handle(&mut self, action: PromptAction) -> Transition<String> {
+            match action {
+                PromptAction::Next => {
+                    self.current = (self.current + 1) % self.regions.len();
+                    Transition::Continue
+                }
+                PromptAction::Submit => Transition::Submit(self.regions[self.current].into()),
+                _ => Transition::Bell,
+            }
+        }
+    }
+
+    fn question(type_name: &str) -> Question {
+        Question {
+            name: "q".into(),
+            type_name: type_name.into(),
+            message: "Q?".into(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn new_registry_contains_builtins_only() {
+        let r = PromptRegistry::new();
+        for name in [
+            "text",
+            "password",
+            "invisible",
+            "number",
+            "confirm",
+            "toggle",
+            "select",
+            "list",
+            "autocomplete",
+            "multiselect",
+            "date",
+            "editor",
+        ] {
+            assert!(r.contains(name), "{}", name);
+        }
+        assert!(!r.contains("region"));
+    }
+
+    #[test]
+    fn registered_prompt_is_driven() {
+        let mut r = PromptRegistry::new();
+        r.register_prompt("region", |_q: &Question| RegionPicker {
+            regions: vec!["eu-west-1", "us-east-1"],
+            current: 0,
+        });
+        assert!(r.contains("region"));
+        let mut term = MemoryTerminal::new("\t\n");
+        assert_eq!(
+            r.run(&question("region"), &mut term, &Answers::new())
+                .unwrap(),
+            Some(PromptValue::String("us-east-1".into()))
+        );
+        assert!(term.output().contains("Region: eu-west-1"));
+    }
+
+    #[test]
+    fn registered_prompt_can_be_cancelled() {
+        let mut r = PromptRegistry::new();
+        r.register_prompt("region", |_q: &Question| RegionPicker {
+            regions: vec!["eu-west-1"],
+            current: 0,
+        });
+        let mut term = MemoryTerminal::new("\x03");
+        assert_eq!(
+            r.ask(&question("region"), &mut term, &Answers::new(), None)
+                .unwrap(),
+            Outcome::Cancelled(State::Aborted)
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn editor_question_runs_the_command() {
+        let dir = tempfile::tempdir().unwrap();
+        let script = dir.path().join("editor.sh");
+        std::fs::write(&script, "echo body >> \"$1\"").unwrap();
+        let q = Question {
+            initial_text: Some("# comment\n".into()),
+            editor: Some(format!("sh {}", script.display())),
+            comment: Some("#".into()),
+            ..question("editor")
+        };
+        let mut term = MemoryTerminal::new("");
+        assert_eq!(
+            PromptRegistry::new()
+                .run(&q, &mut term, &Answers::new())
+                .unwrap(),
+            Some(PromptValue::String("body".into()))
+        );
+    }
+
+    #[test]
+    fn registered_runner_gets_question_and_terminal() {
+        let mut r = PromptRegistry::new();
+        r.register_runner("echo", |q, term| {
+            term.write(&q.message)?;
+            Ok(Some(PromptValue::String(q.name.clone())))
+        });
+        let mut term = MemoryTerminal::new("");
+        assert_eq!(
+            r.run(&question("echo"), &mut term, &Answers::new())
+                .unwrap(),
+            Some(PromptValue::String("q".into()))
+        );
+        assert_eq!(term.output(), "Q?");
+    }
+
+    #[test]
+    fn runner_none_is_cancel() {
+        let mut r = PromptRegistry::new();
+        r.register_runner("never", |_, _| Ok(None));
+        let mut term = MemoryTerminal::new("");
+        assert_eq!(
+            r.ask(&question("never"), &mut term, &Answers::new(), None)
+                .unwrap(),
+            Outcome::Cancelled(State::Exited)
+        );
+    }
+
+    #[test]
+    fn runner_errors_get_question_name() {
+        let mut r = PromptRegistry::new();
+        r.register_runner("bad", |_, _| {
+            Err(Error::Validation {
+                name: String::new(),
+                message: "no".into(),
+            })
+        });
+        let mut term = MemoryTerminal::new("");
+        let e = r
+            .run(&question("bad"), &mut term, &Answers::new())
+            .unwrap_err();
+        assert_eq!(e.name(), Some("q"));
+    }
+
+    #[test]
+    fn registered_type_overrides_builtin() {
+        let mut r = PromptRegistry::new();
+        r.register_runner("text", |_, _| Ok(Some(PromptValue::Bool(true))));
+        let mut term = MemoryTerminal::new("ignored\n");
+        assert_eq!(
+            r.run(&question("text"), &mut term, &Answers::new())
+                .unwrap(),
+            Some(PromptValue::Bool(true))
+        );
+    }
+
+    #[test]
+    fn unknown_type_is_error() {
+        let mut term = MemoryTerminal::new("");
+        let e = PromptRegistry::new()
+            .run(&question("nope"), &mut term, &Answers::new())
+            .unwrap_err();
+        assert!(matches!(e, Error::UnknownPromptType { type_name, .. } if type_name == "nope"));
+    }
+
+    #[test]
+    fn clone_shares_registrations() {
+        let mut r = PromptRegistry::new();
+        r.register_runner("x", |_, _| Ok(None));
+        assert!(r.clone().contains("x"));
+        assert!(format!("{:?}", r).contains("\"x\""));
+    }
+
+    #[test]
+    fn runner_answers_are_validated() {
+        let mut r = PromptRegistry::new();
+        r.register_runner("zero", |_, _| Ok(Some(PromptValue::Float(0.0))));
+        let q = Question {
+            validate: Some(Arc::new(|v, _| match v {
+                PromptValue::Float(n) if *n == 0.0 => Err("must not be zero".into()),
+                _ => Ok(()),
+            })),
+            max_attempts: Some(3),
+            ..question("zero")
+        };
+        let mut term = MemoryTerminal::new("");
+        let e = r.run(&q, &mut term, &Answers::new()).unwrap_err();
+        assert!(matches!(&e, Error::Validation { name, .. } if name == "q"));
+        assert_eq!(term.output().matches("must not be zero").count(), 3);
+    }
+
+    #[test]
+    fn runner_answers_are_formatted() {
+        let mut r = PromptRegistry::new();
+        r.register_runner("echo", |q, _| Ok(Some(PromptValue::String(q.name.clone()))));
+        let q = Question {
+            format: Some(Arc::new(|v, answers| match (v, answers.get("suffix")) {
+                (PromptValue::String(s), Some(PromptValue::String(suffix))) => {
+                    PromptValue::String(s + suffix)
+                }
+                (v, _) => v,
+            })),
+            ..question("echo")
+        };
+        let answers = Answers::from([("suffix".to_string(), PromptValue::from("!"))]);
+        let mut term = MemoryTerminal::new("");
+        assert_eq!(
+            r.run(&q, &mut term, &answers).unwrap(),
+            Some(PromptValue::String("q!".into()))
+        );
+    }
+
+    #[test]
+    fn display_gets_formatted_value_once() {
+        let calls = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let counter = calls.clone();
+        let q = Question {
+            format: Some(Arc::new(move |_, _| {
+                counter.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+                PromptValue::Bool(true)
+            })),
+            display: Some(Arc::new(|v| format!("[{:?}]", v))),
+            ..question("text")
+        };
+        let mut term = MemoryTerminal::new("x\n");
+        assert_eq!(
+            PromptRegistry::new()
+                .run(&q, &mut term, &Answers::new())
+                .unwrap(),
+            Some(PromptValue::Bool(true))
+        );
+        assert_eq!(calls.load(std::sync::atomic::Ordering::SeqCst), 1);
+        assert!(term.output().contains("[Bool(true)]"));
+    }
+}
@@ -0,0 +1,656 @@
+//! Terminal backends: where prompts read keys from and draw to.
+
+use crate::util::action::{Key, KeyName};
+use crate::util::keys::{ByteSource, KeyDecoder};
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use std::io::{self, Cursor, IsTerminal, Write};
+use std::sync::{Mutex, MutexGuard, OnceLock, PoisonError, mpsc};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Input and output device a prompt runs against.
+///
+/// When `is_tty` is true, prompts switch to raw mode and redraw in place on every key.
+/// Otherwise they print once and consume keys as a plain byte stream.
+pub trait Terminal {
+    /// Whether this is an interactive terminal.
+    fn is_tty(&self) -> bool;
+
+    /// Switches to raw mode: keys arrive unbuffered and unechoed.
+    fn enable_raw(&mut self) -> io::Result<()>;
+
+    /// Restores the mode active before `enable_raw`.
+    fn disable_raw(&mut self) -> io::Result<()>;
+
+    /// Returns `(columns, rows)`.
+    fn size(&self) -> io::Result<(u16, u16)>;
+
+    /// Reads the next key. Returns `None` at end of input.
+    fn read_key(&mut self) -> io::Result<Option<Key>>;
+
+    /// Waits up to `timeout` for input; `true` means `read_key` will not block. Devices that
+    /// cannot wait report `true`, so their prompts never time out.
+    fn poll_key(&mut self, _timeout: Duration) -> io::Result<bool> {
+        Ok(true)
+    }
+
+    /// Writes text as is, escape sequences included.
+    fn write(&mut self, text: &str) -> io::Result<()>;
+
+    /// Flushes written text to the device.
+    fn flush(&mut self) -> io::Result<()>;
+}
+
+impl<T: Terminal + ?Sized> Terminal for &mut T {
+    fn is_tty(&self) -> bool {
+        (**self).is_tty()
+    }
+
+    fn enable_raw(&mut self) -> io::Result<()> {
+        (**self).enable_raw()
+    }
+
+    fn disable_raw(&mut self) -> io::Result<()> {
+        (**self).disable_raw()
+    }
+
+    fn size(&self) -> io::Result<(u16, u16)> {
+        (**self).size()
+    }
+
+    fn read_key(&mut self) -> io::Result<Option<Key>> {
+        (**self).read_key()
+    }
+
+    fn poll_key(&mut self, timeout: Duration) -> io::Result<bool> {
+        (**self).poll_key(timeout)
+    }
+
+    fn write(&mut self, text: &str) -> io::Result<()> {
+        (**self).write(text)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        (**self).flush()
+    }
+}
+
+/// Terminal whose keys can be awaited, for the `*_async` functions.
+#[cfg(feature = "tokio")]
+pub trait AsyncTerminal: Terminal {
+    /// Waits for the next key without blocking the runtime. Returns `None` at end of input.
+    fn read_key_async(&mut self) -> impl Future<Output = io::Result<Option<Key>>>;
+}
+
+#[cfg(feature = "tokio")]
+impl<T: AsyncTerminal + ?Sized> AsyncTerminal for &mut T {
+    fn read_key_async(&mut self) -> impl Future<Output = io::Result<Option<Key>>> {
+        (**self).read_key_async()
+    }
+}
+
+/// Process terminal: stdin and stdout, with raw mode and key events from crossterm.
+///
+/// When stdin or stdout is not a terminal (piped input, redirected output), keys are
+/// decoded from stdin bytes instead.
+#[derive(Debug, Default)]
+pub struct CrosstermTerminal {
+    decoder: KeyDecoder,
+    raw: bool,
+    /// Key read by `poll_key` in raw mode, returned by the next `read_key`.
+    pending: Option<Key>,
+    /// Key events for `read_key_async`, opened on first use.
+    #[cfg(feature = "tokio")]
+    events: Option<event::EventStream>,
+    /// Stdin read started by `read_key_async` outside raw mode. It is kept when the future is
+    /// dropped, so the next call gets its key instead of starting another read.
+    #[cfg(feature = "tokio")]
+    reading: Option<tokio::task::JoinHandle<io::Result<Option<Key>>>>,
+}
+
+impl CrosstermTerminal {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Takes the stdin byte a timed out prompt was waiting for, so the application can read
+    /// stdin itself. Until it arrives stdin stays locked by the prompt's reader, so this
+    /// waits for it. Returns `None` when no byte was pending or at end of input.
+    pub fn take_pending_input() -> io::Result<Option<u8>> {
+        StdinPump::shared().take_pending()
+    }
+}
+
+impl Terminal for CrosstermTerminal {
+    fn is_tty(&self) -> bool {
+        io::stdin().is_terminal() && io::stdout().is_terminal()
+    }
+
+    fn enable_raw(&mut self) -> io::Result<()> {
+        crossterm::terminal::enable_raw_mode()?;
+        self.raw = true;
+        Ok(())
+    }
+
+    fn disable_raw(&mut self) -> io::Result<()> {
+        if self.raw {
+            crossterm::terminal::disable_raw_mode()?;
+            self.raw = false;
+        }
+        Ok(())
+    }
+
+    fn size(&self) -> io::Result<(u16, u16)> {
+        crossterm::terminal::size()
+    }
+
+    fn read_key(&mut self) -> io::Result<Option<Key>> {
+        if !self.raw {
+            return StdinPump::shared().read_key(self.decoder);
+        }
+        if let Some(key) = self.pending.take() {
+            return Ok(Some(key));
+        }
+        loop {
+            if let Event::Key(ev) = event::read()?
+                && let Some(key) = key_from_event(&ev)
+            {
+                return Ok(Some(key));
+            }
+        }
+    }
+
+    fn poll_key(&mut self, timeout: Duration) -> io::Result<bool> {
+        if !self.raw {
+            return StdinPump::shared().poll(timeout);
+        }
+        if self.pending.is_some() {
+            return Ok(true);
+        }
+        let deadline = Instant::now() + timeout;
+        loop {
+            let left = deadline.saturating_duration_since(Instant::now());
+            if !event::poll(left)? {
+                return Ok(false);
+            }
+            if let Event::Key(ev) = event::read()?
+                && let Some(key) = key_from_event(&ev)
+            {
+                self.pending = Some(key);
+                return Ok(true);
+            }
+        }
+    }
+
+    fn write(&mut self, text: &str) -> io::Result<()> {
+        io::stdout().write_all(text.as_bytes())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        io::stdout().flush()
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl AsyncTerminal for CrosstermTerminal {
+    /// In raw mode, awaits crossterm's `EventStream`. Otherwise reads stdin on a blocking
+    /// thread; a read cut short by dropping the future is resumed by the next call.
+    async fn read_key_async(&mut self) -> io::Result<Option<Key>> {
+        use futures_core::Stream;
+        use std::pin::Pin;
+
+        if !self.raw {
+            let decoder = self.decoder;
+            let reading = self.reading.get_or_insert_with(|| {
+                tokio::task::spawn_blocking(move || StdinPump::shared().read_key(decoder))
+            });
+            let read = reading.await;
+            self.reading = None;
+            return read.map_err(io::Error::other)?;
+        }
+        let events = self.events.get_or_insert_with(event::EventStream::new);
+        loop {
+            let next = std::future::poll_fn(|cx| Pin::new(&mut *events).poll_next(cx)).await;
+            match next {
+                Some(Ok(Event::Key(ev))) => {
+                    if let Some(key) = key_from_event(&ev) {
+                        return Ok(Some(key));
+                    }
+                }
+                Some(Ok(_)) => {}
+                Some(Err(e)) => return Err(e),
+                None => return Ok(None),
+            }
+        }
+    }
+}
+
+/// Stdin bytes read on a background thread, so that waiting for them can time out.
+///
+/// One pump serves the whole process and every read outside raw mode goes through it. The
+/// thread reads a byte only when one is asked for and locks stdin just for that read, so the
+/// application can read stdin itself between prompts. A wait that timed out leaves its read
+/// running, holding the lock until a byte arrives; that byte goes to the next read, or to
+/// [`CrosstermTerminal::take_pending_input`].
+#[derive(Debug)]
+struct StdinPump {
+    /// Asks the thread for one more byte.
+    requests: mpsc::Sender<()>,
+    bytes: mpsc::Receiver<io::Result<Option<u8>>>,
+    /// A byte was asked for and has not arrived yet.
+    asked: bool,
+    /// Byte or end of input received by `poll`, not yet read.
+    peeked: Option<Option<u8>>,
+    /// The last key read was Return, so a `\n` after it ends the same line.
+    after_return: bool,
+}
+
+impl StdinPump {
+    /// The process-wide pump reading stdin, started on first use.
+    fn shared() -> MutexGuard<'static, StdinPump> {
+        static PUMP: OnceLock<Mutex<StdinPump>> = OnceLock::new();
+        PUMP.get_or_init(|| Mutex::new(Self::start(|| io::stdin().lock().read_byte())))
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Starts a thread calling `read` once per requested byte.
+    fn start<F>(mut read: F) -> Self
+    where
+        F: FnMut() -> io::Result<Option<u8>> + Send + 'static,
+    {
+        let (requests, asked) = mpsc::channel();
+        let (tx, bytes) = mpsc::channel();
+        thread::spawn(move || {
+            for () in asked {
+                if tx.send(read()).is_err() {
+                    return;
+                }
+            }
+        });
+        Self {
+            requests,
+            bytes,
+            asked: false,
+            peeked: None,
+            after_return: false,
+        }
+    }
+
+    /// Waits for the next byte or end of input, at most `timeout` when one is given. Returns
+    /// `None` when the wait timed out.
+    fn next(&mut self, timeout: Option<Duration>) -> io::Result<Option<Option<u8>>> {
+        if let Some(b) = self.peeked.take() {
+            return Ok(Some(b));
+        }
+        if !self.asked {
+            self.requests.send(()).ok();
+            self.asked = true;
+        }
+        let received = match timeout {
+            None => self.bytes.recv().ok(),
+            Some(timeout) => match self.bytes.recv_timeout(timeout) {
+                Ok(b) => Some(b),
+                Err(mpsc::RecvTimeoutError::Timeout) => return Ok(None),
+                Err(mpsc::RecvTimeoutError::Disconnected) => None,
+            },
+        };
+        self.asked = false;
+        // A stopped thread reads as end of input.
+        received.unwrap_or(Ok(None)).map(Some)
+    }
+
+    /// Waits up to `timeout` for a byte or end of input.
+    fn poll(&mut self, timeout: Duration) -> io::Result<bool> {
+        if self.peeked.is_some() {
+            return Ok(true);
+        }
+        match self.next(Some(timeout))? {
+            Some(b) => {
+                self.peeked = Some(b);
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    /// The byte a timed out wait asked for, waiting for it if it has not arrived. Returns
+    /// `None` when nothing was asked for.
+    fn take_pending(&mut self) -> io::Result<Option<u8>> {
+        self.after_return = false;
+        match self.asked || self.peeked.is_some() {
+            true => self.read_byte(),
+            false => Ok(None),
+        }
+    }
+
+    /// Reads the next key, with `\r\n` read as one Return.
+    fn read_key(&mut self, decoder: KeyDecoder) -> io::Result<Option<Key>> {
+        let mut after_return = self.after_return;
+        let key = read_line_key(decoder, self, &mut after_return);
+        self.after_return = after_return;
+        key
+    }
+}
+
+impl ByteSource for StdinPump {
+    fn read_byte(&mut self) -> io::Result<Option<u8>> {
+        self.next(None).map(Option::flatten)
+    }
+
+    fn read_byte_timeout(&mut self, timeout: Duration) -> io::Result<Option<u8>> {
+        self.next(Some(timeout)).map(Option::flatten)
+    }
+}
+
+/// Reads a key outside raw mode, where input arrives as lines: a `\r\n` line ending is one
+/// Return rather than Return and Enter.
+fn read_line_key<S: ByteSource + ?Sized>(
+    decoder: KeyDecoder,
+    src: &mut S,
+    after_return: &mut bool,
+) -> io::Result<Option<Key>> {
+    loop {
+        let key = decoder.read_key(src)?;
+        let is = |name| key.as_ref() == Some(&Key::new(name));
+        let line_feed = *after_return && is(KeyName::Enter);
+        *after_return = is(KeyName::Return);
+        if !line_feed {
+            return Ok(key);
+        }
+    }
+}
+
+/// Converts a crossterm key event. Returns `None` for key releases.
+pub(crate) fn key_from_event(ev: &KeyEvent) -> Option<Key> {
+    if ev.kind == KeyEventKind::Release {
+        return None;
+    }
+    let name = match ev.code {
+        KeyCode::Char(c) => KeyName::Char(c),
+        KeyCode::Enter => KeyName::Return,
+        KeyCode::Backspace => KeyName::Backspace,
+        KeyCode::Delete => KeyName::Delete,
+        KeyCode::Tab => KeyName::Tab,
+        KeyCode::Esc => KeyName::Escape,
+        KeyCode::Up => KeyName::Up,
+        KeyCode::Down => KeyName::Down,
+        KeyCode::Left => KeyName::Left,
+        KeyCode::Right => KeyName::Right,
+        KeyCode::Home => KeyName::Home,
+        KeyCode::End => KeyName::End,
+        KeyCode::PageUp => KeyName::PageUp,
+        KeyCode::PageDown => KeyName::PageDown,
+        _ => KeyName::Unknown,
+    };
+    Some(Key {
+        name,
+        ctrl: ev.modifiers.contains(KeyModifiers::CONTROL),
+        meta: ev.modifiers.contains(KeyModifiers::ALT),
+    })
+}
+
+/// In-memory terminal: keys come from a fixed byte string, output is captured.
+#[derive(Debug, Clone)]
+pub struct MemoryTerminal {
+    input: Cursor<Vec<u8>>,
+    output: String,
+    tty: bool,
+    raw: bool,
+    size: (u16, u16),
+    /// The last key read outside raw mode was Return.
+    after_return: bool,
+}
+
+impl MemoryTerminal {
+    /// Builds a non-interactive terminal that reads `input` as raw key bytes.
+    pub fn new(input: impl Into<Vec<u8>>) -> Self {
+        Self {
+            input: Cursor::new(input.into()),
+            output: String::new(),
+            tty: false,
+            raw: false,
+            size: (80, 24),
+            after_return: false,
+        }
+    }
+
+    /// Sets whether the terminal reports itself as interactive.
+    pub fn tty(mut self, tty: bool) -> Self {
+        self.tty = tty;
+        self
+    }
+
+    /// Sets the reported `(columns, rows)`.
+    pub fn with_size(mut self, columns: u16, rows: u16) -> Self {
+        self.size = (columns, rows);
+        self
+    }
+
+    /// Returns everything written so far.
+    pub fn output(&self) -> &str {
+        &self.output
+    }
+
+    /// Returns the input not yet read.
+    pub fn remaining_input(&self) -> &[u8] {
+        let pos = (self.input.position() as usize).min(self.input.get_ref().len());
+        &self.input.get_ref()[pos..]
+    }
+
+    /// Whether raw mode is currently on.
+    pub fn is_raw(&self) -> bool {
+        self.raw
+    }
+}
+
+impl Terminal for MemoryTerminal {
+    fn is_tty(&self) -> bool {
+        self.tty
+    }
+
+    fn enable_raw(&mut self) -> io::Result<()> {
+        self.raw = true;
+        Ok(())
+    }
+
+    fn disable_raw(&mut self) -> io::Result<()> {
+        self.raw = false;
+        Ok(())
+    }
+
+    fn size(&self) -> io::Result<(u16, u16)> {
+        Ok(self.size)
+    }
+
+    fn read_key(&mut self) -> io::Result<Option<Key>> {
+        if self.raw {
+            return KeyDecoder::default().read_key(&mut self.input);
+        }
+        read_line_key(
+            KeyDecoder::default(),
+            &mut self.input,
+            &mut self.after_return,
+        )
+    }
+
+    /// Ready while input is left; once it is used up, waiting times out at once.
+    fn poll_key(&mut self, _timeout: Duration) -> io::Result<bool> {
+        Ok(!self.remaining_input().is_empty())
+    }
+
+    fn write(&mut self, text: &str) -> io::Result<()> {
+        self.output.push_str(text);
+        Ok(())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl AsyncTerminal for MemoryTerminal {
+    async fn read_key_async(&mut self) -> io::Result<Option<Key>> {
+        self.read_key()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[test]
+    fn memory_terminal_reads_keys_then_eof() {
+        let mut t = MemoryTerminal::new("a\x1b[A");
+        assert_eq!(t.read_key().unwrap().unwrap().name, KeyName::Char('a'));
+        assert_eq!(t.read_key().unwrap().unwrap().name, KeyName::Up);
+        assert!(t.read_key().unwrap().is_none());
+    }
+
+    #[test]
+    fn line_mode_reads_crlf_as_one_return() {
+        let mut t = MemoryTerminal::new("a\r\n\r\n\n");
+        let mut names = Vec::new();
+        while let Some(k) = t.read_key().unwrap() {
+            names.push(k.name);
+        }
+        assert_eq!(
+            names,
+            [
+                KeyName::Char('a'),
+                KeyName::Return,
+                KeyName::Return,
+                KeyName::Enter
+            ]
+        );
+        let mut t = MemoryTerminal::new("\r\n").tty(true);
+        t.enable_raw().unwrap();
+        assert_eq!(t.read_key().unwrap().unwrap().name, KeyName::Return);
+        assert_eq!(t.read_key().unwrap().unwrap().name, KeyName::Enter);
+    }
+
+    #[test]
+    fn stdin_pump_reads_on_demand_and_keeps_late_bytes() {
+        let reads = Arc::new(AtomicUsize::new(0));
+        let counted = reads.clone();
+        let mut input = b"a\r\n".to_vec().into_iter();
+        let mut pump = StdinPump::start(move || {
+            counted.fetch_add(1, Ordering::SeqCst);
+            thread::sleep(Duration::from_millis(20));
+            Ok(input.next())
+        });
+        thread::sleep(Duration::from_millis(30));
+        assert_eq!(reads.load(Ordering::SeqCst), 0);
+        assert!(!pump.poll(Duration::from_millis(1)).unwrap());
+        assert!(!pump.poll(Duration::from_millis(1)).unwrap());
+        let decoder = KeyDecoder::default();
+        assert_eq!(
+            pump.read_key(decoder).unwrap().unwrap().name,
+            KeyName::Char('a')
+        );
+        assert_eq!(
+            pump.read_key(decoder).unwrap().unwrap().name,
+            KeyName::Return
+        );
+        assert!(pump.read_key(decoder).unwrap().is_none());
+        assert_eq!(reads.load(Ordering::SeqCst), 4);
+    }
+
+    #[test]
+    fn stdin_pump_hands_over_the_byte_of_a_timed_out_wait() {
+        let reads = Arc::new(AtomicUsize::new(0));
+        let counted = reads.clone();
+        let mut input = b"yes\n".to_vec().into_iter();
+        let mut pump = StdinPump::start(move || {
+            counted.fetch_add(1, Ordering::SeqCst);
+            thread::sleep(Duration::from_millis(20));
+            Ok(input.next())
+        });
+        assert_eq!(pump.take_pending().unwrap(), None);
+        assert!(!pump.poll(Duration::from_millis(1)).unwrap());
+        assert_eq!(pump.take_pending().unwrap(), Some(b'y'));
+        assert_eq!(pump.take_pending().unwrap(), None);
+        assert_eq!(reads.load(Ordering::SeqCst), 1);
+        assert!(pump.poll(Duration::from_millis(100)).unwrap());
+        assert_eq!(pump.take_pending().unwrap(), Some(b'e'));
+        assert_eq!(
+            pump.read_key(KeyDecoder::default()).unwrap().unwrap().name,
+            KeyName::Char('s')
+        );
+        assert_eq!(reads.load(Ordering::SeqCst), 3);
+    }
+
+    #[test]
+    fn memory_terminal_captures_output() {
+        let mut t = MemoryTerminal::new("");
+        t.write("hello ").unwrap();
+        t.write("world").unwrap();
+        t.flush().unwrap();
+        assert_eq!(t.output(), "hello world");
+    }
+
+    #[test]
+    fn memory_terminal_tty_and_size() {
+        let t = MemoryTerminal::new("");
+        assert!(!t.is_tty());
+        assert_eq!(t.size().unwrap(), (80, 24));
+        let t = t.tty(true).with_size(40, 10);
+        assert!(t.is_tty());
+        assert_eq!(t.size().unwrap(), (40, 10));
+    }
+
+    #[test]
+    fn memory_terminal_raw_mode_toggles() {
+        let mut t = MemoryTerminal::new("");
+        t.enable_raw().unwrap();
+        assert!(t.is_raw());
+        t.disable_raw().unwrap();
+        assert!(!t.is_raw());
+    }
+
+    #[test]
+    fn memory_terminal_remaining_input() {
+        let mut t = MemoryTerminal::new("ab");
+        t.read_key().unwrap();
+        assert_eq!(t.remaining_input(), b"b");
+    }
+
+    #[test]
+    fn key_from_event_maps_modifiers() {
+        let k = key_from_event(&KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL)).unwrap();
+        assert_eq!(
+            k,
+            Key {
+                name: KeyName::Char('c'),
+                ctrl: true,
+                meta: false
+            }
+        );
+        let k = key_from_event(&KeyEvent::new(KeyCode::Left, KeyModifiers::ALT)).unwrap();
+        assert!(k.meta);
+        assert_eq!(k.name, KeyName::Left);
+    }
+
+    #[test]
+    fn key_from_event_maps_named_keys() {
+        let name = |code| {
+            key_from_event(&KeyEvent::new(code, KeyModifiers::NONE))
+                .unwrap()
+                .name
+        };
+        assert_eq!(name(KeyCode::Enter), KeyName::Return);
+        assert_eq!(name(KeyCode::Esc), KeyName::Escape);
+        assert_eq!(name(KeyCode::PageDown), KeyName::PageDown);
+        assert_eq!(name(KeyCode::F(1)), KeyName::Unknown);
+    }
+
+    #[test]
+    fn key_from_event_ignores_release() {
+        let mut ev = KeyEvent::new(KeyCode::Char('a'), KeyModifiers::NONE);
+        ev.kind = KeyEventKind::Release;
+        assert!(key_from_event(&ev).is_none());
+    }
+}
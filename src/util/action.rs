@@ -1,13 +1,47 @@
 //! Key-to-action mapping for prompt input.
 
+use std::fmt;
+
 /// Key event for line-based input.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Key {
     pub name: KeyName,
     pub ctrl: bool,
     pub meta: bool,
 }
 
+impl Key {
+    /// Builds a key without modifiers.
+    pub fn new(name: KeyName) -> Self {
+        Self {
+            name,
+            ctrl: false,
+            meta: false,
+        }
+    }
+}
+
+impl fmt::Display for Key {
+    /// Writes the key as shown in hints, e.g. `Ctrl-D` or `Alt-Enter`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.ctrl {
+            f.write_str("Ctrl-")?;
+        }
+        if self.meta {
+            f.write_str("Alt-")?;
+        }
+        match &self.name {
+            KeyName::Char(c) if self.ctrl || self.meta => write!(f, "{}", c.to_ascii_uppercase()),
+            KeyName::Char(c) => write!(f, "{}", c),
+            KeyName::Return | KeyName::Enter => f.write_str("Enter"),
+            KeyName::Abort => f.write_str("Ctrl-C"),
+            KeyName::PageUp => f.write_str("Page Up"),
+            KeyName::PageDown => f.write_str("Page Down"),
+            other => write!(f, "{:?}", other),
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum KeyName {
     Char(char),
@@ -39,6 +73,12 @@ pub enum PromptAction {
     Submit,
     Delete,
     DeleteForward,
+    /// Deletes the word before the cursor (Ctrl-W).
+    DeleteWord,
+    /// Deletes from the start of the line to the cursor (Ctrl-U).
+    DeleteToStart,
+    /// Deletes from the cursor to the end of the line (Ctrl-K).
+    DeleteToEnd,
     Exit,
     Next,
     NextPage,
@@ -64,6 +104,9 @@ pub fn key_action(key: &Key, is_select: bool) -> Option<PromptAction> {
             KeyName::Char('d') => Some(PromptAction::Abort),
             KeyName::Char('e') => Some(PromptAction::Last),
             KeyName::Char('g') => Some(PromptAction::Reset),
+            KeyName::Char('k') => Some(PromptAction::DeleteToEnd),
+            KeyName::Char('u') => Some(PromptAction::DeleteToStart),
+            KeyName::Char('w') => Some(PromptAction::DeleteWord),
             _ => None,
         };
     }
@@ -357,6 +400,36 @@ mod tests {
         );
     }
 
+    #[test]
+    fn ctrl_w_u_k_delete() {
+        assert_eq!(
+            key_action(&key_ctrl('w'), false),
+            Some(PromptAction::DeleteWord)
+        );
+        assert_eq!(
+            key_action(&key_ctrl('u'), false),
+            Some(PromptAction::DeleteToStart)
+        );
+        assert_eq!(
+            key_action(&key_ctrl('k'), true),
+            Some(PromptAction::DeleteToEnd)
+        );
+    }
+
+    #[test]
+    fn key_display_names_modifiers() {
+        assert_eq!(key_ctrl('d').to_string(), "Ctrl-D");
+        assert_eq!(key_char('x').to_string(), "x");
+        let key = Key {
+            name: KeyName::Return,
+            ctrl: false,
+            meta: true,
+        };
+        assert_eq!(key.to_string(), "Alt-Enter");
+        assert_eq!(Key::new(KeyName::PageDown).to_string(), "Page Down");
+        assert_eq!(Key::new(KeyName::Escape).to_string(), "Escape");
+    }
+
     #[test]
     fn ctrl_other_char_none() {
         assert_eq!(key_action(&key_ctrl('z'), false), None);
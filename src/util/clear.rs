@@ -1,20 +1,17 @@
 //! Clearing of prompt lines on the terminal.
 
-use crate::util::strip::strip_ansi;
+use crate::util::lines::display_width;
 use ansi_escapes::{CursorTo, EraseLine, EraseLines};
 
-fn width(s: &str) -> usize {
-    strip_ansi(s).chars().count()
-}
-
-/// Returns escape sequence to clear the prompt over `per_line` columns.
+/// Returns escape sequence to clear the prompt over `per_line` columns. Rows are counted by
+/// display width, as in [`lines_count`](crate::util::lines::lines_count).
 pub fn clear(prompt: &str, per_line: usize) -> String {
     if per_line == 0 {
         return format!("{}{}", EraseLine, CursorTo::AbsoluteX(0));
     }
     let mut rows = 0u16;
     for line in prompt.split('\n') {
-        let w = width(line);
+        let w = display_width(line);
         rows += 1 + (w.saturating_sub(1) / per_line) as u16;
     }
     format!("{}", EraseLines(rows))
@@ -27,7 +24,7 @@ mod tests {
     #[test]
     fn clear_zero_width_returns_erase_line() {
         let out = clear("hello", 0);
-        assert!(out.contains("\x1b[2K") || out.len() > 0);
+        assert!(out.contains("\x1b[2K") || !out.is_empty());
     }
 
     #[test]
@@ -80,6 +77,11 @@ mod tests {
         assert!(out_wrapped.len() >= out_single.len());
     }
 
+    #[test]
+    fn clear_counts_wide_characters_as_two_columns() {
+        assert_eq!(clear("日本語", 4), EraseLines(2).to_string());
+    }
+
     #[test]
     fn clear_multiline_row_count_greater_than_line_count() {
         let two_lines = clear("a\nb", 10);
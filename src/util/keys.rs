@@ -0,0 +1,462 @@
+//! Decoding of raw input bytes into keys.
+//!
+//! Understands CSI (`ESC [`) and SS3 (`ESC O`) sequences, xterm modifier parameters,
+//! Alt-prefixed keys, Ctrl-letters and UTF-8. A lone ESC is told apart from the start
+//! of a sequence by waiting a short time for the next byte.
+
+use crate::util::action::{Key, KeyName};
+use std::io::{self, BufRead};
+use std::time::Duration;
+
+/// Default time to wait after ESC before treating it as a lone Escape press.
+pub const ESCAPE_TIMEOUT: Duration = Duration::from_millis(50);
+
+/// Source of raw input bytes.
+pub trait ByteSource {
+    /// Reads the next byte, blocking. Returns `None` at end of input.
+    fn read_byte(&mut self) -> io::Result<Option<u8>>;
+
+    /// Reads the next byte if one arrives within `timeout`. Returns `None` on timeout or end
+    /// of input. Sources without a notion of time treat end of input as the timeout.
+    fn read_byte_timeout(&mut self, _timeout: Duration) -> io::Result<Option<u8>> {
+        self.read_byte()
+    }
+}
+
+impl<R: BufRead + ?Sized> ByteSource for R {
+    fn read_byte(&mut self) -> io::Result<Option<u8>> {
+        match read_byte(self) {
+            Ok(b) => Ok(Some(b)),
+            Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+/// Read a single byte from a BufRead. Blocks until one is available.
+pub(crate) fn read_byte<R: BufRead + ?Sized>(r: &mut R) -> io::Result<u8> {
+    let buf = r.fill_buf()?;
+    if buf.is_empty() {
+        return Err(io::Error::new(
+            io::ErrorKind::UnexpectedEof,
+            "unexpected end of input",
+        ));
+    }
+    let b = buf[0];
+    r.consume(1);
+    Ok(b)
+}
+
+fn key(name: KeyName) -> Key {
+    Key::new(name)
+}
+
+fn ctrl(c: char) -> Key {
+    Key {
+        name: KeyName::Char(c),
+        ctrl: true,
+        meta: false,
+    }
+}
+
+/// Turns a byte stream into keys.
+#[derive(Debug, Clone, Copy)]
+pub struct KeyDecoder {
+    /// How long to wait after ESC for the rest of a sequence.
+    pub escape_timeout: Duration,
+}
+
+impl Default for KeyDecoder {
+    fn default() -> Self {
+        Self {
+            escape_timeout: ESCAPE_TIMEOUT,
+        }
+    }
+}
+
+impl KeyDecoder {
+    /// Reads the next key. Returns `None` at end of input.
+    pub fn read_key<S: ByteSource + ?Sized>(&self, src: &mut S) -> io::Result<Option<Key>> {
+        match src.read_byte()? {
+            None => Ok(None),
+            Some(0x1b) => self.escape(src).map(Some),
+            Some(b) => self.plain(src, b).map(Some),
+        }
+    }
+
+    /// Decodes a key that does not start with ESC.
+    fn plain<S: ByteSource + ?Sized>(&self, src: &mut S, b: u8) -> io::Result<Key> {
+        Ok(match b {
+            b'\r' => key(KeyName::Return),
+            b'\n' => key(KeyName::Enter),
+            b'\t' => key(KeyName::Tab),
+            0x7f | 0x08 => key(KeyName::Backspace),
+            0x00 => ctrl(' '),
+            0x01..=0x1a => ctrl((b'a' + b - 1) as char),
+            0x1c..=0x1f => ctrl((b + 0x40) as char),
+            0x20..=0x7e => key(KeyName::Char(b as char)),
+            _ => key(self.utf8(src, b)?.map_or(KeyName::Unknown, KeyName::Char)),
+        })
+    }
+
+    /// Decodes what follows ESC: a lone Escape, a CSI or SS3 sequence, or an Alt-prefixed key.
+    fn escape<S: ByteSource + ?Sized>(&self, src: &mut S) -> io::Result<Key> {
+        match src.read_byte_timeout(self.escape_timeout)? {
+            None => Ok(key(KeyName::Escape)),
+            Some(b'[') => self.csi(src),
+            Some(b'O') => self.ss3(src),
+            Some(0x1b) => Ok(Key {
+                name: KeyName::Escape,
+                ctrl: false,
+                meta: true,
+            }),
+            Some(b) => {
+                let mut k = self.plain(src, b)?;
+                k.meta = true;
+                Ok(k)
+            }
+        }
+    }
+
+    /// Decodes `ESC [ params final`.
+    fn csi<S: ByteSource + ?Sized>(&self, src: &mut S) -> io::Result<Key> {
+        let mut params = Vec::new();
+        let fin = loop {
+            match src.read_byte_timeout(self.escape_timeout)? {
+                None => return Ok(key(KeyName::Unknown)),
+                Some(b @ 0x30..=0x3f) => params.push(b),
+                Some(b @ 0x40..=0x7e) => break b,
+                Some(_) => return Ok(key(KeyName::Unknown)),
+            }
+        };
+        let params = String::from_utf8_lossy(&params);
+        let nums: Vec<u32> = params.split(';').map(|p| p.parse().unwrap_or(0)).collect();
+        let (name, modifier) = match fin {
+            b'A' => (KeyName::Up, nums.get(1)),
+            b'B' => (KeyName::Down, nums.get(1)),
+            b'C' => (KeyName::Right, nums.get(1)),
+            b'D' => (KeyName::Left, nums.get(1)),
+            b'H' => (KeyName::Home, nums.get(1)),
+            b'F' => (KeyName::End, nums.get(1)),
+            b'~' => {
+                let name = match nums.first() {
+                    Some(1) | Some(7) => KeyName::Home,
+                    Some(4) | Some(8) => KeyName::End,
+                    Some(3) => KeyName::Delete,
+                    Some(5) => KeyName::PageUp,
+                    Some(6) => KeyName::PageDown,
+                    _ => KeyName::Unknown,
+                };
+                (name, nums.get(1))
+            }
+            _ => (KeyName::Unknown, None),
+        };
+        Ok(with_modifier(name, modifier.copied()))
+    }
+
+    /// Decodes `ESC O final`, sent for arrows and Home/End in application cursor mode.
+    fn ss3<S: ByteSource + ?Sized>(&self, src: &mut S) -> io::Result<Key> {
+        let name = match src.read_byte_timeout(self.escape_timeout)? {
+            Some(b'A') => KeyName::Up,
+            Some(b'B') => KeyName::Down,
+            Some(b'C') => KeyName::Right,
+            Some(b'D') => KeyName::Left,
+            Some(b'H') => KeyName::Home,
+            Some(b'F') => KeyName::End,
+            Some(b'M') => KeyName::Enter,
+            _ => KeyName::Unknown,
+        };
+        Ok(key(name))
+    }
+
+    /// Decodes a multi-byte UTF-8 character whose first byte is `lead`.
+    fn utf8<S: ByteSource + ?Sized>(&self, src: &mut S, lead: u8) -> io::Result<Option<char>> {
+        let len = match lead {
+            0xc0..=0xdf => 2,
+            0xe0..=0xef => 3,
+            0xf0..=0xf7 => 4,
+            _ => return Ok(None),
+        };
+        let mut buf = [lead, 0, 0, 0];
+        for slot in buf.iter_mut().take(len).skip(1) {
+            match src.read_byte()? {
+                Some(b) => *slot = b,
+                None => return Ok(None),
+            }
+        }
+        Ok(std::str::from_utf8(&buf[..len])
+            .ok()
+            .and_then(|s| s.chars().next()))
+    }
+}
+
+/// Applies an xterm modifier parameter (1 + bitmask of shift=1, alt=2, ctrl=4).
+fn with_modifier(name: KeyName, modifier: Option<u32>) -> Key {
+    let bits = modifier.unwrap_or(1).saturating_sub(1);
+    Key {
+        name,
+        ctrl: bits & 4 != 0,
+        meta: bits & 2 != 0,
+    }
+}
+
+/// Reads the next key with the default decoder. Returns `None` at end of input.
+pub fn read_key<R: BufRead + ?Sized>(r: &mut R) -> io::Result<Option<Key>> {
+    KeyDecoder::default().read_key(r)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::VecDeque;
+    use std::io::Cursor;
+
+    fn keys(input: &[u8]) -> Vec<Key> {
+        let mut r = Cursor::new(input);
+        let mut out = Vec::new();
+        while let Some(k) = read_key(&mut r).unwrap() {
+            out.push(k);
+        }
+        out
+    }
+
+    fn names(input: &[u8]) -> Vec<KeyName> {
+        keys(input).into_iter().map(|k| k.name).collect()
+    }
+
+    /// Byte source where `None` entries are pauses longer than any timeout.
+    struct Timed(VecDeque<Option<u8>>);
+
+    impl ByteSource for Timed {
+        fn read_byte(&mut self) -> io::Result<Option<u8>> {
+            loop {
+                match self.0.pop_front() {
+                    Some(Some(b)) => return Ok(Some(b)),
+                    Some(None) => continue,
+                    None => return Ok(None),
+                }
+            }
+        }
+
+        fn read_byte_timeout(&mut self, _timeout: Duration) -> io::Result<Option<u8>> {
+            match self.0.front() {
+                Some(None) => {
+                    self.0.pop_front();
+                    Ok(None)
+                }
+                _ => self.read_byte(),
+            }
+        }
+    }
+
+    #[test]
+    fn read_byte_returns_first_byte() {
+        let mut r = Cursor::new(b"ab");
+        assert_eq!(read_byte(&mut r).unwrap(), b'a');
+        assert_eq!(read_byte(&mut r).unwrap(), b'b');
+    }
+
+    #[test]
+    fn read_byte_eof_returns_err() {
+        let mut r = Cursor::new(b"");
+        assert!(read_byte(&mut r).is_err());
+    }
+
+    #[test]
+    fn read_key_eof_returns_none() {
+        let mut r = Cursor::new(b"");
+        assert!(read_key(&mut r).unwrap().is_none());
+    }
+
+    #[test]
+    fn read_key_printable_and_newline() {
+        assert_eq!(
+            names(b"hi\n"),
+            [KeyName::Char('h'), KeyName::Char('i'), KeyName::Enter]
+        );
+    }
+
+    #[test]
+    fn read_key_carriage_return_tab_backspace() {
+        assert_eq!(
+            names(b"\r\t\x7f\x08"),
+            [
+                KeyName::Return,
+                KeyName::Tab,
+                KeyName::Backspace,
+                KeyName::Backspace
+            ]
+        );
+    }
+
+    #[test]
+    fn read_key_csi_arrows() {
+        assert_eq!(
+            names(b"\x1b[A\x1b[B\x1b[C\x1b[D"),
+            [KeyName::Up, KeyName::Down, KeyName::Right, KeyName::Left]
+        );
+    }
+
+    #[test]
+    fn read_key_ss3_arrows_and_home_end() {
+        assert_eq!(
+            names(b"\x1bOA\x1bOB\x1bOC\x1bOD\x1bOH\x1bOF"),
+            [
+                KeyName::Up,
+                KeyName::Down,
+                KeyName::Right,
+                KeyName::Left,
+                KeyName::Home,
+                KeyName::End
+            ]
+        );
+    }
+
+    #[test]
+    fn read_key_home_end_variants() {
+        assert_eq!(
+            names(b"\x1b[H\x1b[F\x1b[1~\x1b[4~\x1b[7~\x1b[8~"),
+            [
+                KeyName::Home,
+                KeyName::End,
+                KeyName::Home,
+                KeyName::End,
+                KeyName::Home,
+                KeyName::End
+            ]
+        );
+    }
+
+    #[test]
+    fn read_key_delete_page_up_down() {
+        assert_eq!(
+            names(b"\x1b[3~\x1b[5~\x1b[6~"),
+            [KeyName::Delete, KeyName::PageUp, KeyName::PageDown]
+        );
+    }
+
+    #[test]
+    fn read_key_modifier_parameters() {
+        let k = keys(b"\x1b[1;5C");
+        assert_eq!(
+            k,
+            [Key {
+                name: KeyName::Right,
+                ctrl: true,
+                meta: false
+            }]
+        );
+        let k = keys(b"\x1b[3;3~");
+        assert_eq!(
+            k,
+            [Key {
+                name: KeyName::Delete,
+                ctrl: false,
+                meta: true
+            }]
+        );
+        let k = keys(b"\x1b[1;2A");
+        assert_eq!(k, [Key::new(KeyName::Up)]);
+    }
+
+    #[test]
+    fn read_key_unknown_csi_consumes_whole_sequence() {
+        assert_eq!(
+            names(b"\x1b[2~\x1b[15~x"),
+            [KeyName::Unknown, KeyName::Unknown, KeyName::Char('x')]
+        );
+    }
+
+    #[test]
+    fn read_key_alt_prefixed_keys_set_meta() {
+        let k = keys(b"\x1bb\x1b\x7f\x1b\x1b");
+        assert_eq!(
+            k,
+            [
+                Key {
+                    name: KeyName::Char('b'),
+                    ctrl: false,
+                    meta: true
+                },
+                Key {
+                    name: KeyName::Backspace,
+                    ctrl: false,
+                    meta: true
+                },
+                Key {
+                    name: KeyName::Escape,
+                    ctrl: false,
+                    meta: true
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn read_key_ctrl_letters() {
+        let k = keys(b"\x01\x03\x17\x1f");
+        let chars: Vec<_> = k.iter().map(|k| (k.name.clone(), k.ctrl)).collect();
+        assert_eq!(
+            chars,
+            [
+                (KeyName::Char('a'), true),
+                (KeyName::Char('c'), true),
+                (KeyName::Char('w'), true),
+                (KeyName::Char('_'), true)
+            ]
+        );
+    }
+
+    #[test]
+    fn read_key_utf8_char() {
+        assert_eq!(
+            names("日é🦀".as_bytes()),
+            [KeyName::Char('日'), KeyName::Char('é'), KeyName::Char('🦀')]
+        );
+    }
+
+    #[test]
+    fn read_key_alt_utf8_char() {
+        let k = keys("\x1bé".as_bytes());
+        assert_eq!(k[0].name, KeyName::Char('é'));
+        assert!(k[0].meta);
+    }
+
+    #[test]
+    fn read_key_truncated_utf8_is_unknown() {
+        assert_eq!(names(&[0xe6, 0x97]), [KeyName::Unknown]);
+    }
+
+    #[test]
+    fn read_key_lone_escape_at_end_of_input() {
+        assert_eq!(names(b"\x1b"), [KeyName::Escape]);
+    }
+
+    #[test]
+    fn read_key_truncated_csi_is_unknown() {
+        assert_eq!(names(b"\x1b[1;"), [KeyName::Unknown]);
+    }
+
+    #[test]
+    fn decoder_lone_escape_after_timeout() {
+        let mut src = Timed(VecDeque::from([Some(0x1b), None, Some(b'['), Some(b'A')]));
+        let d = KeyDecoder::default();
+        assert_eq!(d.read_key(&mut src).unwrap().unwrap().name, KeyName::Escape);
+        assert_eq!(
+            d.read_key(&mut src).unwrap().unwrap().name,
+            KeyName::Char('[')
+        );
+        assert_eq!(
+            d.read_key(&mut src).unwrap().unwrap().name,
+            KeyName::Char('A')
+        );
+    }
+
+    #[test]
+    fn decoder_sequence_within_timeout() {
+        let mut src = Timed(VecDeque::from([Some(0x1b), Some(b'['), Some(b'B')]));
+        let d = KeyDecoder::default();
+        assert_eq!(d.read_key(&mut src).unwrap().unwrap().name, KeyName::Down);
+        assert!(d.read_key(&mut src).unwrap().is_none());
+    }
+}
@@ -0,0 +1,329 @@
+//! Input buffer with a cursor, for one line or several separated by `\n`.
+
+use crate::util::action::PromptAction;
+use unicode_segmentation::UnicodeSegmentation;
+
+/// Editable input of one or more lines. The cursor is a byte offset that always sits between
+/// graphemes, so moving or deleting never splits an accented letter or emoji.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LineEditor {
+    text: String,
+    cursor: usize,
+    /// Column kept across consecutive [`LineEditor::move_line`] calls, so passing a short
+    /// line does not pull the cursor left for good.
+    goal: Option<usize>,
+}
+
+impl LineEditor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the current buffer.
+    pub fn text(&self) -> String {
+        self.text.clone()
+    }
+
+    /// Returns the buffer left of the cursor.
+    pub fn before_cursor(&self) -> String {
+        self.text[..self.cursor].to_string()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.text.is_empty()
+    }
+
+    /// Replaces the buffer and moves the cursor to the end.
+    pub fn set(&mut self, text: &str) {
+        self.text = text.to_string();
+        self.cursor = self.text.len();
+        self.goal = None;
+    }
+
+    /// Inserts a character at the cursor.
+    pub fn insert(&mut self, c: char) {
+        self.text.insert(self.cursor, c);
+        self.cursor += c.len_utf8();
+        self.goal = None;
+    }
+
+    /// Start of the grapheme left of the cursor.
+    fn prev(&self) -> Option<usize> {
+        self.text[..self.cursor]
+            .grapheme_indices(true)
+            .next_back()
+            .map(|(i, _)| i)
+    }
+
+    /// End of the grapheme right of the cursor.
+    fn next(&self) -> Option<usize> {
+        self.text[self.cursor..]
+            .graphemes(true)
+            .next()
+            .map(|g| self.cursor + g.len())
+    }
+
+    /// Start of the line holding the cursor.
+    fn line_start(&self) -> usize {
+        self.text[..self.cursor].rfind('\n').map_or(0, |i| i + 1)
+    }
+
+    /// End of the line holding the cursor, before its `\n`.
+    fn line_end(&self) -> usize {
+        self.text[self.cursor..]
+            .find('\n')
+            .map_or(self.text.len(), |i| self.cursor + i)
+    }
+
+    /// Moves the cursor to the line above (`up`) or below, keeping its column where the line
+    /// is long enough and returning to it on later lines. Returns `false` on the first or last
+    /// line.
+    pub fn move_line(&mut self, up: bool) -> bool {
+        let start = self.line_start();
+        let column = self
+            .goal
+            .unwrap_or_else(|| self.text[start..self.cursor].graphemes(true).count());
+        let target = if up {
+            if start == 0 {
+                return false;
+            }
+            self.text[..start - 1].rfind('\n').map_or(0, |i| i + 1)
+        } else {
+            let end = self.line_end();
+            if end == self.text.len() {
+                return false;
+            }
+            end + 1
+        };
+        let end = self.text[target..]
+            .find('\n')
+            .map_or(self.text.len(), |i| target + i);
+        self.cursor = self.text[target..end]
+            .grapheme_indices(true)
+            .nth(column)
+            .map_or(end, |(i, _)| target + i);
+        self.goal = Some(column);
+        true
+    }
+
+    /// Start of the word left of the cursor, skipping whitespace before it.
+    fn word_start(&self) -> usize {
+        let mut start = self.cursor;
+        let mut in_word = false;
+        for (i, g) in self.text[..self.cursor].grapheme_indices(true).rev() {
+            let space = g.chars().all(char::is_whitespace);
+            if space && in_word {
+                break;
+            }
+            in_word |= !space;
+            start = i;
+        }
+        start
+    }
+
+    /// Applies a cursor or deletion action. Returns `None` for actions that are not editing
+    /// actions, otherwise whether the buffer or cursor changed.
+    pub fn apply(&mut self, action: &PromptAction) -> Option<bool> {
+        let (start, end) = (self.line_start(), self.line_end());
+        let goal = self.goal.take();
+        let changed = match action {
+            PromptAction::First | PromptAction::Home => {
+                self.cursor = start;
+                true
+            }
+            PromptAction::Last | PromptAction::End => {
+                self.cursor = end;
+                true
+            }
+            PromptAction::Left => self.prev().map(|i| self.cursor = i).is_some(),
+            PromptAction::Right => self.next().map(|i| self.cursor = i).is_some(),
+            PromptAction::Delete => self.prev().map(|i| self.remove(i..self.cursor)).is_some(),
+            PromptAction::DeleteForward => {
+                self.next().map(|i| self.remove(self.cursor..i)).is_some()
+            }
+            PromptAction::DeleteWord if self.cursor > 0 => {
+                self.remove(self.word_start()..self.cursor);
+                true
+            }
+            PromptAction::DeleteToStart if self.cursor > start => {
+                self.remove(start..self.cursor);
+                true
+            }
+            PromptAction::DeleteToEnd if self.cursor < end => {
+                self.remove(self.cursor..end);
+                true
+            }
+            PromptAction::DeleteWord | PromptAction::DeleteToStart | PromptAction::DeleteToEnd => {
+                false
+            }
+            _ => {
+                self.goal = goal;
+                return None;
+            }
+        };
+        Some(changed)
+    }
+
+    /// Removes `range` and leaves the cursor at its start.
+    fn remove(&mut self, range: std::ops::Range<usize>) {
+        self.cursor = range.start;
+        self.text.replace_range(range, "");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn editor(s: &str) -> LineEditor {
+        let mut e = LineEditor::new();
+        e.set(s);
+        e
+    }
+
+    #[test]
+    fn insert_at_cursor() {
+        let mut e = editor("ac");
+        e.apply(&PromptAction::Left);
+        e.insert('b');
+        assert_eq!(e.text(), "abc");
+        assert_eq!(e.before_cursor(), "ab");
+    }
+
+    #[test]
+    fn home_end_first_last() {
+        let mut e = editor("abc");
+        assert_eq!(e.apply(&PromptAction::Home), Some(true));
+        assert_eq!(e.before_cursor(), "");
+        e.apply(&PromptAction::End);
+        assert_eq!(e.before_cursor(), "abc");
+        e.apply(&PromptAction::First);
+        assert_eq!(e.before_cursor(), "");
+        e.apply(&PromptAction::Last);
+        assert_eq!(e.before_cursor(), "abc");
+    }
+
+    #[test]
+    fn delete_backward_and_forward() {
+        let mut e = editor("abc");
+        e.apply(&PromptAction::Delete);
+        assert_eq!(e.text(), "ab");
+        e.apply(&PromptAction::Home);
+        e.apply(&PromptAction::DeleteForward);
+        assert_eq!(e.text(), "b");
+    }
+
+    #[test]
+    fn moves_past_bounds_report_no_change() {
+        let mut e = editor("a");
+        assert_eq!(e.apply(&PromptAction::Right), Some(false));
+        assert_eq!(e.apply(&PromptAction::DeleteForward), Some(false));
+        assert_eq!(e.apply(&PromptAction::DeleteToEnd), Some(false));
+        e.apply(&PromptAction::Home);
+        assert_eq!(e.apply(&PromptAction::Left), Some(false));
+        assert_eq!(e.apply(&PromptAction::Delete), Some(false));
+        assert_eq!(e.apply(&PromptAction::DeleteWord), Some(false));
+        assert_eq!(e.apply(&PromptAction::DeleteToStart), Some(false));
+    }
+
+    #[test]
+    fn non_editing_actions_return_none() {
+        let mut e = editor("a");
+        assert_eq!(e.apply(&PromptAction::Submit), None);
+        assert_eq!(e.apply(&PromptAction::Up), None);
+    }
+
+    #[test]
+    fn unicode_chars_are_single_positions() {
+        let mut e = editor("日本");
+        e.apply(&PromptAction::Delete);
+        assert_eq!(e.text(), "日");
+    }
+
+    #[test]
+    fn graphemes_move_and_delete_as_one() {
+        // "e" + combining acute, then a family emoji joined with ZWJs.
+        let mut e = editor("ae\u{301}👨\u{200d}👩\u{200d}👧");
+        e.apply(&PromptAction::Delete);
+        assert_eq!(e.text(), "ae\u{301}");
+        e.apply(&PromptAction::Left);
+        assert_eq!(e.before_cursor(), "a");
+        e.apply(&PromptAction::DeleteForward);
+        assert_eq!(e.text(), "a");
+    }
+
+    #[test]
+    fn combining_mark_joins_the_grapheme_before_it() {
+        let mut e = editor("e");
+        e.insert('\u{301}');
+        e.apply(&PromptAction::Left);
+        assert_eq!(e.before_cursor(), "");
+    }
+
+    #[test]
+    fn delete_word_skips_trailing_spaces() {
+        let mut e = editor("git commit  ");
+        assert_eq!(e.apply(&PromptAction::DeleteWord), Some(true));
+        assert_eq!(e.text(), "git ");
+        e.apply(&PromptAction::DeleteWord);
+        assert_eq!(e.text(), "");
+    }
+
+    #[test]
+    fn line_keys_stay_on_the_cursor_line() {
+        let mut e = editor("first\nsecond\nthird");
+        e.apply(&PromptAction::Home);
+        assert_eq!(e.before_cursor(), "first\nsecond\n");
+        assert!(e.move_line(true));
+        e.apply(&PromptAction::End);
+        assert_eq!(e.before_cursor(), "first\nsecond");
+        e.apply(&PromptAction::DeleteToStart);
+        assert_eq!(e.text(), "first\n\nthird");
+        e.insert('x');
+        e.apply(&PromptAction::Home);
+        e.apply(&PromptAction::DeleteToEnd);
+        assert_eq!(e.text(), "first\n\nthird");
+    }
+
+    #[test]
+    fn move_line_keeps_the_column() {
+        let mut e = editor("long line\nab\nlonger");
+        e.apply(&PromptAction::Home);
+        e.apply(&PromptAction::Right);
+        e.apply(&PromptAction::Right);
+        e.apply(&PromptAction::Right);
+        assert!(e.move_line(true));
+        assert_eq!(e.before_cursor(), "long line\nab");
+        assert!(e.move_line(true));
+        assert_eq!(e.before_cursor(), "lon");
+        assert!(!e.move_line(true));
+        assert!(e.move_line(false));
+        assert!(e.move_line(false));
+        assert_eq!(e.before_cursor(), "long line\nab\nlon");
+        assert!(!e.move_line(false));
+    }
+
+    #[test]
+    fn editing_resets_the_kept_column() {
+        let mut e = editor("long line\nab\nlonger");
+        assert!(e.move_line(true));
+        assert_eq!(e.before_cursor(), "long line\nab");
+        e.apply(&PromptAction::Left);
+        assert!(e.move_line(true));
+        assert_eq!(e.before_cursor(), "l");
+    }
+
+    #[test]
+    fn delete_to_start_and_end_of_line() {
+        let mut e = editor("hello world");
+        for _ in 0..5 {
+            e.apply(&PromptAction::Left);
+        }
+        e.apply(&PromptAction::DeleteToEnd);
+        assert_eq!(e.text(), "hello ");
+        e.apply(&PromptAction::Left);
+        e.apply(&PromptAction::DeleteToStart);
+        assert_eq!(e.text(), " ");
+        assert_eq!(e.before_cursor(), "");
+    }
+}
@@ -1,8 +1,17 @@
 //! Line count for styled text.
 
 use crate::util::strip::strip_ansi;
+use unicode_width::UnicodeWidthStr;
 
-/// Returns the number of lines when wrapped to `per_line` width.
+/// Returns the columns `msg` takes on screen: escape codes take none, wide characters two.
+pub fn display_width(msg: &str) -> usize {
+    strip_ansi(msg).width()
+}
+
+/// Returns the number of lines when wrapped to `per_line` columns.
+///
+/// Lines are measured in terminal columns, so wide characters such as CJK take two. Earlier
+/// versions counted chars, which undercounted rows for wide text.
 pub fn lines_count(msg: &str, per_line: usize) -> usize {
     let s = strip_ansi(msg);
     if s.is_empty() {
@@ -11,15 +20,20 @@ pub fn lines_count(msg: &str, per_line: usize) -> usize {
     if per_line == 0 {
         return s.split('\n').count();
     }
-    s.split('\n')
-        .map(|l| l.chars().count().div_ceil(per_line))
-        .sum()
+    s.split('\n').map(|l| l.width().div_ceil(per_line)).sum()
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn display_width_counts_columns() {
+        assert_eq!(display_width("\x1b[31mab\x1b[0m"), 2);
+        assert_eq!(display_width("日本"), 4);
+        assert_eq!(display_width("e\u{301}"), 1);
+    }
+
     #[test]
     fn lines_count_empty() {
         assert_eq!(lines_count("", 80), 0);
@@ -82,11 +96,28 @@ mod tests {
 
     #[test]
     fn lines_count_unicode_chars_count_not_bytes() {
-        assert_eq!(lines_count("中文", 1), 2);
+        assert_eq!(lines_count("éè", 1), 2);
     }
 
     #[test]
     fn lines_count_unicode_with_ansi() {
-        assert_eq!(lines_count("\x1b[31m中\x1b[0m文", 1), 2);
+        assert_eq!(lines_count("\x1b[31mé\x1b[0mè", 1), 2);
+    }
+
+    #[test]
+    fn lines_count_wide_chars_take_two_columns() {
+        assert_eq!(lines_count("中文", 1), 4);
+        assert_eq!(lines_count("中文", 2), 2);
+        assert_eq!(lines_count("中文", 4), 1);
+    }
+
+    #[test]
+    fn lines_count_combining_marks_take_no_column() {
+        assert_eq!(lines_count("e\u{301}e\u{301}", 2), 1);
+    }
+
+    #[test]
+    fn lines_count_wide_chars_with_ansi() {
+        assert_eq!(lines_count("\x1b[31m中\x1b[0m文", 2), 2);
     }
 }
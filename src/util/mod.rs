@@ -3,13 +3,16 @@
 pub mod action;
 pub mod clear;
 pub mod figures;
+pub mod keys;
+pub mod line_editor;
 pub mod lines;
 pub mod strip;
 pub mod style;
 
-pub use action::key_action;
+pub use action::{Key, KeyName, PromptAction, key_action};
 pub use clear::clear;
 pub use figures::Figures;
+pub use keys::{ByteSource, KeyDecoder, read_key};
 pub use lines::lines_count;
 pub use strip::strip_ansi;
 pub use style::{InputStyle, render_style};
@@ -3,9 +3,15 @@
 use crate::util::figures::Figures;
 use colour::{write_cyan, write_gray, write_green, write_red, write_yellow};
 use std::io::Write;
+use unicode_segmentation::UnicodeSegmentation;
 
 /// Input display style (default, password, or invisible).
 #[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "lowercase")
+)]
 pub enum InputStyle {
     Default,
     Password,
@@ -20,7 +26,7 @@ pub struct StyleTransform {
 impl StyleTransform {
     pub fn render(&self, input: &str, style: InputStyle) -> String {
         match style {
-            InputStyle::Password => "*".repeat(input.len()),
+            InputStyle::Password => "*".repeat(input.graphemes(true).count()),
             InputStyle::Invisible => String::new(),
             InputStyle::Default => input.to_string(),
         }
@@ -83,6 +89,16 @@ mod tests {
         assert_eq!(transform.render("secret", InputStyle::Password), "******");
     }
 
+    #[test]
+    fn render_style_password_masks_one_star_per_grapheme() {
+        let (transform, _) = render_style(InputStyle::Password);
+        assert_eq!(
+            transform.render("pässwörd", InputStyle::Password),
+            "********"
+        );
+        assert_eq!(transform.render("e\u{301}日", InputStyle::Password), "**");
+    }
+
     #[test]
     fn render_style_invisible_empty() {
         let (transform, scale) = render_style(InputStyle::Invisible);